@@ -0,0 +1,345 @@
+pub mod aggregate {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::analysis::inference::inference::{
+        bootstrap_difference, bootstrap_mean, mann_whitney, mean, Interval, MannWhitney,
+    };
+    use crate::simulation::{results::results::RunSummary, simulation::simulation::Algorithms};
+
+    #[derive(Copy, Clone)]
+    pub enum Metric {
+        MEAN,
+        P99,
+    }
+
+    /// The repetitions of one configuration run with one algorithm.
+    pub struct ConfigurationGroup {
+        /// The configuration without its algorithm, shared by the groups it compares.
+        pub configuration: String,
+        pub algorithm: String,
+        pub runs: Vec<RunSummary>,
+    }
+
+    pub struct AlgorithmEstimate {
+        pub algorithm: String,
+        pub runs: usize,
+        pub mean: f64,
+        pub mean_interval: Option<Interval>,
+        pub p99: f64,
+        pub p99_interval: Option<Interval>,
+    }
+
+    /// How `algorithm` did against `baseline` on one metric.
+    pub struct Comparison {
+        pub algorithm: String,
+        pub baseline: String,
+        pub metric: Metric,
+        /// `algorithm` minus `baseline`.
+        pub difference: f64,
+        pub interval: Option<Interval>,
+        /// The difference as a share of the baseline.
+        pub relative: Option<f64>,
+        pub test: Option<MannWhitney>,
+        pub significant: bool,
+    }
+
+    pub struct ConfigurationEstimate {
+        pub configuration: String,
+        pub algorithms: Vec<AlgorithmEstimate>,
+        pub comparisons: Vec<Comparison>,
+    }
+
+    pub struct Analysis {
+        pub confidence: f64,
+        pub resamples: usize,
+        pub seed: u64,
+        pub baseline: Algorithms,
+    }
+
+    impl Metric {
+        pub fn get_name(&self) -> &'static str {
+            match self {
+                Metric::MEAN => "mean",
+                Metric::P99 => "p99",
+            }
+        }
+
+        pub fn extract(&self, summary: &RunSummary) -> f64 {
+            match self {
+                Metric::MEAN => summary.mean_response_time,
+                Metric::P99 => summary.p99_response_time,
+            }
+        }
+    }
+
+    /// `algorithm=Naive,spin_speed=10` -> `spin_speed=10`
+    pub fn without_algorithm(configuration: &str) -> String {
+        configuration
+            .split(',')
+            .filter(|part| {
+                let name = part.split('=').next().unwrap_or("");
+                name != "algorithm" && !name.ends_with(".algorithm")
+            })
+            .collect::<Vec<&str>>()
+            .join(",")
+    }
+
+    /// Groups runs by configuration and algorithm, keeping the order they were read in.
+    pub fn group_runs(runs: Vec<RunSummary>) -> Vec<ConfigurationGroup> {
+        let mut groups: Vec<ConfigurationGroup> = Vec::new();
+        for run in runs {
+            let configuration = without_algorithm(&run.configuration);
+            match groups
+                .iter_mut()
+                .find(|g| g.configuration == configuration && g.algorithm == run.algorithm)
+            {
+                Some(group) => group.runs.push(run),
+                None => groups.push(ConfigurationGroup {
+                    configuration: configuration,
+                    algorithm: run.algorithm.clone(),
+                    runs: vec![run],
+                }),
+            }
+        }
+
+        groups
+    }
+
+    impl ConfigurationGroup {
+        pub fn get_values(&self, metric: Metric) -> Vec<f64> {
+            self.runs.iter().map(|run| metric.extract(run)).collect()
+        }
+    }
+
+    pub fn format_relative(relative: Option<f64>) -> String {
+        match relative {
+            Some(relative) => format!("{:+.2}%", 100.0 * relative),
+            None => "n/a".to_owned(),
+        }
+    }
+
+    impl Comparison {
+        pub fn format_test(&self) -> String {
+            match &self.test {
+                Some(test) => format!(
+                    "U = {:.1}, p = {:.4} ({})",
+                    test.u,
+                    test.p_value,
+                    if self.significant {
+                        "significant"
+                    } else {
+                        "not significant"
+                    }
+                ),
+                None => "n/a".to_owned(),
+            }
+        }
+    }
+
+    pub fn format_interval(interval: Option<Interval>) -> String {
+        match interval {
+            Some(interval) => format!("[{:.2}, {:.2}]", interval.low, interval.high),
+            None => "[n/a]".to_owned(),
+        }
+    }
+
+    impl Analysis {
+        pub fn default() -> Analysis {
+            Analysis {
+                confidence: 0.95,
+                resamples: 10000,
+                seed: 1,
+                baseline: Algorithms::NAIVE,
+            }
+        }
+
+        pub fn validate(&self) -> Result<(), String> {
+            if !(self.confidence > 0.0 && self.confidence < 1.0) {
+                return Err("The confidence level has to be between 0 and 1".to_owned());
+            }
+
+            Ok(())
+        }
+
+        fn compare(
+            &self,
+            baseline: &ConfigurationGroup,
+            other: &ConfigurationGroup,
+            metric: Metric,
+            rng: &mut StdRng,
+        ) -> Comparison {
+            let first = baseline.get_values(metric);
+            let second = other.get_values(metric);
+            let difference = mean(&second) - mean(&first);
+            let test = mann_whitney(&first, &second);
+
+            Comparison {
+                algorithm: other.algorithm.clone(),
+                baseline: baseline.algorithm.clone(),
+                metric: metric,
+                difference: difference,
+                relative: if mean(&first) != 0.0 {
+                    Some(difference / mean(&first))
+                } else {
+                    None
+                },
+                interval: bootstrap_difference(
+                    &first,
+                    &second,
+                    self.confidence,
+                    self.resamples,
+                    rng,
+                ),
+                significant: test
+                    .as_ref()
+                    .is_some_and(|test| test.p_value < 1.0 - self.confidence),
+                test: test,
+            }
+        }
+
+        /// Estimates every configuration, in the order the groups were read in.
+        pub fn evaluate(&self, groups: &[ConfigurationGroup]) -> Vec<ConfigurationEstimate> {
+            let mut rng = StdRng::seed_from_u64(self.seed);
+            let mut estimates: Vec<ConfigurationEstimate> = Vec::new();
+
+            for group in groups.iter() {
+                if estimates
+                    .iter()
+                    .any(|e| e.configuration == group.configuration)
+                {
+                    continue;
+                }
+
+                let members: Vec<&ConfigurationGroup> = groups
+                    .iter()
+                    .filter(|g| g.configuration == group.configuration)
+                    .collect();
+
+                let mut algorithms = Vec::new();
+                for member in members.iter() {
+                    let means = member.get_values(Metric::MEAN);
+                    let tails = member.get_values(Metric::P99);
+                    algorithms.push(AlgorithmEstimate {
+                        algorithm: member.algorithm.clone(),
+                        runs: member.runs.len(),
+                        mean: mean(&means),
+                        mean_interval: bootstrap_mean(
+                            &means,
+                            self.confidence,
+                            self.resamples,
+                            &mut rng,
+                        ),
+                        p99: mean(&tails),
+                        p99_interval: bootstrap_mean(
+                            &tails,
+                            self.confidence,
+                            self.resamples,
+                            &mut rng,
+                        ),
+                    });
+                }
+
+                let mut comparisons = Vec::new();
+                let baseline_name = self.baseline.get_name();
+                if let Some(baseline) = members.iter().find(|m| m.algorithm == baseline_name) {
+                    for other in members.iter().filter(|m| m.algorithm != baseline_name) {
+                        for metric in [Metric::MEAN, Metric::P99] {
+                            comparisons.push(self.compare(baseline, other, metric, &mut rng));
+                        }
+                    }
+                }
+
+                estimates.push(ConfigurationEstimate {
+                    configuration: group.configuration.clone(),
+                    algorithms: algorithms,
+                    comparisons: comparisons,
+                });
+            }
+
+            estimates
+        }
+
+        pub fn show(&self, groups: &[ConfigurationGroup]) {
+            let level = format!("{}% CI", 100.0 * self.confidence);
+
+            for estimate in self.evaluate(groups).iter() {
+                if estimate.configuration.is_empty() {
+                    println!("Configuration <default>");
+                } else {
+                    println!("Configuration {}", estimate.configuration);
+                }
+                println!(
+                    "\t{:<10} {:>5} {:>12} {:>24} {:>12} {:>24}",
+                    "algorithm", "runs", "mean", &level, "p99", &level
+                );
+                for algorithm in estimate.algorithms.iter() {
+                    println!(
+                        "\t{:<10} {:>5} {:>12.2} {:>24} {:>12.2} {:>24}",
+                        algorithm.algorithm,
+                        algorithm.runs,
+                        algorithm.mean,
+                        format_interval(algorithm.mean_interval),
+                        algorithm.p99,
+                        format_interval(algorithm.p99_interval)
+                    );
+                }
+
+                for comparison in estimate.comparisons.iter() {
+                    println!(
+                        "\t{} vs {}, {}: difference {:.2} {} ({}), Mann-Whitney {}",
+                        comparison.algorithm,
+                        comparison.baseline,
+                        comparison.metric.get_name(),
+                        comparison.difference,
+                        format_interval(comparison.interval),
+                        format_relative(comparison.relative),
+                        comparison.format_test()
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn configurations_are_compared_without_their_algorithm() {
+            assert_eq!(
+                without_algorithm("algorithm=Naive,spin_speed=10"),
+                "spin_speed=10"
+            );
+            assert_eq!(
+                without_algorithm("disk.tracks=100,scheduler.algorithm=elevator,repetition=2"),
+                "disk.tracks=100,repetition=2"
+            );
+            assert_eq!(without_algorithm("scheduler.algorithm=naive"), "");
+        }
+
+        #[test]
+        fn confidence_is_strictly_between_zero_and_one() {
+            let mut analysis = Analysis::default();
+            assert!(analysis.validate().is_ok());
+
+            for confidence in [0.0, 1.0, 1.5, f64::NAN] {
+                analysis.confidence = confidence;
+                assert!(analysis.validate().is_err());
+            }
+        }
+
+        #[test]
+        fn missing_estimates_are_written_as_not_available() {
+            assert_eq!(format_relative(Some(-0.1234)), "-12.34%");
+            assert_eq!(format_relative(None), "n/a");
+            assert_eq!(
+                format_interval(Some(Interval {
+                    low: 1.0,
+                    high: 2.5
+                })),
+                "[1.00, 2.50]"
+            );
+            assert_eq!(format_interval(None), "[n/a]");
+        }
+    }
+}
@@ -0,0 +1,548 @@
+pub mod charts {
+    use std::{error::Error, fs, path::Path, sync::Once};
+
+    use plotters::{
+        coord::Shift,
+        prelude::*,
+        style::{register_font, FontStyle},
+    };
+    use serde::{Deserialize, Serialize};
+
+    use crate::simulation::simulation::simulation::SimulationResult;
+
+    #[derive(Serialize, Deserialize, Copy, Clone)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ChartFormat {
+        SVG,
+        PNG,
+    }
+
+    #[derive(Copy, Clone)]
+    pub enum ChartKind {
+        CDF,
+        HISTOGRAM,
+        TRACE,
+        HEATMAP,
+    }
+
+    /// Values of one group, e.g. the response times of one algorithm.
+    pub struct Series {
+        pub label: String,
+        pub values: Vec<u32>,
+    }
+
+    /// A line through `(x, y)` points, e.g. the head position over time.
+    pub struct Trace {
+        pub label: String,
+        pub points: Vec<(u32, u32)>,
+    }
+
+    type ChartResult = Result<(), Box<dyn Error>>;
+
+    const SIZE: (u32, u32) = (1024, 640);
+    /// Longer lines are thinned out to about this many points.
+    const MAX_POINTS: usize = 2000;
+    const HEATMAP_COLUMNS: u32 = 100;
+    const HEATMAP_ROWS: u32 = 50;
+
+    static FONTS: Once = Once::new();
+
+    impl ChartFormat {
+        pub fn from_name(name: &str) -> Result<ChartFormat, String> {
+            match name.trim().to_lowercase().as_str() {
+                "svg" => Ok(ChartFormat::SVG),
+                "png" => Ok(ChartFormat::PNG),
+                _ => Err(format!(
+                    "Unknown chart format `{}`, expected `svg` or `png`",
+                    name
+                )),
+            }
+        }
+
+        pub fn from_path(path: &str) -> Result<ChartFormat, String> {
+            let extension = path.rsplit_once('.').map(|(_, e)| e).unwrap_or("");
+            ChartFormat::from_name(extension)
+                .map_err(|_| format!("{}: charts are written as .svg or .png files", path))
+        }
+
+        pub fn get_extension(&self) -> &'static str {
+            match self {
+                ChartFormat::SVG => "svg",
+                ChartFormat::PNG => "png",
+            }
+        }
+    }
+
+    impl ChartKind {
+        pub fn from_name(name: &str) -> Result<ChartKind, String> {
+            match name.trim().to_lowercase().as_str() {
+                "cdf" => Ok(ChartKind::CDF),
+                "histogram" => Ok(ChartKind::HISTOGRAM),
+                "trace" => Ok(ChartKind::TRACE),
+                "heatmap" => Ok(ChartKind::HEATMAP),
+                _ => Err(format!(
+                    "Unknown chart `{}`, expected `cdf`, `histogram`, `trace` or `heatmap`",
+                    name
+                )),
+            }
+        }
+    }
+
+    /// The font is compiled in, so charts render the same without any
+    /// fonts installed on the machine.
+    fn register_fonts() {
+        FONTS.call_once(|| {
+            if register_font("sans-serif", FontStyle::Normal, notosans::REGULAR_TTF).is_err() {
+                panic!("There was an error while loading the chart font");
+            }
+        });
+    }
+
+    fn render<S, P>(path: &str, svg: S, png: P) -> Result<(), String>
+    where
+        S: FnOnce(&DrawingArea<SVGBackend, Shift>) -> ChartResult,
+        P: FnOnce(&DrawingArea<BitMapBackend, Shift>) -> ChartResult,
+    {
+        register_fonts();
+
+        let result = match ChartFormat::from_path(path)? {
+            ChartFormat::SVG => {
+                let root = SVGBackend::new(path, SIZE).into_drawing_area();
+                svg(&root).and_then(|_| Ok(root.present()?))
+            }
+            ChartFormat::PNG => {
+                let root = BitMapBackend::new(path, SIZE).into_drawing_area();
+                png(&root).and_then(|_| Ok(root.present()?))
+            }
+        };
+
+        result.map_err(|e| format!("Could not draw {}: {}", path, e))
+    }
+
+    fn render_svg<S>(svg: S) -> Result<String, String>
+    where
+        S: FnOnce(&DrawingArea<SVGBackend, Shift>) -> ChartResult,
+    {
+        register_fonts();
+
+        let mut content = String::new();
+        {
+            let root = SVGBackend::with_string(&mut content, SIZE).into_drawing_area();
+            svg(&root)
+                .and_then(|_| Ok(root.present()?))
+                .map_err(|e| format!("Could not draw a chart: {}", e))?;
+        }
+
+        Ok(content)
+    }
+
+    fn thin_out<T: Copy>(points: &[T]) -> Vec<T> {
+        let step = (points.len() / MAX_POINTS).max(1);
+        points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i % step == 0 || i + 1 == points.len())
+            .map(|(_, &point)| point)
+            .collect()
+    }
+
+    fn cdf<DB: DrawingBackend>(root: &DrawingArea<DB, Shift>, series: &[Series]) -> ChartResult
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        let max = series
+            .iter()
+            .flat_map(|s| s.values.iter())
+            .max()
+            .map_or(1, |&max| max.max(1));
+
+        let mut chart = ChartBuilder::on(root)
+            .caption("Response time CDF", ("sans-serif", 24))
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(56)
+            .build_cartesian_2d(0..max, 0.0..1.0)?;
+        chart
+            .configure_mesh()
+            .x_desc("response time (ticks)")
+            .y_desc("share of requests")
+            .draw()?;
+
+        for (i, s) in series.iter().enumerate() {
+            let mut sorted = s.values.clone();
+            sorted.sort_unstable();
+            let n = sorted.len() as f64;
+            let points: Vec<(u32, f64)> = sorted
+                .iter()
+                .enumerate()
+                .map(|(rank, &value)| (value, (rank + 1) as f64 / n))
+                .collect();
+
+            let color = Palette99::pick(i).to_rgba();
+            chart
+                .draw_series(LineSeries::new(thin_out(&points), color.stroke_width(2)))?
+                .label(s.label.as_str())
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::LowerRight)
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+        Ok(())
+    }
+
+    fn histogram<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        series: &[Series],
+        bins: u32,
+    ) -> ChartResult
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        let bins = bins.max(1);
+        let max = series
+            .iter()
+            .flat_map(|s| s.values.iter())
+            .max()
+            .map_or(1, |&max| max.max(1));
+        let width = max / bins + 1;
+
+        let counts: Vec<Vec<u32>> = series
+            .iter()
+            .map(|s| {
+                let mut counts = vec![0; bins as usize];
+                for &value in s.values.iter() {
+                    counts[(value / width) as usize] += 1;
+                }
+                counts
+            })
+            .collect();
+        let highest = counts.iter().flatten().max().map_or(1, |&c| c.max(1));
+
+        let mut chart = ChartBuilder::on(root)
+            .caption("Response time histogram", ("sans-serif", 24))
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(56)
+            .build_cartesian_2d(0..bins * width, 0..highest + highest / 10 + 1)?;
+        chart
+            .configure_mesh()
+            .x_desc("response time (ticks)")
+            .y_desc("requests")
+            .draw()?;
+
+        for (i, (s, counts)) in series.iter().zip(counts.iter()).enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            chart
+                .draw_series(counts.iter().enumerate().map(|(bin, &count)| {
+                    let low = bin as u32 * width;
+                    Rectangle::new([(low, 0), (low + width, count)], color.mix(0.5).filled())
+                }))?
+                .label(s.label.as_str())
+                .legend(move |(x, y)| {
+                    Rectangle::new([(x, y - 5), (x + 15, y + 5)], color.filled())
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+        Ok(())
+    }
+
+    fn head_trace<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        traces: &[Trace],
+        max_track: u32,
+    ) -> ChartResult
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        let end = traces
+            .iter()
+            .flat_map(|t| t.points.iter())
+            .map(|&(time, _)| time)
+            .max()
+            .map_or(1, |end| end.max(1));
+
+        let mut chart = ChartBuilder::on(root)
+            .caption("Head position", ("sans-serif", 24))
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(56)
+            .build_cartesian_2d(0..end, 0..max_track.max(1))?;
+        chart
+            .configure_mesh()
+            .x_desc("time (ticks)")
+            .y_desc("track")
+            .draw()?;
+
+        for (i, trace) in traces.iter().enumerate() {
+            let color = Palette99::pick(i).to_rgba();
+            chart
+                .draw_series(LineSeries::new(
+                    thin_out(&trace.points),
+                    color.stroke_width(1),
+                ))?
+                .label(trace.label.as_str())
+                .legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+                });
+        }
+
+        if traces.len() > 1 {
+            chart
+                .configure_series_labels()
+                .background_style(WHITE.mix(0.8))
+                .border_style(BLACK)
+                .draw()?;
+        }
+        Ok(())
+    }
+
+    fn heatmap<DB: DrawingBackend>(
+        root: &DrawingArea<DB, Shift>,
+        accesses: &[(u32, u32)],
+        max_track: u32,
+    ) -> ChartResult
+    where
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        let max_track = max_track.max(1);
+        let end = accesses
+            .iter()
+            .map(|&(time, _)| time)
+            .max()
+            .map_or(1, |end| end.max(1));
+        let column_width = end / HEATMAP_COLUMNS + 1;
+        let row_height = max_track / HEATMAP_ROWS + 1;
+
+        let mut cells = vec![0u32; (HEATMAP_COLUMNS * HEATMAP_ROWS) as usize];
+        for &(time, track) in accesses.iter() {
+            let column = (time / column_width).min(HEATMAP_COLUMNS - 1);
+            let row = (track.min(max_track) / row_height).min(HEATMAP_ROWS - 1);
+            cells[(row * HEATMAP_COLUMNS + column) as usize] += 1;
+        }
+        let highest = cells.iter().max().map_or(1, |&c| c.max(1));
+
+        let caption = format!("Track accesses (darkest = {} per cell)", highest);
+        let mut chart = ChartBuilder::on(root)
+            .caption(caption, ("sans-serif", 24))
+            .margin(16)
+            .x_label_area_size(40)
+            .y_label_area_size(56)
+            .build_cartesian_2d(
+                0..column_width * HEATMAP_COLUMNS,
+                0..row_height * HEATMAP_ROWS,
+            )?;
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .x_desc("time (ticks)")
+            .y_desc("track")
+            .draw()?;
+
+        chart.draw_series(cells.iter().enumerate().filter(|(_, &c)| c > 0).map(
+            |(cell, &count)| {
+                let row = cell as u32 / HEATMAP_COLUMNS;
+                let column = cell as u32 % HEATMAP_COLUMNS;
+                let fade = (255.0 * (1.0 - count as f64 / highest as f64)) as u8;
+
+                Rectangle::new(
+                    [
+                        (column * column_width, row * row_height),
+                        ((column + 1) * column_width, (row + 1) * row_height),
+                    ],
+                    RGBColor(200u8.saturating_add(fade / 5), fade, fade).filled(),
+                )
+            },
+        ))?;
+        Ok(())
+    }
+
+    /// Cumulative distribution of each series, on one set of axes.
+    pub fn draw_cdf(path: &str, series: &[Series]) -> Result<(), String> {
+        render(path, |root| cdf(root, series), |root| cdf(root, series))
+    }
+
+    /// The CDF as an SVG document, for embedding.
+    pub fn cdf_svg(series: &[Series]) -> Result<String, String> {
+        render_svg(|root| cdf(root, series))
+    }
+
+    pub fn draw_histogram(path: &str, series: &[Series], bins: u32) -> Result<(), String> {
+        render(
+            path,
+            |root| histogram(root, series, bins),
+            |root| histogram(root, series, bins),
+        )
+    }
+
+    pub fn histogram_svg(series: &[Series], bins: u32) -> Result<String, String> {
+        render_svg(|root| histogram(root, series, bins))
+    }
+
+    /// Traces of `(time, track)` points.
+    pub fn draw_head_trace(path: &str, traces: &[Trace], max_track: u32) -> Result<(), String> {
+        render(
+            path,
+            |root| head_trace(root, traces, max_track),
+            |root| head_trace(root, traces, max_track),
+        )
+    }
+
+    /// How often each region of the disk was accessed over time, from
+    /// `(time, track)` pairs.
+    pub fn draw_heatmap(path: &str, accesses: &[(u32, u32)], max_track: u32) -> Result<(), String> {
+        render(
+            path,
+            |root| heatmap(root, accesses, max_track),
+            |root| heatmap(root, accesses, max_track),
+        )
+    }
+
+    /// Draws every chart of one simulation into `directory` and returns the
+    /// paths written.
+    pub fn draw_run_charts(
+        directory: &str,
+        format: ChartFormat,
+        label: &str,
+        result: &SimulationResult,
+        max_track: u32,
+    ) -> Result<Vec<String>, String> {
+        fs::create_dir_all(directory)
+            .map_err(|e| format!("Could not create chart directory {}: {}", directory, e))?;
+        let path = |name: &str| {
+            Path::new(directory)
+                .join(format!("{}.{}", name, format.get_extension()))
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let series = [Series {
+            label: label.to_owned(),
+            values: result.response_times.clone(),
+        }];
+        let accesses: Vec<(u32, u32)> = result
+            .tasks
+            .iter()
+            .map(|task| (task.completion, task.track))
+            .collect();
+
+        let mut written = vec![path("response_cdf"), path("response_histogram")];
+        draw_cdf(&written[0], &series)?;
+        draw_histogram(&written[1], &series, 50)?;
+
+        written.push(path("track_heatmap"));
+        draw_heatmap(&written[2], &accesses, max_track)?;
+
+        if !result.samples.is_empty() {
+            let trace = Trace {
+                label: label.to_owned(),
+                points: result.samples.iter().map(|s| (s.time, s.track)).collect(),
+            };
+            written.push(path("head_trace"));
+            draw_head_trace(&written[3], &[trace], max_track)?;
+        }
+
+        Ok(written)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn series(label: &str, values: Vec<u32>) -> Series {
+            Series {
+                label: label.to_owned(),
+                values,
+            }
+        }
+
+        #[test]
+        fn formats_are_taken_from_the_extension() {
+            assert!(matches!(
+                ChartFormat::from_path("charts/cdf.SVG"),
+                Ok(ChartFormat::SVG)
+            ));
+            assert!(matches!(
+                ChartFormat::from_path("cdf.png"),
+                Ok(ChartFormat::PNG)
+            ));
+            assert!(ChartFormat::from_path("cdf.jpg").is_err());
+            assert!(ChartFormat::from_path("cdf").is_err());
+            assert!(ChartKind::from_name("Heatmap").is_ok());
+            assert!(ChartKind::from_name("pie").is_err());
+        }
+
+        #[test]
+        fn long_lines_are_thinned_out_but_keep_their_ends() {
+            let points: Vec<u32> = (0..5001).collect();
+            let thinned = thin_out(&points);
+
+            assert!(thinned.len() <= 2 * MAX_POINTS);
+            assert_eq!(thinned[0], 0);
+            assert_eq!(*thinned.last().unwrap(), 5000);
+            assert_eq!(thin_out(&[1, 2, 3]), vec![1, 2, 3]);
+        }
+
+        #[test]
+        fn charts_are_written_in_the_format_of_their_path() {
+            let dir = tempfile::tempdir().unwrap();
+            let svg = dir.path().join("cdf.svg");
+            let png = dir.path().join("histogram.png");
+            let values = series("Elevator", vec![5, 1, 9, 3, 3]);
+
+            draw_cdf(svg.to_str().unwrap(), std::slice::from_ref(&values)).unwrap();
+            let content = fs::read_to_string(&svg).unwrap();
+            assert!(content.starts_with("<svg"));
+            assert!(content.contains("Elevator"));
+
+            draw_histogram(png.to_str().unwrap(), &[values], 4).unwrap();
+            assert!(fs::read(&png).unwrap().starts_with(b"\x89PNG"));
+
+            let jpg = dir.path().join("cdf.jpg");
+            assert!(draw_cdf(jpg.to_str().unwrap(), &[]).is_err());
+            assert!(!jpg.exists());
+        }
+
+        #[test]
+        fn heatmaps_and_head_traces_are_drawn() {
+            let dir = tempfile::tempdir().unwrap();
+            let heatmap = dir.path().join("heatmap.svg");
+            let trace = dir.path().join("trace.svg");
+
+            draw_heatmap(
+                heatmap.to_str().unwrap(),
+                &[(1, 10), (50, 90), (99, 10)],
+                100,
+            )
+            .unwrap();
+            draw_head_trace(
+                trace.to_str().unwrap(),
+                &[Trace {
+                    label: "Naive".to_owned(),
+                    points: vec![(0, 1), (10, 40), (20, 5)],
+                }],
+                100,
+            )
+            .unwrap();
+
+            assert!(fs::read_to_string(&heatmap).unwrap().starts_with("<svg"));
+            let trace = fs::read_to_string(&trace).unwrap();
+            assert!(trace.contains("Head position"));
+            assert!(trace.contains("<polyline"));
+        }
+    }
+}
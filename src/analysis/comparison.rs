@@ -0,0 +1,173 @@
+pub mod comparison {
+    use crate::analysis::{
+        aggregate::aggregate::format_relative, stats::stats::ResponseStatistics,
+    };
+    use crate::simulation::{
+        scenario::scenario::DiskConfig,
+        simulation::simulation::{run_simulation, Algorithms, SchedulerConfig},
+        workload::workload::Arrival,
+    };
+
+    /// How one scheduler did on the shared workload.
+    pub struct AlgorithmResult {
+        pub algorithm: Algorithms,
+        pub statistics: ResponseStatistics,
+        pub head_movement: u64,
+    }
+
+    /// Runs every algorithm on the same arrivals and disk, so the only thing
+    /// that differs between the results is the scheduler.
+    pub fn compare_algorithms(
+        scheduler: SchedulerConfig,
+        disk: &DiskConfig,
+        arrivals: &[Arrival],
+        algorithms: &[Algorithms],
+    ) -> Result<Vec<AlgorithmResult>, String> {
+        algorithms
+            .iter()
+            .map(|&algorithm| {
+                let mut scheduler = scheduler;
+                scheduler.algorithm = algorithm;
+
+                let result = run_simulation(scheduler, disk.build(), arrivals, false, None)?;
+                Ok(AlgorithmResult {
+                    algorithm: algorithm,
+                    statistics: ResponseStatistics::from_times(
+                        &result.response_times,
+                        Some(result.total_time),
+                    ),
+                    head_movement: result.disk.get_tracks_traversed(),
+                })
+            })
+            .collect()
+    }
+
+    /// Improvement of `value` over `baseline` as a fraction of the baseline;
+    /// positive is better whichever way the metric points.
+    fn improvement(baseline: f64, value: f64, lower_is_better: bool) -> Option<f64> {
+        if baseline == 0.0 {
+            return None;
+        }
+
+        if lower_is_better {
+            Some((baseline - value) / baseline)
+        } else {
+            Some((value - baseline) / baseline)
+        }
+    }
+
+    pub fn show_comparison(results: &[AlgorithmResult], baseline: Algorithms) {
+        println!(
+            "{:<10} {:>10} {:>14} {:>10} {:>10} {:>10} {:>14} {:>10}",
+            "algorithm", "requests", "mean", "p95", "p99", "max", "head movement", "throughput"
+        );
+        for result in results.iter() {
+            let statistics = &result.statistics;
+            println!(
+                "{:<10} {:>10} {:>14.2} {:>10.1} {:>10.1} {:>10} {:>14} {:>10.3}",
+                result.algorithm.get_name(),
+                statistics.count,
+                statistics.mean,
+                statistics.p95,
+                statistics.p99,
+                statistics.max,
+                result.head_movement,
+                statistics.throughput.unwrap_or(0.0)
+            );
+        }
+
+        let Some(base) = results.iter().find(|r| r.algorithm == baseline) else {
+            return;
+        };
+
+        println!();
+        println!(
+            "Improvement over {} (positive is better)",
+            baseline.get_name()
+        );
+        println!(
+            "{:<10} {:>10} {:>10} {:>10} {:>14} {:>10}",
+            "algorithm", "mean", "p95", "p99", "head movement", "throughput"
+        );
+        for result in results.iter().filter(|r| r.algorithm != baseline) {
+            let (b, s) = (&base.statistics, &result.statistics);
+            println!(
+                "{:<10} {:>10} {:>10} {:>10} {:>14} {:>10}",
+                result.algorithm.get_name(),
+                format_relative(improvement(b.mean, s.mean, true)),
+                format_relative(improvement(b.p95, s.p95, true)),
+                format_relative(improvement(b.p99, s.p99, true)),
+                format_relative(improvement(
+                    base.head_movement as f64,
+                    result.head_movement as f64,
+                    true
+                )),
+                format_relative(
+                    b.throughput
+                        .zip(s.throughput)
+                        .and_then(|(b, s)| improvement(b, s, false))
+                )
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::{disk::disk::DiskMetadata, driver::driver::Task};
+
+        /// Requests for tracks 90, 10 and 60, one tick apart. First come first
+        /// served moves the head 89 + 80 + 50 tracks, a scan 9 + 50 + 30.
+        fn results() -> Vec<AlgorithmResult> {
+            let arrivals: Vec<Arrival> = [90, 10, 60]
+                .iter()
+                .enumerate()
+                .map(|(i, &track)| Arrival {
+                    time: i as u32,
+                    task: Task::new(i as u32 + 1, track, 90),
+                    owner: 0,
+                })
+                .collect();
+
+            compare_algorithms(
+                SchedulerConfig::new(Algorithms::NAIVE),
+                &DiskConfig::new(DiskMetadata::from_config(1, 1), 100),
+                &arrivals,
+                &[Algorithms::NAIVE, Algorithms::ELEVATOR],
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn improvements_are_positive_when_better() {
+            assert_eq!(improvement(200.0, 150.0, true), Some(0.25));
+            assert_eq!(improvement(200.0, 250.0, true), Some(-0.25));
+            assert_eq!(improvement(2.0, 2.5, false), Some(0.25));
+            assert_eq!(improvement(2.0, 1.5, false), Some(-0.25));
+            assert_eq!(improvement(0.0, 1.0, true), None);
+            assert_eq!(format_relative(improvement(200.0, 150.0, true)), "+25.00%");
+        }
+
+        #[test]
+        fn algorithms_run_on_the_same_workload() {
+            let results = results();
+            let (naive, elevator) = (&results[0], &results[1]);
+
+            assert!(naive.algorithm == Algorithms::NAIVE);
+            assert_eq!(naive.statistics.count, 3);
+            assert_eq!(elevator.statistics.count, 3);
+            assert_eq!(naive.head_movement, 219);
+            assert_eq!(elevator.head_movement, 89);
+
+            let head_movement = improvement(
+                naive.head_movement as f64,
+                elevator.head_movement as f64,
+                true,
+            );
+            assert_eq!(head_movement, Some(130.0 / 219.0));
+            assert_eq!(format_relative(head_movement), "+59.36%");
+            let mean = improvement(naive.statistics.mean, elevator.statistics.mean, true);
+            assert!(mean.unwrap() > 0.0);
+        }
+    }
+}
@@ -0,0 +1,238 @@
+pub mod fairness {
+    use crate::simulation::simulation::simulation::TaskRecord;
+
+    pub struct OwnerSummary {
+        pub owner: u32,
+        pub tasks: u32,
+        pub mean_response_time: f64,
+    }
+
+    /// How evenly a run treated its tasks and their owners.
+    pub struct FairnessReport {
+        pub mean_wait_time: f64,
+        pub mean_service_time: f64,
+        pub max_bypassed: u32,
+        /// Jain's index over the owners' mean response times; 1 is perfectly fair.
+        pub fairness_index: f64,
+        /// Sorted by owner.
+        pub owners: Vec<OwnerSummary>,
+    }
+
+    /// Jain's fairness index, `(sum x)^2 / (n * sum x^2)`, between 1/n and 1.
+    pub fn jain_index(values: &[f64]) -> f64 {
+        let sum: f64 = values.iter().sum();
+        let squares: f64 = values.iter().map(|v| v * v).sum();
+        if squares == 0.0 {
+            return 1.0;
+        }
+
+        sum * sum / (values.len() as f64 * squares)
+    }
+
+    fn average(values: impl Iterator<Item = u32>) -> f64 {
+        let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v as f64, count + 1));
+        if count == 0 {
+            return 0.0;
+        }
+
+        sum / count as f64
+    }
+
+    impl FairnessReport {
+        pub fn from_tasks(tasks: &[TaskRecord]) -> FairnessReport {
+            let mut owners: Vec<OwnerSummary> = Vec::new();
+            for task in tasks.iter() {
+                let index = match owners.binary_search_by_key(&task.owner, |o| o.owner) {
+                    Ok(index) => index,
+                    Err(index) => {
+                        owners.insert(
+                            index,
+                            OwnerSummary {
+                                owner: task.owner,
+                                tasks: 0,
+                                mean_response_time: 0.0,
+                            },
+                        );
+                        index
+                    }
+                };
+
+                // Accumulates the total here and divides once every task is counted.
+                owners[index].tasks += 1;
+                owners[index].mean_response_time += task.get_response_time() as f64;
+            }
+            for owner in owners.iter_mut() {
+                owner.mean_response_time /= owner.tasks as f64;
+            }
+
+            let means: Vec<f64> = owners.iter().map(|o| o.mean_response_time).collect();
+
+            FairnessReport {
+                mean_wait_time: average(tasks.iter().map(|t| t.get_wait_time())),
+                mean_service_time: average(tasks.iter().map(|t| t.get_service_time())),
+                max_bypassed: tasks.iter().map(|t| t.bypassed).max().unwrap_or(0),
+                fairness_index: jain_index(&means),
+                owners: owners,
+            }
+        }
+
+        pub fn show(&self) {
+            println!(
+                "Fairness <\n\tmean wait: {:.2}, mean service: {:.2}, most bypassed: {} times\n\tJain's index over {} owners: {:.4}",
+                self.mean_wait_time,
+                self.mean_service_time,
+                self.max_bypassed,
+                self.owners.len(),
+                self.fairness_index
+            );
+            if self.owners.len() > 1 {
+                for owner in self.owners.iter() {
+                    println!(
+                        "\towner {}: {} requests, mean response time {:.2}",
+                        owner.owner, owner.tasks, owner.mean_response_time
+                    );
+                }
+            }
+            println!(">");
+        }
+    }
+
+    /// Tasks whose response time exceeded `threshold` ticks, the slowest first.
+    pub fn find_starved_tasks(tasks: &[TaskRecord], threshold: u32) -> Vec<&TaskRecord> {
+        let mut starved: Vec<&TaskRecord> = tasks
+            .iter()
+            .filter(|task| task.get_response_time() > threshold)
+            .collect();
+        starved.sort_by_key(|task| std::cmp::Reverse(task.get_response_time()));
+
+        starved
+    }
+
+    pub fn show_starvation(tasks: &[TaskRecord], threshold: u32) {
+        let starved = find_starved_tasks(tasks, threshold);
+        println!(
+            "{} of {} requests waited longer than {} ticks",
+            starved.len(),
+            tasks.len(),
+            threshold
+        );
+        if starved.is_empty() {
+            return;
+        }
+
+        println!(
+            "\t{:>8} {:>6} {:>8} {:>10} {:>10} {:>10} {:>10} {:>9}",
+            "id", "owner", "track", "arrival", "response", "wait", "service", "bypassed"
+        );
+        for task in starved.iter() {
+            println!(
+                "\t{:>8} {:>6} {:>8} {:>10} {:>10} {:>10} {:>10} {:>9}",
+                task.id,
+                task.owner,
+                task.track,
+                task.arrival,
+                task.get_response_time(),
+                task.get_wait_time(),
+                task.get_service_time(),
+                task.bypassed
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::{
+            disk::disk::{Disk, DiskMetadata},
+            driver::driver::Task,
+        };
+        use crate::simulation::{
+            simulation::simulation::{run_simulation, Algorithms, SchedulerConfig},
+            workload::workload::Arrival,
+        };
+
+        /// Three requests, one tick apart, for tracks 90, 10 and 60. A scan
+        /// heading for track 90 picks up the two later ones on its way.
+        fn run(algorithm: Algorithms) -> Vec<TaskRecord> {
+            let arrivals: Vec<Arrival> = [(90, 0), (10, 1), (60, 1)]
+                .iter()
+                .enumerate()
+                .map(|(i, &(track, owner))| Arrival {
+                    time: i as u32,
+                    task: Task::new(i as u32 + 1, track, 90),
+                    owner: owner,
+                })
+                .collect();
+
+            run_simulation(
+                SchedulerConfig::new(algorithm),
+                Box::new(Disk::new(DiskMetadata::from_config(1, 1))),
+                &arrivals,
+                false,
+                None,
+            )
+            .unwrap()
+            .tasks
+        }
+
+        fn bypassed(tasks: &[TaskRecord]) -> Vec<(u32, u32)> {
+            let mut bypassed: Vec<(u32, u32)> = tasks.iter().map(|t| (t.id, t.bypassed)).collect();
+            bypassed.sort();
+
+            bypassed
+        }
+
+        #[test]
+        fn jain_index_is_one_for_equal_means_and_one_over_n_for_one_owner() {
+            assert_eq!(jain_index(&[7.0, 7.0, 7.0]), 1.0);
+            assert_eq!(jain_index(&[12.0, 0.0, 0.0, 0.0]), 0.25);
+            assert_eq!(jain_index(&[1.0, 3.0]), 0.8);
+            assert_eq!(jain_index(&[]), 1.0);
+        }
+
+        #[test]
+        fn first_come_first_served_bypasses_nobody() {
+            let tasks = run(Algorithms::NAIVE);
+
+            assert_eq!(bypassed(&tasks), vec![(1, 0), (2, 0), (3, 0)]);
+            assert_eq!(FairnessReport::from_tasks(&tasks).max_bypassed, 0);
+        }
+
+        #[test]
+        fn a_scan_bypasses_the_request_it_set_out_for() {
+            let tasks = run(Algorithms::ELEVATOR);
+            let order: Vec<u32> = tasks.iter().map(|t| t.id).collect();
+
+            assert_eq!(order, vec![2, 3, 1]);
+            assert_eq!(bypassed(&tasks), vec![(1, 2), (2, 0), (3, 0)]);
+
+            let report = FairnessReport::from_tasks(&tasks);
+            assert_eq!(report.max_bypassed, 2);
+            assert_eq!(report.owners.len(), 2);
+            assert_eq!(report.owners[0].owner, 0);
+            assert_eq!(report.owners[0].tasks, 1);
+            assert_eq!(report.owners[1].tasks, 2);
+            for task in tasks.iter() {
+                assert_eq!(
+                    task.get_wait_time() + task.get_service_time(),
+                    task.get_response_time()
+                );
+            }
+        }
+
+        #[test]
+        fn starved_tasks_are_listed_slowest_first() {
+            let tasks = run(Algorithms::ELEVATOR);
+            let mut response_times: Vec<u32> =
+                tasks.iter().map(|t| t.get_response_time()).collect();
+            response_times.sort();
+
+            let starved = find_starved_tasks(&tasks, response_times[0]);
+            let ids: Vec<u32> = starved.iter().map(|t| t.id).collect();
+            assert_eq!(ids.len(), 2);
+            assert_eq!(ids[0], 1);
+            assert!(starved[0].get_response_time() >= starved[1].get_response_time());
+            assert!(find_starved_tasks(&tasks, response_times[2]).is_empty());
+        }
+    }
+}
@@ -0,0 +1,258 @@
+pub mod inference {
+    use rand::{rngs::StdRng, Rng};
+
+    /// A two-sided interval holding `confidence` of the bootstrap distribution.
+    #[derive(Copy, Clone)]
+    pub struct Interval {
+        pub low: f64,
+        pub high: f64,
+    }
+
+    pub struct MannWhitney {
+        /// U statistic of the first sample.
+        pub u: f64,
+        pub z: f64,
+        /// Two-sided, from the normal approximation with tie correction.
+        pub p_value: f64,
+    }
+
+    pub fn mean(values: &[f64]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+
+    fn resample_mean(values: &[f64], rng: &mut StdRng) -> f64 {
+        let total: f64 = (0..values.len())
+            .map(|_| values[rng.gen_range(0..values.len())])
+            .sum();
+        total / values.len() as f64
+    }
+
+    fn percentile_interval(mut estimates: Vec<f64>, confidence: f64) -> Interval {
+        estimates.sort_by(|a, b| a.total_cmp(b));
+        let last = estimates.len() - 1;
+        let tail = (1.0 - confidence) / 2.0;
+
+        Interval {
+            low: estimates[(tail * last as f64).floor() as usize],
+            high: estimates[((1.0 - tail) * last as f64).ceil() as usize],
+        }
+    }
+
+    /// Percentile bootstrap interval of the mean; `None` for fewer than two values.
+    pub fn bootstrap_mean(
+        values: &[f64],
+        confidence: f64,
+        resamples: usize,
+        rng: &mut StdRng,
+    ) -> Option<Interval> {
+        if values.len() < 2 || resamples == 0 {
+            return None;
+        }
+
+        let estimates = (0..resamples).map(|_| resample_mean(values, rng)).collect();
+        Some(percentile_interval(estimates, confidence))
+    }
+
+    /// Percentile bootstrap interval of `mean(second) - mean(first)`, resampling
+    /// both groups independently.
+    pub fn bootstrap_difference(
+        first: &[f64],
+        second: &[f64],
+        confidence: f64,
+        resamples: usize,
+        rng: &mut StdRng,
+    ) -> Option<Interval> {
+        if first.len() < 2 || second.len() < 2 || resamples == 0 {
+            return None;
+        }
+
+        let estimates = (0..resamples)
+            .map(|_| resample_mean(second, rng) - resample_mean(first, rng))
+            .collect();
+        Some(percentile_interval(estimates, confidence))
+    }
+
+    /// Complementary error function, accurate to about 1.2e-7.
+    fn erfc(x: f64) -> f64 {
+        let z = x.abs();
+        let t = 1.0 / (1.0 + 0.5 * z);
+        let polynomial = -z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+        let result = t * polynomial.exp();
+
+        if x >= 0.0 {
+            result
+        } else {
+            2.0 - result
+        }
+    }
+
+    /// Mann-Whitney U test of whether the two samples come from the same distribution.
+    pub fn mann_whitney(first: &[f64], second: &[f64]) -> Option<MannWhitney> {
+        if first.is_empty() || second.is_empty() {
+            return None;
+        }
+
+        let mut values: Vec<(f64, bool)> = first
+            .iter()
+            .map(|&v| (v, true))
+            .chain(second.iter().map(|&v| (v, false)))
+            .collect();
+        values.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let n = values.len() as f64;
+        let mut first_ranks = 0.0;
+        let mut ties = 0.0;
+        let mut i = 0;
+        while i < values.len() {
+            let mut j = i;
+            while j + 1 < values.len() && values[j + 1].0 == values[i].0 {
+                j += 1;
+            }
+
+            let rank = (i + j) as f64 / 2.0 + 1.0;
+            let count = (j - i + 1) as f64;
+            first_ranks += rank * values[i..=j].iter().filter(|v| v.1).count() as f64;
+            ties += count * count * count - count;
+            i = j + 1;
+        }
+
+        let n1 = first.len() as f64;
+        let n2 = second.len() as f64;
+        let u = first_ranks - n1 * (n1 + 1.0) / 2.0;
+        let expected = n1 * n2 / 2.0;
+        let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+
+        if variance <= 0.0 {
+            return Some(MannWhitney {
+                u: u,
+                z: 0.0,
+                p_value: 1.0,
+            });
+        }
+
+        let difference = u - expected;
+        let corrected = (difference.abs() - 0.5).max(0.0) * difference.signum();
+        let z = corrected / variance.sqrt();
+
+        Some(MannWhitney {
+            u: u,
+            z: z,
+            p_value: erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use rand::SeedableRng;
+
+        use super::*;
+
+        fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+            assert!(
+                (actual - expected).abs() < tolerance,
+                "{} is not {}",
+                actual,
+                expected
+            );
+        }
+
+        #[test]
+        fn erfc_matches_known_values() {
+            assert_close(erfc(0.0), 1.0, 1e-7);
+            assert_close(erfc(1.0), 0.157299207, 1e-7);
+            assert_close(erfc(-1.0), 1.842700793, 1e-7);
+        }
+
+        #[test]
+        fn percentile_interval_cuts_both_tails() {
+            let estimates: Vec<f64> = (0..=100).rev().map(|v| v as f64).collect();
+            let interval = percentile_interval(estimates, 0.5);
+
+            assert_eq!(interval.low, 25.0);
+            assert_eq!(interval.high, 75.0);
+        }
+
+        #[test]
+        fn bootstrap_of_constant_values_has_no_width() {
+            let mut rng = StdRng::seed_from_u64(1);
+            let interval = bootstrap_mean(&[3.0; 5], 0.95, 100, &mut rng).unwrap();
+
+            assert_eq!(interval.low, 3.0);
+            assert_eq!(interval.high, 3.0);
+        }
+
+        #[test]
+        fn bootstrap_needs_two_values_and_a_resample() {
+            let mut rng = StdRng::seed_from_u64(1);
+
+            assert!(bootstrap_mean(&[1.0], 0.95, 100, &mut rng).is_none());
+            assert!(bootstrap_mean(&[1.0, 2.0], 0.95, 0, &mut rng).is_none());
+            assert!(bootstrap_difference(&[1.0, 2.0], &[1.0], 0.95, 100, &mut rng).is_none());
+        }
+
+        #[test]
+        fn bootstrap_is_reproducible_and_holds_the_mean() {
+            let values: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+            let first = bootstrap_mean(&values, 0.95, 1000, &mut StdRng::seed_from_u64(7)).unwrap();
+            let again = bootstrap_mean(&values, 0.95, 1000, &mut StdRng::seed_from_u64(7)).unwrap();
+
+            assert_eq!((first.low, first.high), (again.low, again.high));
+            assert!(first.low < 5.5 && 5.5 < first.high);
+            assert!(first.low >= 1.0 && first.high <= 10.0);
+        }
+
+        #[test]
+        fn bootstrap_difference_is_second_minus_first() {
+            let first: Vec<f64> = (1..=10).map(|v| v as f64).collect();
+            let second: Vec<f64> = first.iter().map(|v| v + 5.0).collect();
+            let mut rng = StdRng::seed_from_u64(3);
+            let interval = bootstrap_difference(&first, &second, 0.95, 2000, &mut rng).unwrap();
+
+            assert!(interval.low > 0.0);
+            assert!(interval.low < 5.0 && 5.0 < interval.high);
+        }
+
+        #[test]
+        fn mann_whitney_of_separated_samples() {
+            let test = mann_whitney(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]).unwrap();
+
+            assert_eq!(test.u, 0.0);
+            assert_close(test.z, -4.0 / 5.25f64.sqrt(), 1e-12);
+            assert_close(test.p_value, 0.0808556, 1e-6);
+
+            let swapped = mann_whitney(&[4.0, 5.0, 6.0], &[1.0, 2.0, 3.0]).unwrap();
+            assert_eq!(swapped.u, 9.0);
+            assert_close(swapped.z, -test.z, 1e-12);
+            assert_close(swapped.p_value, test.p_value, 1e-12);
+        }
+
+        #[test]
+        fn mann_whitney_gives_ties_the_average_rank() {
+            // Ranks 1.5, 1.5 and 4 for the first sample.
+            let test = mann_whitney(&[1.0, 1.0, 3.0], &[2.0, 4.0]).unwrap();
+            assert_eq!(test.u, 1.0);
+
+            let same = mann_whitney(&[1.0, 1.0], &[1.0, 1.0]).unwrap();
+            assert_eq!(same.u, 2.0);
+            assert_eq!(same.z, 0.0);
+            assert_eq!(same.p_value, 1.0);
+        }
+
+        #[test]
+        fn mann_whitney_needs_both_samples() {
+            assert!(mann_whitney(&[], &[1.0]).is_none());
+            assert!(mann_whitney(&[1.0], &[]).is_none());
+        }
+    }
+}
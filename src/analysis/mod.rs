@@ -0,0 +1,20 @@
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
+pub mod aggregate;
+#[allow(clippy::module_inception)]
+pub mod charts;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod comparison;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod fairness;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod inference;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod operations;
+#[allow(clippy::module_inception)]
+pub mod report;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod stats;
@@ -0,0 +1,88 @@
+pub mod operations {
+    use crate::analysis::stats::stats::ResponseStatistics;
+    use crate::disk::driver::driver::Operation;
+    use crate::simulation::simulation::simulation::TaskRecord;
+
+    pub struct OperationSummary {
+        pub operation: Operation,
+        pub tasks: u32,
+        /// Blocks read, written or discarded; flushes have none.
+        pub blocks: u64,
+        pub mean_response_time: f64,
+        pub p99_response_time: f64,
+        pub mean_service_time: f64,
+    }
+
+    /// Response times split by what the tasks asked the disk to do.
+    pub struct OperationReport {
+        /// In the order of `Operation::ALL`, without the ones no task asked for.
+        pub operations: Vec<OperationSummary>,
+    }
+
+    impl OperationReport {
+        pub fn from_tasks(tasks: &[TaskRecord]) -> OperationReport {
+            let mut operations = Vec::new();
+            for operation in Operation::ALL.iter() {
+                let tasks: Vec<&TaskRecord> = tasks
+                    .iter()
+                    .filter(|task| task.operation == *operation)
+                    .collect();
+                if tasks.is_empty() {
+                    continue;
+                }
+
+                let response_times: Vec<u32> =
+                    tasks.iter().map(|task| task.get_response_time()).collect();
+                let statistics = ResponseStatistics::from_times(&response_times, None);
+                let service_time: f64 = tasks
+                    .iter()
+                    .map(|task| task.get_service_time() as f64)
+                    .sum();
+
+                operations.push(OperationSummary {
+                    operation: *operation,
+                    tasks: tasks.len() as u32,
+                    blocks: if *operation == Operation::FLUSH {
+                        0
+                    } else {
+                        tasks.iter().map(|task| task.length as u64).sum()
+                    },
+                    mean_response_time: statistics.mean,
+                    p99_response_time: statistics.p99,
+                    mean_service_time: service_time / tasks.len() as f64,
+                });
+            }
+
+            OperationReport {
+                operations: operations,
+            }
+        }
+
+        pub fn get(&self, operation: Operation) -> Option<&OperationSummary> {
+            self.operations
+                .iter()
+                .find(|summary| summary.operation == operation)
+        }
+
+        /// Whether the tasks asked for more than one kind of operation.
+        pub fn is_mixed(&self) -> bool {
+            self.operations.len() > 1
+        }
+
+        pub fn show(&self) {
+            println!("Operations <");
+            for summary in self.operations.iter() {
+                println!(
+                    "\t{}: {} requests, {} blocks, mean response time {:.2}, p99 {:.2}, mean service {:.2}",
+                    summary.operation.get_name(),
+                    summary.tasks,
+                    summary.blocks,
+                    summary.mean_response_time,
+                    summary.p99_response_time,
+                    summary.mean_service_time
+                );
+            }
+            println!(">");
+        }
+    }
+}
@@ -0,0 +1,578 @@
+pub mod report {
+    use std::fs;
+
+    use crate::analysis::{
+        aggregate::aggregate::{
+            format_interval, format_relative, group_runs, without_algorithm, Analysis,
+        },
+        charts::charts::{cdf_svg, histogram_svg, Series},
+    };
+    use crate::simulation::results::results::{RunRecord, RunSummary};
+
+    const STYLE: &str = "body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+th { background: #f0f0f0; }
+.charts svg { max-width: 48%; height: auto; }";
+
+    /// `results.jsonl` -> `results.html`
+    pub fn report_path(path: &str) -> String {
+        match path.rsplit_once('.') {
+            Some((stem, extension)) if !extension.contains('/') => format!("{}.html", stem),
+            _ => format!("{}.html", path),
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    fn row(cells: &[String], header: bool) -> String {
+        let tag = if header { "th" } else { "td" };
+        let cells: Vec<String> = cells
+            .iter()
+            .map(|cell| format!("<{}>{}</{}>", tag, escape(cell), tag))
+            .collect();
+
+        format!("<tr>{}</tr>\n", cells.join(""))
+    }
+
+    fn table(header: &[&str], rows: &[Vec<String>]) -> String {
+        let header: Vec<String> = header.iter().map(|&h| h.to_owned()).collect();
+        let mut html = String::from("<table>\n");
+        html.push_str(&row(&header, true));
+        for cells in rows.iter() {
+            html.push_str(&row(cells, false));
+        }
+        html.push_str("</table>\n");
+
+        html
+    }
+
+    /// Distinct values in the order they first appear.
+    fn distinct<T: ToString>(values: impl Iterator<Item = T>) -> String {
+        let mut seen: Vec<String> = Vec::new();
+        for value in values {
+            let value = value.to_string();
+            if !seen.contains(&value) {
+                seen.push(value);
+            }
+        }
+
+        seen.join(", ")
+    }
+
+    /// Mean over the runs that know the value.
+    fn average<F: Fn(&RunSummary) -> Option<f64>>(runs: &[&RunSummary], field: F) -> String {
+        let values: Vec<f64> = runs.iter().filter_map(|&run| field(run)).collect();
+        if values.is_empty() {
+            return "n/a".to_owned();
+        }
+
+        format!("{:.2}", values.iter().sum::<f64>() / values.len() as f64)
+    }
+
+    fn configuration_name(configuration: &str) -> String {
+        if configuration.is_empty() {
+            "<default>".to_owned()
+        } else {
+            configuration.to_owned()
+        }
+    }
+
+    fn parameters_section(runs: &[RunRecord]) -> String {
+        let summaries: Vec<&RunSummary> = runs.iter().map(|run| &run.summary).collect();
+        let seeds: Vec<u64> = summaries.iter().filter_map(|s| s.seed).collect();
+        let seeds = match seeds.len() {
+            0 => "unknown".to_owned(),
+            1..=10 => distinct(seeds.iter()),
+            n => format!("{} seeds, see the runs below", n),
+        };
+
+        let rows = vec![
+            vec![
+                "algorithms".to_owned(),
+                distinct(summaries.iter().map(|s| &s.algorithm)),
+            ],
+            vec![
+                "forward speed".to_owned(),
+                distinct(summaries.iter().map(|s| s.forward_speed)),
+            ],
+            vec![
+                "spin speed".to_owned(),
+                distinct(summaries.iter().map(|s| s.spin_speed)),
+            ],
+            vec![
+                "seek model".to_owned(),
+                distinct(summaries.iter().filter_map(|s| s.seek_model.as_ref())),
+            ],
+            vec![
+                "rotation model".to_owned(),
+                distinct(summaries.iter().filter_map(|s| s.rotation_model.as_ref())),
+            ],
+            vec![
+                "tracks".to_owned(),
+                distinct(summaries.iter().map(|s| s.tracks)),
+            ],
+            vec![
+                "heads".to_owned(),
+                distinct(summaries.iter().filter_map(|s| s.heads)),
+            ],
+            vec![
+                "block mapping".to_owned(),
+                distinct(summaries.iter().filter_map(|s| s.mapping.as_ref())),
+            ],
+            vec![
+                "queue depth".to_owned(),
+                distinct(summaries.iter().filter_map(|s| s.queue_depth)),
+            ],
+            vec![
+                "device".to_owned(),
+                distinct(summaries.iter().filter_map(|s| s.device.as_ref())),
+            ],
+            vec![
+                "requests".to_owned(),
+                distinct(summaries.iter().map(|s| s.requests)),
+            ],
+            vec!["workload seeds".to_owned(), seeds],
+        ];
+
+        format!(
+            "<h2>Configuration</h2>\n{}",
+            table(&["parameter", "values"], &rows)
+        )
+    }
+
+    fn summary_section(runs: &[RunRecord]) -> String {
+        let mut rows = Vec::new();
+        for group in group_runs(runs.iter().map(|run| run.summary.clone()).collect()) {
+            let summaries: Vec<&RunSummary> = group.runs.iter().collect();
+            rows.push(vec![
+                configuration_name(&group.configuration),
+                group.algorithm.clone(),
+                group.runs.len().to_string(),
+                average(&summaries, |s| Some(s.mean_response_time)),
+                average(&summaries, |s| Some(s.p95_response_time)),
+                average(&summaries, |s| Some(s.p99_response_time)),
+                average(&summaries, |s| Some(s.max_response_time as f64)),
+                average(&summaries, |s| s.throughput),
+                average(&summaries, |s| s.head_movement.map(|m| m as f64)),
+                average(&summaries, |s| s.fairness_index),
+            ]);
+        }
+
+        format!(
+            "<h2>Summary</h2>\n<p>Averages over the repetitions of each configuration, response times in ticks.</p>\n{}",
+            table(
+                &[
+                    "configuration",
+                    "algorithm",
+                    "runs",
+                    "mean",
+                    "p95",
+                    "p99",
+                    "max",
+                    "throughput",
+                    "head movement",
+                    "fairness",
+                ],
+                &rows
+            )
+        )
+    }
+
+    /// Requests and response times per operation, left out when every run
+    /// only read.
+    fn operations_section(runs: &[RunRecord]) -> String {
+        let mixed = runs.iter().any(|run| {
+            let s = &run.summary;
+            s.writes.unwrap_or(0) + s.flushes.unwrap_or(0) + s.discards.unwrap_or(0) > 0
+        });
+        if !mixed {
+            return String::new();
+        }
+
+        let mut rows = Vec::new();
+        for group in group_runs(runs.iter().map(|run| run.summary.clone()).collect()) {
+            let summaries: Vec<&RunSummary> = group.runs.iter().collect();
+            rows.push(vec![
+                configuration_name(&group.configuration),
+                group.algorithm.clone(),
+                average(&summaries, |s| s.reads.map(|n| n as f64)),
+                average(&summaries, |s| s.writes.map(|n| n as f64)),
+                average(&summaries, |s| s.flushes.map(|n| n as f64)),
+                average(&summaries, |s| s.discards.map(|n| n as f64)),
+                average(&summaries, |s| s.mean_read_response_time),
+                average(&summaries, |s| s.mean_write_response_time),
+            ]);
+        }
+
+        format!(
+            "<h2>Operations</h2>
+<p>Averages over the repetitions of each configuration, response times in ticks.</p>
+{}",
+            table(
+                &[
+                    "configuration",
+                    "algorithm",
+                    "reads",
+                    "writes",
+                    "flushes",
+                    "discards",
+                    "mean read",
+                    "mean write",
+                ],
+                &rows
+            )
+        )
+    }
+
+    /// How often the drive served queued tasks out of order and what it did
+    /// to response times, left out when no drive queued more than one task.
+    fn queue_section(runs: &[RunRecord]) -> String {
+        if !runs
+            .iter()
+            .any(|run| run.summary.queue_depth.is_some_and(|depth| depth > 1))
+        {
+            return String::new();
+        }
+
+        let mut rows = Vec::new();
+        for group in group_runs(runs.iter().map(|run| run.summary.clone()).collect()) {
+            let summaries: Vec<&RunSummary> = group.runs.iter().collect();
+            rows.push(vec![
+                configuration_name(&group.configuration),
+                group.algorithm.clone(),
+                distinct(summaries.iter().filter_map(|s| s.queue_depth)),
+                average(&summaries, |s| s.reordered_tasks.map(|n| n as f64)),
+                average(&summaries, |s| Some(s.mean_response_time)),
+                average(&summaries, |s| Some(s.p99_response_time)),
+            ]);
+        }
+
+        format!(
+            "<h2>Command queuing</h2>\n<p>Averages over the repetitions of each configuration, response times in ticks.</p>\n{}",
+            table(
+                &[
+                    "configuration",
+                    "algorithm",
+                    "queue depth",
+                    "reordered",
+                    "mean",
+                    "p99",
+                ],
+                &rows
+            )
+        )
+    }
+
+    /// What garbage collection cost the SSDs, left out when every run was on
+    /// a disk.
+    fn flash_section(runs: &[RunRecord]) -> String {
+        if !runs
+            .iter()
+            .any(|run| run.summary.device.as_deref() == Some("ssd"))
+        {
+            return String::new();
+        }
+
+        let mut rows = Vec::new();
+        for group in group_runs(runs.iter().map(|run| run.summary.clone()).collect()) {
+            let summaries: Vec<&RunSummary> = group.runs.iter().collect();
+            rows.push(vec![
+                configuration_name(&group.configuration),
+                group.algorithm.clone(),
+                distinct(summaries.iter().filter_map(|s| s.device.as_ref())),
+                average(&summaries, |s| s.block_erases.map(|n| n as f64)),
+                average(&summaries, |s| s.write_amplification),
+                average(&summaries, |s| Some(s.mean_response_time)),
+                average(&summaries, |s| Some(s.p99_response_time)),
+            ]);
+        }
+
+        format!(
+            "<h2>Flash</h2>\n<p>Averages over the repetitions of each configuration, response times in ticks.</p>\n{}",
+            table(
+                &[
+                    "configuration",
+                    "algorithm",
+                    "device",
+                    "block erases",
+                    "write amplification",
+                    "mean",
+                    "p99",
+                ],
+                &rows
+            )
+        )
+    }
+
+    /// How often reads hit the drive's cache and how many writes its write
+    /// cache took, left out when no drive had either.
+    fn cache_section(runs: &[RunRecord]) -> String {
+        if runs.iter().all(|run| {
+            run.summary.cache_hit_ratio.is_none() && run.summary.buffered_writes.is_none()
+        }) {
+            return String::new();
+        }
+
+        let mut rows = Vec::new();
+        for group in group_runs(runs.iter().map(|run| run.summary.clone()).collect()) {
+            let summaries: Vec<&RunSummary> = group.runs.iter().collect();
+            rows.push(vec![
+                configuration_name(&group.configuration),
+                group.algorithm.clone(),
+                average(&summaries, |s| s.cache_hits.map(|n| n as f64)),
+                average(&summaries, |s| s.cache_hit_ratio),
+                average(&summaries, |s| s.buffered_writes.map(|n| n as f64)),
+                average(&summaries, |s| s.destaged_writes.map(|n| n as f64)),
+                average(&summaries, |s| s.dirty_sectors.map(|n| n as f64)),
+                average(&summaries, |s| Some(s.mean_response_time)),
+            ]);
+        }
+
+        format!(
+            "<h2>Drive cache</h2>\n<p>Averages over the repetitions of each configuration.</p>\n{}",
+            table(
+                &[
+                    "configuration",
+                    "algorithm",
+                    "hits",
+                    "hit ratio",
+                    "buffered writes",
+                    "destaged writes",
+                    "dirty sectors left",
+                    "mean",
+                ],
+                &rows
+            )
+        )
+    }
+
+    fn comparison_section(runs: &[RunRecord], analysis: &Analysis) -> String {
+        let groups = group_runs(runs.iter().map(|run| run.summary.clone()).collect());
+        let level = format!("{}% CI", 100.0 * analysis.confidence);
+        let mut html = format!(
+            "<h2>Algorithm comparison</h2>\n<p>Bootstrap intervals over the repetitions and Mann-Whitney tests against {}, significant below p = {:.2}.</p>\n",
+            analysis.baseline.get_name(),
+            1.0 - analysis.confidence
+        );
+
+        for estimate in analysis.evaluate(&groups).iter() {
+            html.push_str(&format!(
+                "<h3>{}</h3>\n",
+                escape(&configuration_name(&estimate.configuration))
+            ));
+
+            let rows: Vec<Vec<String>> = estimate
+                .algorithms
+                .iter()
+                .map(|a| {
+                    vec![
+                        a.algorithm.clone(),
+                        a.runs.to_string(),
+                        format!("{:.2}", a.mean),
+                        format_interval(a.mean_interval),
+                        format!("{:.2}", a.p99),
+                        format_interval(a.p99_interval),
+                    ]
+                })
+                .collect();
+            html.push_str(&table(
+                &["algorithm", "runs", "mean", &level, "p99", &level],
+                &rows,
+            ));
+
+            if !estimate.comparisons.is_empty() {
+                let rows: Vec<Vec<String>> = estimate
+                    .comparisons
+                    .iter()
+                    .map(|c| {
+                        vec![
+                            format!("{} vs {}", c.algorithm, c.baseline),
+                            c.metric.get_name().to_owned(),
+                            format!("{:.2}", c.difference),
+                            format_interval(c.interval),
+                            format_relative(c.relative),
+                            c.format_test(),
+                        ]
+                    })
+                    .collect();
+                html.push_str(&table(
+                    &[
+                        "comparison",
+                        "metric",
+                        "difference",
+                        &level,
+                        "relative",
+                        "Mann-Whitney",
+                    ],
+                    &rows,
+                ));
+            }
+        }
+
+        html
+    }
+
+    fn charts_section(runs: &[RunRecord]) -> Result<String, String> {
+        // Response times of each configuration, one series per algorithm.
+        let mut configurations: Vec<(String, Vec<Series>)> = Vec::new();
+        for run in runs.iter() {
+            let configuration = without_algorithm(&run.summary.configuration);
+            let index = match configurations.iter().position(|(c, _)| *c == configuration) {
+                Some(index) => index,
+                None => {
+                    configurations.push((configuration, Vec::new()));
+                    configurations.len() - 1
+                }
+            };
+
+            let series = &mut configurations[index].1;
+            let times = run.tasks.iter().map(|task| task.response_time);
+            match series.iter_mut().find(|s| s.label == run.summary.algorithm) {
+                Some(s) => s.values.extend(times),
+                None => series.push(Series {
+                    label: run.summary.algorithm.clone(),
+                    values: times.collect(),
+                }),
+            }
+        }
+
+        let mut html = String::from("<h2>Response times</h2>\n");
+        for (configuration, series) in configurations.iter() {
+            if series.iter().all(|s| s.values.is_empty()) {
+                continue;
+            }
+
+            html.push_str(&format!(
+                "<h3>{}</h3>\n<div class=\"charts\">\n{}\n{}\n</div>\n",
+                escape(&configuration_name(configuration)),
+                cdf_svg(series)?,
+                histogram_svg(series, 50)?
+            ));
+        }
+
+        Ok(html)
+    }
+
+    fn runs_section(runs: &[RunRecord]) -> String {
+        let rows: Vec<Vec<String>> = runs
+            .iter()
+            .map(|run| {
+                let s = &run.summary;
+                vec![
+                    s.key.clone(),
+                    s.seed.map_or("n/a".to_owned(), |seed| seed.to_string()),
+                    s.completed.to_string(),
+                    s.total_time.map_or("n/a".to_owned(), |t| t.to_string()),
+                    format!("{:.2}", s.mean_response_time),
+                    format!("{:.2}", s.p99_response_time),
+                ]
+            })
+            .collect();
+
+        format!(
+            "<h2>Runs</h2>\n{}",
+            table(
+                &["key", "seed", "completed", "total time", "mean", "p99"],
+                &rows
+            )
+        )
+    }
+
+    /// Writes a self-contained HTML page about `runs`: their configuration,
+    /// summary tables, the algorithm comparison and embedded SVG charts.
+    pub fn write_report(
+        path: &str,
+        title: &str,
+        runs: &[RunRecord],
+        analysis: &Analysis,
+    ) -> Result<(), String> {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n<h1>{}</h1>\n<p>{} runs.</p>\n",
+            escape(title),
+            STYLE,
+            escape(title),
+            runs.len()
+        );
+        html.push_str(&parameters_section(runs));
+        html.push_str(&summary_section(runs));
+        html.push_str(&operations_section(runs));
+        html.push_str(&queue_section(runs));
+        html.push_str(&cache_section(runs));
+        html.push_str(&flash_section(runs));
+        html.push_str(&comparison_section(runs, analysis));
+        html.push_str(&charts_section(runs)?);
+        html.push_str(&runs_section(runs));
+        html.push_str("</body>\n</html>\n");
+
+        fs::write(path, html).map_err(|e| format!("Could not write report {}: {}", path, e))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::simulation::{
+            scenario::scenario::Scenario, simulation::simulation::Algorithms,
+            workload::workload::ArrivalProcess,
+        };
+
+        fn run(algorithm: Algorithms, repetition: u32) -> RunRecord {
+            let mut scenario = Scenario::default();
+            scenario.disk.tracks = 100;
+            scenario.disk.metadata.set_spin_speed(5);
+            scenario.workload.requests = 20;
+            scenario.workload.seed = Some(repetition as u64);
+            scenario.workload.arrival = Some(ArrivalProcess::PERIODIC { interval: 10 });
+            scenario.scheduler.algorithm = algorithm;
+            let result = scenario.run(false).unwrap();
+
+            let configuration = format!("scheduler.algorithm={}", algorithm.get_name());
+            RunRecord::new(
+                format!("{},repetition={}", configuration, repetition),
+                configuration,
+                repetition,
+                &scenario,
+                result,
+            )
+        }
+
+        #[test]
+        fn reports_are_written_next_to_the_results() {
+            assert_eq!(report_path("out/results.jsonl"), "out/results.html");
+            assert_eq!(report_path("results"), "results.html");
+            assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+        }
+
+        #[test]
+        fn reports_embed_everything_they_show() {
+            let runs: Vec<RunRecord> = (0..3)
+                .flat_map(|i| [run(Algorithms::NAIVE, i), run(Algorithms::ELEVATOR, i)])
+                .collect();
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("report.html");
+            let path = path.to_str().unwrap();
+
+            write_report(path, "Sweep <1>", &runs, &Analysis::default()).unwrap();
+            let html = fs::read_to_string(path).unwrap();
+
+            assert!(html.starts_with("<!DOCTYPE html>"));
+            assert!(html.contains("<title>Sweep &lt;1&gt;</title>"));
+            assert!(html.contains("<p>6 runs.</p>"));
+            assert!(html.contains("<svg"));
+            assert!(html.contains("scheduler.algorithm=Elevator,repetition=2"));
+            for external in ["<script", "<link", "src=", "http://", "https://"] {
+                assert!(
+                    !html.replace("http://www.w3.org/", "").contains(external),
+                    "{}",
+                    external
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,146 @@
+pub mod stats {
+    /// Summary of the response times of one run, in ticks.
+    pub struct ResponseStatistics {
+        pub count: usize,
+        pub mean: f64,
+        pub median: f64,
+        pub p90: f64,
+        pub p95: f64,
+        pub p99: f64,
+        pub p999: f64,
+        pub max: u32,
+        pub std_dev: f64,
+        /// Completed requests per million ticks, when the run length is known.
+        pub throughput: Option<f64>,
+    }
+
+    /// Nearest-rank percentile of already sorted values; `p` is in [0, 100].
+    pub fn percentile(sorted: &[u32], p: f64) -> f64 {
+        if sorted.is_empty() {
+            return 0.0;
+        }
+
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1] as f64
+    }
+
+    pub fn mean(values: &[u32]) -> f64 {
+        if values.is_empty() {
+            return 0.0;
+        }
+
+        values.iter().map(|&v| v as f64).sum::<f64>() / values.len() as f64
+    }
+
+    /// Sample standard deviation.
+    pub fn std_dev(values: &[u32]) -> f64 {
+        if values.len() < 2 {
+            return 0.0;
+        }
+
+        let mean = mean(values);
+        let squares: f64 = values.iter().map(|&v| (v as f64 - mean).powi(2)).sum();
+        (squares / (values.len() - 1) as f64).sqrt()
+    }
+
+    impl ResponseStatistics {
+        pub fn from_times(response_times: &[u32], total_time: Option<u32>) -> ResponseStatistics {
+            let mut sorted = response_times.to_vec();
+            sorted.sort_unstable();
+
+            let median = if sorted.is_empty() {
+                0.0
+            } else if sorted.len().is_multiple_of(2) {
+                (sorted[sorted.len() / 2 - 1] as f64 + sorted[sorted.len() / 2] as f64) / 2.0
+            } else {
+                sorted[sorted.len() / 2] as f64
+            };
+
+            ResponseStatistics {
+                count: sorted.len(),
+                mean: mean(&sorted),
+                median: median,
+                p90: percentile(&sorted, 90.0),
+                p95: percentile(&sorted, 95.0),
+                p99: percentile(&sorted, 99.0),
+                p999: percentile(&sorted, 99.9),
+                max: *sorted.last().unwrap_or(&0),
+                std_dev: std_dev(&sorted),
+                throughput: total_time
+                    .filter(|&time| time > 0)
+                    .map(|time| sorted.len() as f64 * 1000000.0 / time as f64),
+            }
+        }
+
+        pub fn show(&self) {
+            let throughput = match self.throughput {
+                Some(throughput) => format!("{:.3} per million ticks", throughput),
+                None => "unknown".to_owned(),
+            };
+
+            println!(
+                "Statistics <\n\trequests: {}\n\tmean: {:.2}, std dev: {:.2}\n\tmedian: {:.1}, p90: {:.1}, p95: {:.1}, p99: {:.1}, p99.9: {:.1}, max: {}\n\tthroughput: {}\n>",
+                self.count,
+                self.mean,
+                self.std_dev,
+                self.median,
+                self.p90,
+                self.p95,
+                self.p99,
+                self.p999,
+                self.max,
+                throughput
+            );
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn percentile_takes_the_nearest_rank() {
+            let sorted: Vec<u32> = (1..=10).collect();
+
+            assert_eq!(percentile(&sorted, 0.0), 1.0);
+            assert_eq!(percentile(&sorted, 50.0), 5.0);
+            assert_eq!(percentile(&sorted, 90.0), 9.0);
+            assert_eq!(percentile(&sorted, 91.0), 10.0);
+            assert_eq!(percentile(&sorted, 100.0), 10.0);
+            assert_eq!(percentile(&[], 99.0), 0.0);
+        }
+
+        #[test]
+        fn standard_deviation_is_of_the_sample() {
+            assert_eq!(std_dev(&[2, 4, 4, 4, 5, 5, 7, 9]), (32.0f64 / 7.0).sqrt());
+            assert_eq!(std_dev(&[7]), 0.0);
+        }
+
+        #[test]
+        fn statistics_sort_the_response_times() {
+            let statistics = ResponseStatistics::from_times(&[40, 10, 30, 20], Some(2000000));
+
+            assert_eq!(statistics.count, 4);
+            assert_eq!(statistics.mean, 25.0);
+            assert_eq!(statistics.median, 25.0);
+            assert_eq!(statistics.p90, 40.0);
+            assert_eq!(statistics.max, 40);
+            assert_eq!(statistics.throughput, Some(2.0));
+
+            let odd = ResponseStatistics::from_times(&[5, 1, 3], None);
+            assert_eq!(odd.median, 3.0);
+            assert_eq!(odd.throughput, None);
+        }
+
+        #[test]
+        fn statistics_of_no_responses_are_zero() {
+            let statistics = ResponseStatistics::from_times(&[], Some(0));
+
+            assert_eq!(statistics.count, 0);
+            assert_eq!(statistics.mean, 0.0);
+            assert_eq!(statistics.median, 0.0);
+            assert_eq!(statistics.max, 0);
+            assert_eq!(statistics.throughput, None);
+        }
+    }
+}
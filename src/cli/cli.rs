@@ -0,0 +1,1165 @@
+pub mod cli {
+    use clap::{Args, Parser, Subcommand};
+
+    use crate::analysis::{
+        aggregate::aggregate::{group_runs, Analysis},
+        charts::charts::{
+            draw_cdf, draw_head_trace, draw_heatmap, draw_histogram, ChartFormat, ChartKind,
+            Series, Trace,
+        },
+        comparison::comparison::{compare_algorithms, show_comparison},
+        fairness::fairness::{show_starvation, FairnessReport},
+        operations::operations::OperationReport,
+        report::report::{report_path, write_report},
+        stats::stats::ResponseStatistics,
+    };
+    use crate::disk::{
+        cache::cache::WriteCacheConfig,
+        geometry::geometry::Location,
+        mapping::mapping::Mapping,
+        rotation::rotation::{ContinuousRotation, RotationModel},
+        seek::seek::{read_seek_table, SeekCurve, SeekModel},
+        ssd::ssd::SsdConfig,
+    };
+    use crate::simulation::{
+        results::results::{read_legacy_runs, read_runs, ResultWriter, RunRecord, RunSummary},
+        scenario::scenario::{OutputConfig, OutputFormat, Scenario},
+        simulation::simulation::{run_simulation, Algorithms},
+        sweep::sweep::{SweepMode, SweepParameter, SweepSpec},
+        timeseries::timeseries::read_time_series,
+        workload::workload::{read_trace, write_trace, OperationMix},
+    };
+
+    #[derive(Parser)]
+    #[command(name = "elevator_algorithm", about = "Disk scheduling simulator")]
+    pub struct Cli {
+        #[command(subcommand)]
+        command: Command,
+    }
+
+    #[derive(Args)]
+    struct DiskArgs {
+        /// Ticks the head needs to move one track
+        #[arg(long)]
+        forward_speed: Option<u32>,
+
+        /// Ticks the platter needs to rotate one degree
+        #[arg(long)]
+        spin_speed: Option<u32>,
+
+        /// Spin the platter continuously at this many revolutions per minute,
+        /// with ticks of a microsecond
+        #[arg(long)]
+        rpm: Option<f64>,
+
+        /// Number of tracks on the disk, or cylinders when it has several heads
+        #[arg(long)]
+        tracks: Option<u32>,
+
+        /// Number of heads, one per recording surface
+        #[arg(long)]
+        heads: Option<u32>,
+
+        /// How logical blocks are laid out: `linear`, `zoned` or `serpentine`
+        #[arg(long, value_parser = Mapping::from_name)]
+        mapping: Option<Mapping>,
+
+        /// Ticks the head settles for before writing after a seek or head switch
+        #[arg(long)]
+        write_settle: Option<u32>,
+
+        /// Read every write back on the next revolution
+        #[arg(long)]
+        write_verify: bool,
+
+        /// Complete writes once in a write-back cache of this many sectors
+        #[arg(long)]
+        write_cache: Option<u32>,
+
+        /// Let the drive queue this many tasks and pick their order itself
+        #[arg(long)]
+        queue_depth: Option<u32>,
+
+        /// Simulate an SSD holding the disk's logical blocks instead of the disk
+        #[arg(long)]
+        ssd: bool,
+    }
+
+    #[derive(Args)]
+    struct SimulationArgs {
+        /// Scenario file to start from; the other flags override its values
+        #[arg(long)]
+        scenario: Option<String>,
+
+        #[command(flatten)]
+        disk: DiskArgs,
+
+        /// Number of requests to simulate
+        #[arg(long)]
+        requests: Option<u32>,
+
+        /// Seed of the random workload
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Number of clients the requests are spread over
+        #[arg(long)]
+        owners: Option<u32>,
+
+        /// Fraction of the requests that are writes, the rest being reads
+        #[arg(long)]
+        writes: Option<f64>,
+    }
+
+    #[derive(Args)]
+    struct TimeSeriesArgs {
+        /// Sample queue length, disk state and completions into this CSV file
+        #[arg(long)]
+        timeseries: Option<String>,
+
+        /// Ticks between two time series samples
+        #[arg(long)]
+        sample_interval: Option<u32>,
+    }
+
+    #[derive(Args)]
+    struct ChartArgs {
+        /// Draw the response time CDF, histogram, head trace and track heatmap into this directory
+        #[arg(long)]
+        charts: Option<String>,
+
+        /// `svg` or `png`
+        #[arg(long, value_parser = ChartFormat::from_name)]
+        chart_format: Option<ChartFormat>,
+    }
+
+    #[derive(Args)]
+    struct FormatArgs {
+        /// `legacy`, `csv`, `jsonl` or `parquet`
+        #[arg(long, value_parser = OutputFormat::from_name)]
+        format: Option<OutputFormat>,
+
+        /// Only write run summaries, not one row per task
+        #[arg(long)]
+        no_tasks: bool,
+    }
+
+    #[derive(Args)]
+    struct RunArgs {
+        #[command(flatten)]
+        simulation: SimulationArgs,
+
+        #[arg(long, value_parser = Algorithms::from_name)]
+        algorithm: Option<Algorithms>,
+
+        /// Where to write the results
+        #[arg(long)]
+        output: Option<String>,
+
+        #[command(flatten)]
+        format: FormatArgs,
+
+        #[command(flatten)]
+        timeseries: TimeSeriesArgs,
+
+        #[command(flatten)]
+        charts: ChartArgs,
+
+        /// Also save the generated workload as a trace for `replay`
+        #[arg(long)]
+        trace: Option<String>,
+
+        /// Do not print progress
+        #[arg(long)]
+        quiet: bool,
+
+        /// List the requests whose response time exceeded this many ticks
+        #[arg(long)]
+        starvation_threshold: Option<u32>,
+    }
+
+    #[derive(Args)]
+    struct ReplayArgs {
+        /// Trace file with `time,track,angle[,owner[,head[,length]]]` lines,
+        /// or `time,lba,length[,owner]` lines under a header naming `lba`,
+        /// each optionally ending with `read`, `write`, `flush` or `discard`
+        trace: String,
+
+        /// Scenario file for the disk, scheduler and output; its workload is
+        /// replaced by the trace and the other flags override its values
+        #[arg(long)]
+        scenario: Option<String>,
+
+        #[command(flatten)]
+        disk: DiskArgs,
+
+        #[arg(long, value_parser = Algorithms::from_name)]
+        algorithm: Option<Algorithms>,
+
+        #[arg(long)]
+        output: Option<String>,
+
+        #[command(flatten)]
+        format: FormatArgs,
+
+        #[command(flatten)]
+        timeseries: TimeSeriesArgs,
+
+        #[command(flatten)]
+        charts: ChartArgs,
+    }
+
+    #[derive(Args)]
+    struct SweepArgs {
+        /// Sweep specification file
+        #[arg(long)]
+        spec: Option<String>,
+
+        /// Swept parameter as `name=value,value,...`, e.g. `disk.spin_speed=25,50`
+        #[arg(long = "param")]
+        parameters: Vec<String>,
+
+        #[arg(long, value_delimiter = ',')]
+        algorithms: Option<Vec<String>>,
+
+        #[arg(long, value_delimiter = ',')]
+        forward_speeds: Option<Vec<u32>>,
+
+        #[arg(long, value_delimiter = ',')]
+        spin_speeds: Option<Vec<u32>>,
+
+        #[arg(long, value_delimiter = ',')]
+        tracks: Option<Vec<u32>>,
+
+        #[arg(long, value_delimiter = ',')]
+        requests: Option<Vec<u32>>,
+
+        /// `grid`, `list` or `random`
+        #[arg(long, value_parser = parse_sweep_mode)]
+        mode: Option<SweepMode>,
+
+        /// Number of combinations drawn by a random sweep
+        #[arg(long)]
+        samples: Option<u32>,
+
+        /// Runs per combination
+        #[arg(long)]
+        repetitions: Option<u32>,
+
+        /// Base seed; each run derives its own seed from it
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Simulations run in parallel; defaults to the number of CPU cores
+        #[arg(long)]
+        workers: Option<usize>,
+
+        #[arg(long)]
+        output: Option<String>,
+
+        #[command(flatten)]
+        format: FormatArgs,
+
+        /// Do not write an HTML report next to the results
+        #[arg(long)]
+        no_report: bool,
+
+        /// Start over even if the output has a manifest of finished runs
+        #[arg(long)]
+        fresh: bool,
+    }
+
+    #[derive(Args)]
+    struct AnalysisArgs {
+        /// Confidence level of the intervals; 1 - confidence is the significance level
+        #[arg(long, default_value_t = 0.95)]
+        confidence: f64,
+
+        /// Number of bootstrap resamples
+        #[arg(long, default_value_t = 10000)]
+        resamples: usize,
+
+        /// Seed of the bootstrap resampling
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+
+        /// Algorithm the others are compared against
+        #[arg(long, default_value = "naive", value_parser = Algorithms::from_name)]
+        baseline: Algorithms,
+    }
+
+    #[derive(Subcommand)]
+    enum Command {
+        /// Run a single simulation
+        Run(RunArgs),
+
+        /// Run a parameter sweep, by default the grid of the menu's "Log" entry
+        Sweep(SweepArgs),
+
+        /// Run a saved workload trace against a scheduler
+        Replay(ReplayArgs),
+
+        /// Run several schedulers on the same workload
+        Compare {
+            #[command(flatten)]
+            simulation: SimulationArgs,
+
+            #[arg(long, value_delimiter = ',', value_parser = Algorithms::from_name, default_value = "naive,elevator")]
+            algorithms: Vec<Algorithms>,
+
+            /// Algorithm the improvements are measured against
+            #[arg(long, default_value = "naive", value_parser = Algorithms::from_name)]
+            baseline: Algorithms,
+        },
+
+        /// Convert legacy `.txt` logs into structured results
+        Convert {
+            /// Logs written by the menu or the legacy format
+            #[arg(required = true)]
+            inputs: Vec<String>,
+
+            #[arg(long)]
+            output: String,
+
+            #[command(flatten)]
+            format: FormatArgs,
+        },
+
+        /// Draw a chart from saved results
+        Plot {
+            /// Summary outputs with their tasks file or legacy .txt logs; time
+            /// series CSV files for `trace`
+            #[arg(required = true)]
+            files: Vec<String>,
+
+            /// `cdf`, `histogram`, `trace` or `heatmap`
+            #[arg(long, value_parser = ChartKind::from_name)]
+            kind: ChartKind,
+
+            /// Chart file to write, `.svg` or `.png`
+            #[arg(long)]
+            output: String,
+
+            /// Number of histogram bins
+            #[arg(long, default_value_t = 50)]
+            bins: u32,
+        },
+
+        /// Aggregate repetitions and compare algorithms across result files
+        Analyze {
+            /// Summary outputs (.csv, .jsonl, .parquet) or legacy .txt logs
+            #[arg(required = true)]
+            files: Vec<String>,
+
+            #[command(flatten)]
+            analysis: AnalysisArgs,
+        },
+
+        /// Fit a seek curve to measured seek times and print it as `[disk.seek]`
+        FitSeek {
+            /// File of `distance,ticks` lines, settle time included
+            table: String,
+
+            /// Ticks the head needs to settle after every seek
+            #[arg(long, default_value_t = 0)]
+            settle: u32,
+        },
+
+        /// Map a logical block to its track and sector, or back
+        Locate {
+            /// Scenario file whose disk to use
+            #[arg(long)]
+            scenario: Option<String>,
+
+            #[command(flatten)]
+            disk: DiskArgs,
+
+            #[arg(long, conflicts_with_all = ["track", "head", "sector"])]
+            lba: Option<u64>,
+
+            /// Track, or cylinder when the disk has several heads
+            #[arg(long, requires = "sector")]
+            track: Option<u32>,
+
+            /// Head of the sector; defaults to 0
+            #[arg(long, requires = "track")]
+            head: Option<u32>,
+
+            #[arg(long, requires = "track")]
+            sector: Option<u32>,
+        },
+
+        /// Write an HTML report of archived results
+        Report {
+            /// Summary outputs with their tasks file or legacy .txt logs
+            #[arg(required = true)]
+            files: Vec<String>,
+
+            /// HTML file to write; defaults to the first file with an .html extension
+            #[arg(long)]
+            output: Option<String>,
+
+            #[command(flatten)]
+            analysis: AnalysisArgs,
+        },
+    }
+
+    fn apply_format_args(output: &mut OutputConfig, format: &FormatArgs) {
+        if let Some(format) = format.format {
+            output.format = format;
+        }
+        if format.no_tasks {
+            output.tasks = false;
+        }
+    }
+
+    fn apply_time_series_args(output: &mut OutputConfig, timeseries: &TimeSeriesArgs) {
+        if timeseries.timeseries.is_some() {
+            output.timeseries = timeseries.timeseries.clone();
+        }
+        if let Some(interval) = timeseries.sample_interval {
+            output.sample_interval = interval;
+        }
+    }
+
+    fn apply_chart_args(output: &mut OutputConfig, charts: &ChartArgs) {
+        if charts.charts.is_some() {
+            output.charts = charts.charts.clone();
+        }
+        if let Some(format) = charts.chart_format {
+            output.chart_format = format;
+        }
+    }
+
+    fn apply_disk_args(scenario: &mut Scenario, disk: &DiskArgs) {
+        if let Some(forward_speed) = disk.forward_speed {
+            scenario.disk.metadata.set_forward_speed(forward_speed);
+        }
+        if let Some(spin_speed) = disk.spin_speed {
+            scenario.disk.metadata.set_spin_speed(spin_speed);
+        }
+        if let Some(rpm) = disk.rpm {
+            scenario
+                .disk
+                .metadata
+                .set_rotation_model(RotationModel::CONTINUOUS(ContinuousRotation::from_rpm(rpm)));
+        }
+        if let Some(tracks) = disk.tracks {
+            scenario.disk.tracks = tracks;
+        }
+        if let Some(heads) = disk.heads {
+            scenario.disk.geometry.heads = heads;
+        }
+        if let Some(mapping) = disk.mapping {
+            scenario.disk.geometry.mapping = mapping;
+        }
+        if let Some(write_settle) = disk.write_settle {
+            scenario.disk.metadata.set_write_settle(write_settle);
+        }
+        if disk.write_verify {
+            scenario.disk.metadata.set_write_verify(true);
+        }
+        if let Some(capacity) = disk.write_cache {
+            scenario
+                .disk
+                .metadata
+                .set_write_cache(Some(WriteCacheConfig { capacity: capacity }));
+        }
+        if let Some(queue_depth) = disk.queue_depth {
+            scenario.disk.metadata.set_queue_depth(queue_depth);
+        }
+        if disk.ssd && scenario.disk.ssd.is_none() {
+            scenario.disk.ssd = Some(SsdConfig::default());
+        }
+    }
+
+    fn build_scenario(args: &SimulationArgs) -> Result<Scenario, String> {
+        let mut scenario = match &args.scenario {
+            Some(path) => Scenario::from_file(path)?,
+            None => Scenario::default(),
+        };
+
+        apply_disk_args(&mut scenario, &args.disk);
+        if let Some(requests) = args.requests {
+            scenario.workload.requests = requests;
+        }
+        if args.seed.is_some() {
+            scenario.workload.seed = args.seed;
+        }
+        if let Some(owners) = args.owners {
+            scenario.workload.owners = owners;
+        }
+        if let Some(writes) = args.writes {
+            if !(0.0..=1.0).contains(&writes) {
+                return Err("--writes must be between 0 and 1".to_owned());
+            }
+            scenario.workload.mix = Some(OperationMix::from_write_ratio(writes));
+        }
+
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn run(args: RunArgs) -> Result<(), String> {
+        let mut scenario = build_scenario(&args.simulation)?;
+        if let Some(algorithm) = args.algorithm {
+            scenario.scheduler.algorithm = algorithm;
+        }
+        if args.output.is_some() {
+            scenario.output.path = args.output;
+        }
+        apply_format_args(&mut scenario.output, &args.format);
+        apply_time_series_args(&mut scenario.output, &args.timeseries);
+        apply_chart_args(&mut scenario.output, &args.charts);
+        scenario.validate()?;
+
+        let seed = scenario.workload.resolve_seed();
+        scenario.workload.seed = Some(seed);
+        if let Some(trace) = args.trace {
+            write_trace(
+                &trace,
+                &scenario.workload.generate(
+                    scenario.disk.tracks,
+                    scenario.disk.geometry.heads,
+                    seed,
+                )?,
+            )?;
+        }
+
+        let result = scenario.run(!args.quiet)?;
+        if !args.quiet {
+            ResponseStatistics::from_times(&result.response_times, Some(result.total_time)).show();
+            result.disk.show();
+            if let Some(flash) = &result.flash {
+                flash.show();
+            }
+            let geometry = &scenario.disk.geometry;
+            if geometry.zones.is_zoned() || geometry.is_multi_head() {
+                geometry.show(scenario.disk.metadata.get_rotation_period());
+            }
+            FairnessReport::from_tasks(&result.tasks).show();
+            let operations = OperationReport::from_tasks(&result.tasks);
+            if operations.is_mixed() {
+                operations.show();
+            }
+        }
+        if let Some(threshold) = args.starvation_threshold {
+            show_starvation(&result.tasks, threshold);
+        }
+        let output_path = scenario.write_output(result)?;
+        println!("Results were written to {}", output_path);
+
+        Ok(())
+    }
+
+    fn parse_sweep_mode(name: &str) -> Result<SweepMode, String> {
+        match name.trim().to_lowercase().as_str() {
+            "grid" => Ok(SweepMode::GRID),
+            "list" => Ok(SweepMode::LIST),
+            "random" => Ok(SweepMode::RANDOM),
+            _ => Err(format!(
+                "Unknown sweep mode `{}`, expected `grid`, `list` or `random`",
+                name
+            )),
+        }
+    }
+
+    fn parse_value(value: &str) -> toml::Value {
+        match format!("value = {}", value).parse::<toml::Table>() {
+            Ok(mut table) => table.remove("value").unwrap(),
+            Err(_) => toml::Value::String(value.to_owned()),
+        }
+    }
+
+    fn parse_parameter(parameter: &str) -> Result<SweepParameter, String> {
+        let (name, values) = parameter
+            .split_once('=')
+            .ok_or_else(|| format!("expected `name=value,...`, found `{}`", parameter))?;
+
+        let values = match parse_value(&format!("[{}]", values)) {
+            toml::Value::Array(values) => values,
+            _ => values.split(',').map(|v| parse_value(v.trim())).collect(),
+        };
+
+        Ok(SweepParameter::new(name.trim(), values))
+    }
+
+    fn sweep_args_to_spec(args: SweepArgs) -> Result<SweepSpec, String> {
+        let mut parameters: Vec<SweepParameter> = Vec::new();
+        if let Some(algorithms) = args.algorithms {
+            for algorithm in algorithms.iter() {
+                Algorithms::from_name(algorithm)?;
+            }
+            parameters.push(SweepParameter::new(
+                "scheduler.algorithm",
+                algorithms
+                    .iter()
+                    .map(|a| parse_value(&a.to_lowercase()))
+                    .collect(),
+            ));
+        }
+
+        let shorthands = [
+            ("disk.forward_speed", args.forward_speeds),
+            ("disk.spin_speed", args.spin_speeds),
+            ("disk.tracks", args.tracks),
+            ("workload.requests", args.requests),
+        ];
+        for (name, values) in shorthands {
+            if let Some(values) = values {
+                parameters.push(SweepParameter::new(
+                    name,
+                    values
+                        .iter()
+                        .map(|&v| toml::Value::Integer(v as i64))
+                        .collect(),
+                ));
+            }
+        }
+
+        for parameter in args.parameters.iter() {
+            parameters.push(parse_parameter(parameter)?);
+        }
+
+        let mut spec = match &args.spec {
+            Some(path) => SweepSpec::from_file(path)?,
+            None if parameters.is_empty() => SweepSpec::legacy(),
+            None => SweepSpec::default(),
+        };
+
+        for parameter in parameters {
+            spec.parameters.retain(|p| p.name != parameter.name);
+            spec.parameters.push(parameter);
+        }
+        if let Some(mode) = args.mode {
+            spec.mode = mode;
+        }
+        if args.samples.is_some() {
+            spec.samples = args.samples;
+        }
+        if let Some(repetitions) = args.repetitions {
+            spec.repetitions = repetitions;
+        }
+        if args.seed.is_some() {
+            spec.seed = args.seed;
+        }
+        if args.workers.is_some() {
+            spec.workers = args.workers;
+        }
+        if args.output.is_some() {
+            spec.output.path = args.output;
+        }
+        apply_format_args(&mut spec.output, &args.format);
+        if args.no_report {
+            spec.output.report = false;
+        }
+
+        Ok(spec)
+    }
+
+    fn sweep(args: SweepArgs) -> Result<(), String> {
+        let fresh = args.fresh;
+        let spec = sweep_args_to_spec(args)?;
+        let output_path = spec.run(false, fresh)?;
+        println!("Results were written to {}", output_path);
+
+        Ok(())
+    }
+
+    /// The scenario a trace is replayed in, before its workload is replaced.
+    fn build_replay_scenario(args: &ReplayArgs) -> Result<Scenario, String> {
+        let mut scenario = match &args.scenario {
+            Some(path) => Scenario::from_file(path)?,
+            None => Scenario::default(),
+        };
+        apply_disk_args(&mut scenario, &args.disk);
+        if let Some(algorithm) = args.algorithm {
+            scenario.scheduler.algorithm = algorithm;
+        }
+        if args.output.is_some() {
+            scenario.output.path = args.output.clone();
+        }
+        apply_format_args(&mut scenario.output, &args.format);
+        apply_time_series_args(&mut scenario.output, &args.timeseries);
+        apply_chart_args(&mut scenario.output, &args.charts);
+
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn replay(args: ReplayArgs) -> Result<(), String> {
+        let mut scenario = build_replay_scenario(&args)?;
+        let trace = args.trace;
+        let arrivals = read_trace(&trace, &scenario.disk.geometry, scenario.disk.tracks)?;
+        if arrivals.is_empty() {
+            return Err(format!("{}: the trace has no requests", trace));
+        }
+        scenario.workload.requests = arrivals.len() as u32;
+        scenario.validate()?;
+
+        let result = run_simulation(
+            scenario.scheduler,
+            scenario.disk.build(),
+            &arrivals,
+            false,
+            scenario.output.get_sample_interval(),
+        )?;
+        let output_path = scenario.write_output(result)?;
+        println!("Results were written to {}", output_path);
+
+        Ok(())
+    }
+
+    fn compare(
+        simulation: SimulationArgs,
+        algorithms: Vec<Algorithms>,
+        baseline: Algorithms,
+    ) -> Result<(), String> {
+        if !algorithms.contains(&baseline) {
+            return Err(format!(
+                "The baseline {} is not one of the compared algorithms",
+                baseline.get_name()
+            ));
+        }
+
+        let scenario = build_scenario(&simulation)?;
+        let seed = scenario.workload.resolve_seed();
+        let arrivals =
+            scenario
+                .workload
+                .generate(scenario.disk.tracks, scenario.disk.geometry.heads, seed)?;
+
+        println!("Workload seed: {}", seed);
+        show_comparison(
+            &compare_algorithms(scenario.scheduler, &scenario.disk, &arrivals, &algorithms)?,
+            baseline,
+        );
+
+        Ok(())
+    }
+
+    fn convert(inputs: Vec<String>, output: String, format: FormatArgs) -> Result<(), String> {
+        let mut config = OutputConfig::default();
+        config.format = OutputFormat::CSV;
+        apply_format_args(&mut config, &format);
+
+        let mut writer = ResultWriter::create(&output, config.format, config.tasks)?;
+        let runs = read_legacy_runs(&inputs)?;
+        for (scenario, run) in runs.iter() {
+            writer.write_run(scenario, run)?;
+        }
+        writer.finish()?;
+
+        println!("Converted {} runs into {}", runs.len(), output);
+        Ok(())
+    }
+
+    /// Response times of the runs, one series per algorithm.
+    fn response_series(runs: &[RunRecord]) -> Vec<Series> {
+        let mut series: Vec<Series> = Vec::new();
+        for run in runs.iter() {
+            let times = run.tasks.iter().map(|task| task.response_time);
+            match series.iter_mut().find(|s| s.label == run.summary.algorithm) {
+                Some(s) => s.values.extend(times),
+                None => series.push(Series {
+                    label: run.summary.algorithm.clone(),
+                    values: times.collect(),
+                }),
+            }
+        }
+
+        series
+    }
+
+    fn plot(files: Vec<String>, kind: ChartKind, output: String, bins: u32) -> Result<(), String> {
+        match kind {
+            ChartKind::CDF => draw_cdf(&output, &response_series(&read_runs(&files, true)?))?,
+            ChartKind::HISTOGRAM => {
+                draw_histogram(&output, &response_series(&read_runs(&files, true)?), bins)?
+            }
+            ChartKind::TRACE => {
+                let mut traces = Vec::new();
+                for file in files.iter() {
+                    traces.push(Trace {
+                        label: file.clone(),
+                        points: read_time_series(file)?
+                            .iter()
+                            .map(|sample| (sample.time, sample.track))
+                            .collect(),
+                    });
+                }
+                let max_track = traces
+                    .iter()
+                    .flat_map(|t| t.points.iter())
+                    .map(|&(_, track)| track)
+                    .max()
+                    .unwrap_or(1);
+
+                draw_head_trace(&output, &traces, max_track)?
+            }
+            ChartKind::HEATMAP => {
+                let runs = read_runs(&files, true)?;
+                let mut accesses = Vec::new();
+                for task in runs.iter().flat_map(|run| run.tasks.iter()) {
+                    match (task.completion, task.track) {
+                        (Some(completion), Some(track)) => accesses.push((completion, track)),
+                        _ => {
+                            return Err(
+                                "A heatmap needs the track and completion time of each task, which legacy logs do not keep"
+                                    .to_owned(),
+                            )
+                        }
+                    }
+                }
+                let max_track = runs.iter().map(|run| run.summary.tracks).max().unwrap_or(1);
+
+                draw_heatmap(&output, &accesses, max_track)?
+            }
+        }
+
+        println!("The chart was written to {}", output);
+        Ok(())
+    }
+
+    fn build_analysis(args: &AnalysisArgs) -> Result<Analysis, String> {
+        let analysis = Analysis {
+            confidence: args.confidence,
+            resamples: args.resamples,
+            seed: args.seed,
+            baseline: args.baseline,
+        };
+        analysis.validate()?;
+
+        Ok(analysis)
+    }
+
+    fn analyze(files: Vec<String>, args: AnalysisArgs) -> Result<(), String> {
+        let analysis = build_analysis(&args)?;
+
+        let summaries: Vec<RunSummary> = read_runs(&files, false)?
+            .into_iter()
+            .map(|run| run.summary)
+            .collect();
+        if summaries.is_empty() {
+            return Err("There are no runs to analyze".to_owned());
+        }
+
+        analysis.show(&group_runs(summaries));
+        Ok(())
+    }
+
+    fn locate(
+        scenario: Option<String>,
+        disk: DiskArgs,
+        lba: Option<u64>,
+        location: Option<Location>,
+    ) -> Result<(), String> {
+        let mut scenario = match &scenario {
+            Some(path) => Scenario::from_file(path)?,
+            None => Scenario::default(),
+        };
+        apply_disk_args(&mut scenario, &disk);
+        scenario.validate()?;
+
+        let cylinders = scenario.disk.tracks;
+        let geometry = &scenario.disk.geometry;
+        let mapper = geometry.get_mapper(cylinders);
+        let (lba, location) = match (lba, location) {
+            (Some(lba), _) => match mapper.to_location(lba) {
+                Some(location) => (lba, location),
+                None => {
+                    return Err(format!(
+                        "Block {} is beyond the {} blocks of the disk",
+                        lba,
+                        mapper.get_capacity()
+                    ))
+                }
+            },
+            (None, Some(location)) => match mapper.to_lba(location) {
+                Some(lba) => (lba, location),
+                None => {
+                    return Err(format!(
+                        "No block is mapped to sector {} under head {} on track {}",
+                        location.sector, location.head, location.cylinder
+                    ))
+                }
+            },
+            (None, None) => {
+                println!(
+                    "{} blocks on {} tracks, mapped {}",
+                    mapper.get_capacity(),
+                    cylinders,
+                    mapper.get_name()
+                );
+                if geometry.zones.is_zoned() || geometry.is_multi_head() {
+                    geometry.show(scenario.disk.metadata.get_rotation_period());
+                }
+                return Ok(());
+            }
+        };
+
+        println!(
+            "Block {}: track {}, head {}, sector {} of {}, starting at {} degrees",
+            lba,
+            location.cylinder,
+            location.head,
+            location.sector,
+            geometry.get_sectors(location.cylinder),
+            mapper.get_angle(location)
+        );
+
+        Ok(())
+    }
+
+    fn fit_seek(table: String, settle: u32) -> Result<(), String> {
+        let measurements = read_seek_table(&table)?;
+        let curve = SeekCurve::fit(&measurements, settle)?;
+
+        curve.show();
+        println!("# distance  measured  fitted");
+        for (distance, time) in measurements.iter() {
+            println!(
+                "# {:>8}  {:>8.1}  {:>6}",
+                distance,
+                time,
+                SeekModel::CURVE(curve).get_seek_time(*distance)
+            );
+        }
+
+        Ok(())
+    }
+
+    fn report(
+        files: Vec<String>,
+        output: Option<String>,
+        args: AnalysisArgs,
+    ) -> Result<(), String> {
+        let analysis = build_analysis(&args)?;
+        let runs = read_runs(&files, true)?;
+        if runs.is_empty() {
+            return Err("There are no runs to report on".to_owned());
+        }
+
+        let output = output.unwrap_or_else(|| report_path(&files[0]));
+        write_report(&output, &files.join(", "), &runs, &analysis)?;
+        println!("Report written to {}", output);
+
+        Ok(())
+    }
+
+    pub fn run_cli() -> Result<(), String> {
+        let cli = Cli::parse();
+
+        match cli.command {
+            Command::Run(args) => run(args),
+            Command::Sweep(args) => sweep(args),
+            Command::Replay(args) => replay(args),
+            Command::Compare {
+                simulation,
+                algorithms,
+                baseline,
+            } => compare(simulation, algorithms, baseline),
+            Command::Convert {
+                inputs,
+                output,
+                format,
+            } => convert(inputs, output, format),
+            Command::Plot {
+                files,
+                kind,
+                output,
+                bins,
+            } => plot(files, kind, output, bins),
+            Command::Analyze { files, analysis } => analyze(files, analysis),
+            Command::FitSeek { table, settle } => fit_seek(table, settle),
+            Command::Locate {
+                scenario,
+                disk,
+                lba,
+                track,
+                head,
+                sector,
+            } => locate(
+                scenario,
+                disk,
+                lba,
+                track.zip(sector).map(|(track, sector)| Location {
+                    cylinder: track,
+                    head: head.unwrap_or(0),
+                    sector: sector,
+                }),
+            ),
+            Command::Report {
+                files,
+                output,
+                analysis,
+            } => report(files, output, analysis),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+            Cli::try_parse_from([&["elevator_algorithm"], args].concat()).map(|cli| cli.command)
+        }
+
+        fn simulation_args(args: &[&str]) -> SimulationArgs {
+            match parse(&[&["run"], args].concat()).unwrap() {
+                Command::Run(args) => args.simulation,
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn flags_override_the_default_scenario() {
+            let scenario = build_scenario(&simulation_args(&[
+                "--forward-speed",
+                "3",
+                "--tracks",
+                "50",
+                "--requests",
+                "7",
+                "--seed",
+                "9",
+            ]))
+            .unwrap();
+
+            assert_eq!(*scenario.disk.metadata.get_forward_speed(), 3);
+            assert_eq!(scenario.disk.tracks, 50);
+            assert_eq!(scenario.workload.requests, 7);
+            assert_eq!(scenario.workload.seed, Some(9));
+
+            let scenario = build_scenario(&simulation_args(&[])).unwrap();
+            assert_eq!(scenario.disk.tracks, 10000);
+            assert_eq!(scenario.workload.seed, None);
+        }
+
+        #[test]
+        fn invalid_arguments_are_rejected() {
+            assert!(build_scenario(&simulation_args(&["--tracks", "0"])).is_err());
+            assert!(build_scenario(&simulation_args(&["--requests", "0"])).is_err());
+            assert!(parse(&["run", "--algorithm", "lifo"]).is_err());
+            assert!(parse(&["run", "--requests", "-1"]).is_err());
+            assert!(parse(&["analyze"]).is_err());
+        }
+
+        fn sweep_spec(args: &[&str]) -> Result<SweepSpec, String> {
+            match parse(&[&["sweep"], args].concat()).unwrap() {
+                Command::Sweep(args) => sweep_args_to_spec(args),
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn sweep_flags_become_parameters() {
+            let spec = sweep_spec(&[
+                "--algorithms",
+                "naive,Elevator",
+                "--spin-speeds",
+                "1,2,3",
+                "--param",
+                "scheduler.pick_up_on_the_way=true,false",
+                "--param",
+                "disk.spin_speed=4, 5",
+                "--mode",
+                "list",
+            ])
+            .unwrap();
+
+            let names: Vec<&str> = spec.parameters.iter().map(|p| p.name.as_str()).collect();
+            assert_eq!(
+                names,
+                vec![
+                    "scheduler.algorithm",
+                    "scheduler.pick_up_on_the_way",
+                    "disk.spin_speed"
+                ]
+            );
+            assert_eq!(
+                spec.parameters[0].values,
+                vec![
+                    toml::Value::String("naive".to_owned()),
+                    toml::Value::String("elevator".to_owned())
+                ]
+            );
+            assert_eq!(spec.parameters[1].values[1], toml::Value::Boolean(false));
+            assert_eq!(
+                spec.parameters[2].values,
+                vec![toml::Value::Integer(4), toml::Value::Integer(5)]
+            );
+            assert!(spec.mode == SweepMode::LIST);
+        }
+
+        #[test]
+        fn a_sweep_without_parameters_runs_the_legacy_grid() {
+            let spec = sweep_spec(&["--repetitions", "2"]).unwrap();
+
+            assert_eq!(spec.parameters.len(), 5);
+            assert_eq!(spec.repetitions, 2);
+            assert!(sweep_spec(&["--algorithms", "lifo"]).is_err());
+            assert!(sweep_spec(&["--param", "disk.spin_speed"]).is_err());
+        }
+
+        fn replay_scenario(args: &[&str]) -> Result<Scenario, String> {
+            match parse(&[&["replay", "trace.csv"], args].concat()).unwrap() {
+                Command::Replay(args) => build_replay_scenario(&args),
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn replays_start_from_the_scenario_file() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("scenario.toml");
+            std::fs::write(
+                &path,
+                "[disk]\ntracks = 100\n\n[workload]\nrequests = 5\n\n[scheduler]\nalgorithm = \"naive\"\n\n[output]\nformat = \"jsonl\"\n",
+            )
+            .unwrap();
+            let path = path.to_str().unwrap();
+
+            let scenario = replay_scenario(&["--scenario", path]).unwrap();
+            assert_eq!(scenario.disk.tracks, 100);
+            assert!(scenario.scheduler.algorithm == Algorithms::NAIVE);
+            assert!(matches!(scenario.output.format, OutputFormat::JSONL));
+
+            let scenario = replay_scenario(&[
+                "--scenario",
+                path,
+                "--format",
+                "csv",
+                "--no-tasks",
+                "--algorithm",
+                "elevator",
+            ])
+            .unwrap();
+            assert_eq!(scenario.disk.tracks, 100);
+            assert!(scenario.scheduler.algorithm == Algorithms::ELEVATOR);
+            assert!(matches!(scenario.output.format, OutputFormat::CSV));
+            assert!(!scenario.output.tasks);
+
+            let scenario = replay_scenario(&[]).unwrap();
+            assert_eq!(scenario.disk.tracks, 10000);
+            assert!(replay_scenario(&["--scenario", "missing.toml"]).is_err());
+        }
+
+        #[test]
+        fn the_baseline_has_to_be_compared() {
+            let (simulation, algorithms, baseline) =
+                match parse(&["compare", "--algorithms", "elevator", "--requests", "5"]).unwrap() {
+                    Command::Compare {
+                        simulation,
+                        algorithms,
+                        baseline,
+                    } => (simulation, algorithms, baseline),
+                    _ => unreachable!(),
+                };
+
+            assert!(baseline == Algorithms::NAIVE);
+            let error = compare(simulation, algorithms, baseline).err().unwrap();
+            assert!(error.contains("baseline Naive"), "{}", error);
+        }
+    }
+}
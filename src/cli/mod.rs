@@ -0,0 +1,2 @@
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod cli;
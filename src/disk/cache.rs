@@ -0,0 +1,488 @@
+pub mod cache {
+    use serde::{Deserialize, Serialize};
+
+    /// The drive's read buffer, split into segments that each hold a run of
+    /// consecutive sectors of one track.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct CacheConfig {
+        pub segments: u32,
+        /// Sectors a segment holds.
+        pub segment_size: u32,
+        /// Sectors the drive keeps reading past the end of a read while the
+        /// head stays where it is.
+        #[serde(default)]
+        pub read_ahead: u32,
+    }
+
+    /// A write-back buffer: writes complete once they are in it and reach
+    /// the platter later, when the drive has nothing else to do or a flush
+    /// asks for it.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct WriteCacheConfig {
+        /// Sectors the buffer holds; writes that do not fit go to the platter.
+        pub capacity: u32,
+    }
+
+    /// Consecutive sectors of one track, from `first` on, wrapping around
+    /// the end of the track.
+    #[derive(Copy, Clone, PartialEq)]
+    pub struct Extent {
+        pub cylinder: u32,
+        pub head: u32,
+        pub first: u32,
+        pub count: u32,
+        /// Sectors on the track.
+        pub sectors: u32,
+    }
+
+    /// A read whose following sectors are still being read into its segment.
+    struct ReadAhead {
+        extent: Extent,
+        /// When the read ended.
+        since: u64,
+        /// Ticks a sector takes to pass under the head.
+        sector_time: u32,
+    }
+
+    /// Segments in least recently used order, the most recent first.
+    pub struct ReadCache {
+        config: CacheConfig,
+        segments: Vec<Extent>,
+        read_ahead: Option<ReadAhead>,
+    }
+
+    /// Sectors written to the buffer but not yet to the platter, in the
+    /// order they were written. No two dirty extents overlap.
+    pub struct WriteBuffer {
+        config: WriteCacheConfig,
+        dirty: Vec<Extent>,
+    }
+
+    impl WriteCacheConfig {
+        pub fn validate(&self) -> Result<(), String> {
+            if self.capacity == 0 {
+                return Err("disk.write_cache.capacity must be at least 1".to_owned());
+            }
+
+            Ok(())
+        }
+    }
+
+    impl CacheConfig {
+        pub fn validate(&self) -> Result<(), String> {
+            if self.segments == 0 {
+                return Err("disk.cache.segments must be at least 1".to_owned());
+            }
+            if self.segment_size == 0 {
+                return Err("disk.cache.segment_size must be at least 1".to_owned());
+            }
+
+            Ok(())
+        }
+    }
+
+    impl Extent {
+        fn is_on_track_of(&self, other: &Extent) -> bool {
+            self.cylinder == other.cylinder && self.head == other.head
+        }
+
+        /// Sectors from this extent's first one to `sector`.
+        fn get_offset(&self, sector: u32) -> u32 {
+            (sector + self.sectors - self.first % self.sectors) % self.sectors
+        }
+
+        pub fn contains(&self, other: &Extent) -> bool {
+            self.is_on_track_of(other) && self.get_offset(other.first) + other.count <= self.count
+        }
+
+        pub fn overlaps(&self, other: &Extent) -> bool {
+            self.is_on_track_of(other)
+                && (self.get_offset(other.first) < self.count
+                    || other.get_offset(self.first) < other.count)
+        }
+
+        /// The runs of this extent's sectors that `other` does not cover; at
+        /// most two, either side of it.
+        pub fn subtract(&self, other: &Extent) -> Vec<Extent> {
+            if !self.overlaps(other) {
+                return vec![*self];
+            }
+
+            let mut runs: Vec<Extent> = Vec::new();
+            let mut run: Option<Extent> = None;
+            for index in 0..self.count {
+                let sector = (self.first + index) % self.sectors;
+                if other.get_offset(sector) < other.count {
+                    runs.extend(run.take());
+                } else if let Some(run) = run.as_mut() {
+                    run.count += 1;
+                } else {
+                    run = Some(Extent {
+                        first: sector,
+                        count: 1,
+                        ..*self
+                    });
+                }
+            }
+            runs.extend(run);
+
+            runs
+        }
+    }
+
+    impl WriteBuffer {
+        pub fn new(config: WriteCacheConfig) -> WriteBuffer {
+            WriteBuffer {
+                config: config,
+                dirty: Vec::new(),
+            }
+        }
+
+        pub fn get_dirty_sectors(&self) -> u32 {
+            self.dirty.iter().map(|extent| extent.count).sum()
+        }
+
+        pub fn is_clean(&self) -> bool {
+            self.dirty.is_empty()
+        }
+
+        /// Whether one dirty extent holds all of `extent`.
+        pub fn contains(&self, extent: &Extent) -> bool {
+            self.dirty.iter().any(|dirty| dirty.contains(extent))
+        }
+
+        /// Takes `extent` in, replacing whatever older writes it covers,
+        /// unless it does not fit.
+        pub fn write(&mut self, extent: Extent) -> bool {
+            let dirty: Vec<Extent> = self
+                .dirty
+                .iter()
+                .flat_map(|dirty| dirty.subtract(&extent))
+                .collect();
+            let sectors: u32 = dirty.iter().map(|dirty| dirty.count).sum();
+            if sectors + extent.count > self.config.capacity {
+                return false;
+            }
+
+            self.dirty = dirty;
+            self.dirty.push(extent);
+            true
+        }
+
+        /// Forgets the dirty sectors of `extent`, which were written to the
+        /// platter or discarded since.
+        pub fn remove(&mut self, extent: &Extent) {
+            self.dirty = self
+                .dirty
+                .iter()
+                .flat_map(|dirty| dirty.subtract(extent))
+                .collect();
+        }
+
+        /// Takes out the dirty extent to write back next: the one closest to
+        /// `cylinder`, the oldest of those.
+        pub fn take_nearest(&mut self, cylinder: u32) -> Option<Extent> {
+            let index = self
+                .dirty
+                .iter()
+                .enumerate()
+                .min_by_key(|(index, extent)| (extent.cylinder.abs_diff(cylinder), *index))?
+                .0;
+
+            Some(self.dirty.remove(index))
+        }
+    }
+
+    impl ReadCache {
+        pub fn new(config: CacheConfig) -> ReadCache {
+            ReadCache {
+                config: config,
+                segments: Vec::new(),
+                read_ahead: None,
+            }
+        }
+
+        /// Grows the segment being read ahead into by the sectors that passed
+        /// under the head up to `time`.
+        fn catch_up(&mut self, time: u64) {
+            let read_ahead = match &self.read_ahead {
+                Some(read_ahead) => read_ahead,
+                None => return,
+            };
+
+            let passed = (time.saturating_sub(read_ahead.since) / read_ahead.sector_time as u64)
+                .min(self.config.read_ahead as u64) as u32;
+            let count = (read_ahead.extent.count + passed)
+                .min(self.config.segment_size)
+                .min(read_ahead.extent.sectors);
+            let extent = read_ahead.extent;
+            if let Some(segment) = self
+                .segments
+                .iter_mut()
+                .find(|segment| segment.is_on_track_of(&extent) && segment.first == extent.first)
+            {
+                segment.count = segment.count.max(count);
+            }
+        }
+
+        /// Keeps what was read ahead up to `time`; the head is about to do
+        /// something else.
+        pub fn stop_read_ahead(&mut self, time: u64) {
+            self.catch_up(time);
+            self.read_ahead = None;
+        }
+
+        /// Keeps the sectors of a read that ended at `time` in a fresh
+        /// segment, evicting the least recently used one when all are taken,
+        /// and starts reading ahead after them.
+        pub fn fill(&mut self, extent: Extent, time: u64, sector_time: u32) {
+            self.stop_read_ahead(time);
+            self.invalidate(&extent, time);
+
+            let extent = Extent {
+                count: extent
+                    .count
+                    .min(self.config.segment_size)
+                    .min(extent.sectors),
+                ..extent
+            };
+            self.segments.insert(0, extent);
+            self.segments.truncate(self.config.segments as usize);
+            if self.config.read_ahead > 0 {
+                self.read_ahead = Some(ReadAhead {
+                    extent: extent,
+                    since: time,
+                    sector_time: sector_time.max(1),
+                });
+            }
+        }
+
+        /// Whether one segment holds all of `extent` at `time`, which makes
+        /// that segment the most recently used.
+        pub fn lookup(&mut self, extent: &Extent, time: u64) -> bool {
+            self.catch_up(time);
+            match self
+                .segments
+                .iter()
+                .position(|segment| segment.contains(extent))
+            {
+                Some(index) => {
+                    let segment = self.segments.remove(index);
+                    self.segments.insert(0, segment);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        /// Drops every segment holding a sector of `extent`, which is about
+        /// to be overwritten or was discarded at `time`. Reading ahead on the
+        /// same track stops so that it cannot pick the sectors up again.
+        pub fn invalidate(&mut self, extent: &Extent, time: u64) {
+            if self
+                .read_ahead
+                .as_ref()
+                .is_some_and(|read_ahead| read_ahead.extent.is_on_track_of(extent))
+            {
+                self.stop_read_ahead(time);
+            }
+            self.segments.retain(|segment| !segment.overlaps(extent));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Sectors `first` to `first + count` of cylinder 1, head 0, on a
+        /// track of 10 sectors.
+        fn extent(first: u32, count: u32) -> Extent {
+            Extent {
+                cylinder: 1,
+                head: 0,
+                first: first,
+                count: count,
+                sectors: 10,
+            }
+        }
+
+        fn runs(extents: &[Extent]) -> Vec<(u32, u32)> {
+            extents
+                .iter()
+                .map(|extent| (extent.first, extent.count))
+                .collect()
+        }
+
+        fn read_cache(segments: u32, segment_size: u32, read_ahead: u32) -> ReadCache {
+            ReadCache::new(CacheConfig {
+                segments: segments,
+                segment_size: segment_size,
+                read_ahead: read_ahead,
+            })
+        }
+
+        #[test]
+        fn extents_wrap_around_the_end_of_the_track() {
+            let wrapping = extent(8, 4);
+
+            assert!(wrapping.contains(&extent(9, 1)));
+            assert!(wrapping.contains(&extent(0, 2)));
+            assert!(!wrapping.contains(&extent(1, 2)));
+            assert!(wrapping.overlaps(&extent(1, 3)));
+            assert!(extent(1, 3).overlaps(&wrapping));
+            assert!(!wrapping.overlaps(&extent(2, 6)));
+        }
+
+        #[test]
+        fn extents_on_other_tracks_do_not_overlap() {
+            let other_head = Extent {
+                head: 1,
+                ..extent(0, 4)
+            };
+
+            assert!(!extent(0, 4).overlaps(&other_head));
+            assert!(!extent(0, 4).contains(&other_head));
+        }
+
+        #[test]
+        fn subtracting_leaves_the_runs_either_side() {
+            assert_eq!(
+                runs(&extent(0, 6).subtract(&extent(2, 2))),
+                vec![(0, 2), (4, 2)]
+            );
+            assert_eq!(
+                runs(&extent(8, 4).subtract(&extent(9, 1))),
+                vec![(8, 1), (0, 2)]
+            );
+            assert_eq!(runs(&extent(0, 4).subtract(&extent(2, 4))), vec![(0, 2)]);
+            assert_eq!(runs(&extent(0, 4).subtract(&extent(4, 2))), vec![(0, 4)]);
+            assert!(extent(2, 2).subtract(&extent(0, 6)).is_empty());
+        }
+
+        #[test]
+        fn read_cache_hits_sectors_of_a_filled_segment() {
+            let mut cache = read_cache(2, 8, 0);
+            cache.fill(extent(2, 4), 0, 1);
+
+            assert!(cache.lookup(&extent(3, 2), 10));
+            assert!(!cache.lookup(&extent(5, 2), 10));
+        }
+
+        #[test]
+        fn read_cache_caps_segments_at_their_size() {
+            let mut cache = read_cache(1, 3, 0);
+            cache.fill(extent(0, 6), 0, 1);
+
+            assert!(cache.lookup(&extent(0, 3), 0));
+            assert!(!cache.lookup(&extent(3, 1), 0));
+        }
+
+        #[test]
+        fn read_cache_evicts_the_least_recently_used_segment() {
+            let mut cache = read_cache(2, 8, 0);
+            cache.fill(extent(0, 2), 0, 1);
+            cache.fill(extent(4, 2), 0, 1);
+            assert!(cache.lookup(&extent(0, 2), 0));
+
+            cache.fill(extent(8, 1), 0, 1);
+
+            assert!(cache.lookup(&extent(0, 2), 0));
+            assert!(cache.lookup(&extent(8, 1), 0));
+            assert!(!cache.lookup(&extent(4, 2), 0));
+        }
+
+        #[test]
+        fn read_ahead_grows_the_segment_as_sectors_pass() {
+            let mut cache = read_cache(1, 8, 4);
+            cache.fill(extent(0, 2), 100, 10);
+
+            assert!(!cache.lookup(&extent(2, 2), 115));
+            assert!(cache.lookup(&extent(2, 2), 120));
+            // No further than the read ahead.
+            assert!(cache.lookup(&extent(0, 6), 1000));
+            assert!(!cache.lookup(&extent(6, 1), 1000));
+        }
+
+        #[test]
+        fn stopped_read_ahead_keeps_what_it_read() {
+            let mut cache = read_cache(1, 8, 4);
+            cache.fill(extent(0, 2), 100, 10);
+            cache.stop_read_ahead(110);
+
+            assert!(cache.lookup(&extent(2, 1), 1000));
+            assert!(!cache.lookup(&extent(3, 1), 1000));
+        }
+
+        #[test]
+        fn invalidating_drops_overlapping_segments() {
+            let mut cache = read_cache(2, 8, 0);
+            cache.fill(extent(0, 2), 0, 1);
+            cache.fill(extent(4, 2), 0, 1);
+
+            cache.invalidate(&extent(5, 1), 0);
+
+            assert!(cache.lookup(&extent(0, 2), 0));
+            assert!(!cache.lookup(&extent(4, 1), 0));
+        }
+
+        fn write_buffer(capacity: u32) -> WriteBuffer {
+            WriteBuffer::new(WriteCacheConfig { capacity: capacity })
+        }
+
+        #[test]
+        fn write_buffer_replaces_the_sectors_it_overwrites() {
+            let mut buffer = write_buffer(16);
+            assert!(buffer.write(extent(0, 4)));
+            assert!(buffer.write(extent(2, 4)));
+
+            assert_eq!(buffer.get_dirty_sectors(), 6);
+            assert_eq!(runs(&buffer.dirty), vec![(0, 2), (2, 4)]);
+            assert!(buffer.contains(&extent(3, 3)));
+            assert!(!buffer.contains(&extent(1, 3)));
+        }
+
+        #[test]
+        fn write_buffer_turns_down_writes_that_do_not_fit() {
+            let mut buffer = write_buffer(8);
+            assert!(buffer.write(extent(0, 6)));
+
+            // Rewriting dirty sectors takes no more room.
+            assert!(buffer.write(extent(0, 6)));
+            assert!(buffer.write(extent(4, 4)));
+            assert!(!buffer.write(extent(8, 1)));
+            assert_eq!(buffer.get_dirty_sectors(), 8);
+        }
+
+        #[test]
+        fn write_buffer_forgets_removed_sectors() {
+            let mut buffer = write_buffer(16);
+            buffer.write(extent(0, 6));
+
+            buffer.remove(&extent(2, 2));
+            assert_eq!(runs(&buffer.dirty), vec![(0, 2), (4, 2)]);
+
+            buffer.remove(&extent(0, 10));
+            assert!(buffer.is_clean());
+        }
+
+        #[test]
+        fn write_buffer_writes_back_the_nearest_and_oldest_extent_first() {
+            let mut buffer = write_buffer(16);
+            for (cylinder, first) in [(9, 0), (5, 0), (3, 0), (5, 4)] {
+                buffer.write(Extent {
+                    cylinder: cylinder,
+                    ..extent(first, 2)
+                });
+            }
+
+            let taken: Vec<(u32, u32)> = (0..4)
+                .map(|_| buffer.take_nearest(4).unwrap())
+                .map(|extent| (extent.cylinder, extent.first))
+                .collect();
+            assert_eq!(taken, vec![(5, 0), (3, 0), (5, 4), (9, 0)]);
+            assert!(buffer.take_nearest(4).is_none());
+        }
+    }
+}
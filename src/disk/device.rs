@@ -0,0 +1,67 @@
+pub mod device {
+    use crate::disk::{
+        disk::disk::DiskStatistics,
+        driver::driver::{Operation, Task},
+        hardware_manager::hardware_manager::DiskState,
+        ssd::ssd::FlashStatistics,
+    };
+
+    /// Where drivers send their tasks: a spinning `Disk` or an `Ssd`. Every
+    /// call that works on a task, on the device's own work or idles takes
+    /// exactly one tick; the others take none.
+    pub trait Device {
+        fn show(&self);
+
+        /// Whether the device can serve `task` at all, checked before a
+        /// simulation hands it any task.
+        fn check_task(&self, _task: &Task) -> Result<(), String> {
+            Ok(())
+        }
+
+        /// Whether the device takes a task doing `operation` into its queue now.
+        fn accepts(&self, operation: Operation) -> bool;
+
+        /// Hands `task` over to the device, which serves it once it picks it.
+        fn queue_task(&mut self, task: &Task);
+
+        /// Picks the queued task to serve next and returns its id.
+        fn take_queued(&mut self) -> Option<u32>;
+
+        /// Lets the queue take another task once `task` is done.
+        fn finish_queued(&mut self, task: &Task);
+
+        fn get_queue_depth(&self) -> u32;
+
+        /// Starts on `task`, just taken out of the queue.
+        fn start_task(&mut self, task: &Task);
+
+        /// Works on the started `task` for a tick. Returns whether it is done.
+        fn step_task(&mut self, task: &Task) -> bool;
+
+        /// Whether the device has work of its own to do while no task needs
+        /// it, such as writing cached sectors back.
+        fn has_background_work(&self) -> bool;
+
+        /// Works on it for a tick. Returns whether a piece of it is done.
+        fn step_background(&mut self) -> bool;
+
+        /// Accounts for a tick in which the device did nothing.
+        fn idle(&mut self);
+
+        fn get_state(&self) -> &DiskState;
+
+        /// The track drivers order tasks around.
+        fn get_current_track(&self) -> u32;
+
+        /// Abandons the seek under way, leaving the head on the track it
+        /// reached, for a driver that wants to serve a task there instead.
+        fn stop_seek(&mut self) {}
+
+        fn get_statistics(&self) -> &DiskStatistics;
+
+        /// Page and block counts, for a device made of flash.
+        fn get_flash_statistics(&self) -> Option<&FlashStatistics> {
+            None
+        }
+    }
+}
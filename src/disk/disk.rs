@@ -1,31 +1,141 @@
 pub mod disk {
     use crate::disk::{
-        driver::driver::Task,
+        cache::cache::{CacheConfig, Extent, ReadCache, WriteBuffer, WriteCacheConfig},
+        device::device::Device,
+        driver::driver::{Operation, Task},
+        geometry::geometry::{Geometry, Location},
         hardware_manager::hardware_manager::{DiskState, MoveDirection, MoveState},
+        queue::queue::CommandQueue,
+        rotation::rotation::{ContinuousRotation, RotationModel},
+        seek::seek::SeekModel,
     };
 
+    use serde::{Deserialize, Serialize};
+
     pub struct DiskHead {
         current_track: u32,
+        /// The surface being read; always 0 on a single platter side.
+        current_head: u32,
         current_angle: u32,
         state: DiskState,
     }
 
-    #[derive(Copy, Clone)]
+    #[derive(Serialize, Deserialize, Copy, Clone)]
+    #[serde(default = "DiskMetadata::default")]
     pub struct DiskMetadata {
         forward_speed: u32,
         spin_speed: u32,
+        #[serde(skip_serializing_if = "SeekModel::is_track")]
+        seek: SeekModel,
+        #[serde(skip_serializing_if = "RotationModel::is_stepped")]
+        rotation: RotationModel,
+        /// Ticks the head settles for before writing after a seek or a head
+        /// switch, on top of what a read waits.
+        #[serde(skip_serializing_if = "DiskMetadata::is_zero")]
+        write_settle: u32,
+        /// Whether every write is read back on the next revolution.
+        #[serde(skip_serializing_if = "DiskMetadata::is_false")]
+        write_verify: bool,
+        /// Without a cache every read goes to the platter.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cache: Option<CacheConfig>,
+        /// Without a write cache every write goes to the platter before it completes.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        write_cache: Option<WriteCacheConfig>,
+        /// Tasks the drive takes from the host before finishing any, to
+        /// serve in the order it can reach them fastest. With one it serves
+        /// them in the order the driver hands them over.
+        #[serde(skip_serializing_if = "DiskMetadata::is_one")]
+        queue_depth: u32,
+    }
+
+    /// Where the disk spent its time and how far the head travelled, in ticks and tracks.
+    #[derive(Copy, Clone)]
+    pub struct DiskStatistics {
+        tracks_traversed: u64,
+        /// Every move command counts as one seek, including ones cut short.
+        seeks: u32,
+        direction_reversals: u32,
+        head_switches: u32,
+        /// Reads served from the drive's cache, and the ones that were not
+        /// while it had one.
+        cache_hits: u32,
+        cache_misses: u32,
+        /// Writes that completed once in the write cache, and the writes
+        /// that took dirty sectors from it to the platter.
+        buffered_writes: u32,
+        destaged_writes: u32,
+        /// Sectors in the write cache that have not reached the platter.
+        dirty_sectors: u32,
+        /// Queued tasks the drive served before an older one it had queued.
+        reordered_tasks: u32,
+        /// Includes the ticks spent switching heads.
+        seek_time: u32,
+        rotation_time: u32,
+        /// Ticks spent reading sectors once under the head; on disks without
+        /// zones only for reads of more than one sector. Also the ticks
+        /// cached sectors take to be sent.
+        transfer_time: u32,
+        idle_time: u32,
+        last_direction: Option<MoveDirection>,
+    }
+
+    /// Where the head is in the seek it is doing.
+    struct SeekProgress {
+        start: u32,
+        distance: u32,
+        elapsed: u32,
+        motion_time: u32,
     }
 
     pub struct Disk {
         head: DiskHead,
         metadata: DiskMetadata,
         cahce: u32,
+        statistics: DiskStatistics,
+        seek: SeekProgress,
+        /// Ticks the disk has been stepped or idled for.
+        time: u64,
+        /// When the sector being waited for comes under a continuously spinning head.
+        sector_deadline: u64,
+        /// The angle of the last finished read or write, until the head is given a new task.
+        last_access: Option<u32>,
+        geometry: Geometry,
+        /// The first of the sectors the read or write in progress covers,
+        /// and how many it covers.
+        access_sector: u32,
+        access_length: u32,
+        /// Ticks a write still has to let the head settle for; its sector only
+        /// counts as reached once they have passed.
+        settle: u32,
+        /// Ticks left of the revolution that reads just written sectors back.
+        verify: u32,
+        /// Whether the head moved or switched since its last read or write.
+        moved: bool,
+        /// Ticks left until the sectors under the head have been transferred.
+        transfer: u32,
+        /// Ticks left until the head being switched to can read.
+        switch: u32,
+        /// Ticks of the seek that brought the arm to this cylinder, which a
+        /// head switch right after it overlaps with.
+        overlap: u32,
+        cache: Option<ReadCache>,
+        write_buffer: Option<WriteBuffer>,
+        /// Ticks left until the sectors of a read served from the cache, or
+        /// of a write taken into it, have been sent.
+        buffered: u32,
+        /// The write taking dirty sectors to the platter, if one is under way.
+        destage: Option<Task>,
+        /// Whether the task being served comes from the drive's buffer.
+        buffered_task: bool,
+        queue: CommandQueue,
     }
 
     impl DiskHead {
         pub fn default() -> DiskHead {
             DiskHead {
                 current_track: 1,
+                current_head: 0,
                 current_angle: 0,
                 state: DiskState::STOP,
             }
@@ -35,30 +145,39 @@ pub mod disk {
             let state = match &self.state {
                 DiskState::STOP => "STOP".to_owned(),
                 DiskState::READ(angle) => format!("READ({})", angle),
+                DiskState::WRITE(angle) => format!("WRITE({})", angle),
                 DiskState::MOVE(m) => {
                     format!("MOVE({}, {})", m.destination, match m.direction{
                     crate::disk::hardware_manager::hardware_manager::MoveDirection::FORWARD => "FORWARD".to_owned(),
                     crate::disk::hardware_manager::hardware_manager::MoveDirection::BACKWARD => "BACWARD".to_owned(),
                 })
                 }
+                DiskState::SWITCH(head) => format!("SWITCH({})", head),
             };
 
             println!(
-                "DiskHead<current track: {}, current angle: {}, state: {}>",
-                self.current_track, self.current_angle, state
+                "DiskHead<current track: {}, current head: {}, current angle: {}, state: {}>",
+                self.current_track, self.current_head, self.current_angle, state
             );
         }
     }
 
     impl DiskMetadata {
-        pub fn new(forward_speed: u32, spin_speed: u32) -> DiskMetadata {
-            let forward_speed = ((1.0 / forward_speed as f32) * 1000.0 * 1000.0) as u32;
-            let base_spin_speed =
-                ((1.0 / spin_speed as f32) * 60.0 * 1000.0 * 1000.0 * 1000.0) as u32;
+        /// A platter spinning continuously at `rpm`, with ticks of a microsecond.
+        pub fn new(forward_speed: u32, rpm: u32) -> DiskMetadata {
+            let rotation = ContinuousRotation::from_rpm(rpm as f64);
+            let spin_speed = (rotation.get_period(1) / 360.0).round().max(1.0) as u32;
 
             DiskMetadata {
                 forward_speed: forward_speed,
-                spin_speed: base_spin_speed,
+                spin_speed: spin_speed,
+                seek: SeekModel::default(),
+                rotation: RotationModel::CONTINUOUS(rotation),
+                write_settle: 0,
+                write_verify: false,
+                cache: None,
+                write_cache: None,
+                queue_depth: 1,
             }
         }
 
@@ -70,6 +189,33 @@ pub mod disk {
             DiskMetadata {
                 forward_speed: forward_speed,
                 spin_speed: spin_speed,
+                seek: SeekModel::default(),
+                rotation: RotationModel::default(),
+                write_settle: 0,
+                write_verify: false,
+                cache: None,
+                write_cache: None,
+                queue_depth: 1,
+            }
+        }
+
+        fn is_zero(value: &u32) -> bool {
+            *value == 0
+        }
+
+        fn is_false(value: &bool) -> bool {
+            !*value
+        }
+
+        fn is_one(value: &u32) -> bool {
+            *value == 1
+        }
+
+        /// Ticks one revolution of the platter takes.
+        pub fn get_rotation_period(&self) -> f64 {
+            match self.rotation {
+                RotationModel::STEPPED => 360.0 * self.spin_speed as f64,
+                RotationModel::CONTINUOUS(rotation) => rotation.get_period(self.spin_speed),
             }
         }
 
@@ -80,14 +226,254 @@ pub mod disk {
         pub fn get_spin_speed(&self) -> &u32 {
             &self.spin_speed
         }
+
+        pub fn set_forward_speed(&mut self, forward_speed: u32) {
+            self.forward_speed = forward_speed;
+        }
+
+        pub fn set_spin_speed(&mut self, spin_speed: u32) {
+            self.spin_speed = spin_speed;
+        }
+
+        pub fn get_seek_model(&self) -> &SeekModel {
+            &self.seek
+        }
+
+        pub fn set_seek_model(&mut self, seek: SeekModel) {
+            self.seek = seek;
+        }
+
+        pub fn get_rotation_model(&self) -> &RotationModel {
+            &self.rotation
+        }
+
+        pub fn set_rotation_model(&mut self, rotation: RotationModel) {
+            self.rotation = rotation;
+        }
+
+        pub fn get_write_settle(&self) -> u32 {
+            self.write_settle
+        }
+
+        pub fn set_write_settle(&mut self, write_settle: u32) {
+            self.write_settle = write_settle;
+        }
+
+        pub fn is_write_verified(&self) -> bool {
+            self.write_verify
+        }
+
+        pub fn set_write_verify(&mut self, write_verify: bool) {
+            self.write_verify = write_verify;
+        }
+
+        pub fn get_cache(&self) -> Option<&CacheConfig> {
+            self.cache.as_ref()
+        }
+
+        pub fn set_cache(&mut self, cache: Option<CacheConfig>) {
+            self.cache = cache;
+        }
+
+        pub fn get_write_cache(&self) -> Option<&WriteCacheConfig> {
+            self.write_cache.as_ref()
+        }
+
+        pub fn set_write_cache(&mut self, write_cache: Option<WriteCacheConfig>) {
+            self.write_cache = write_cache;
+        }
+
+        pub fn get_queue_depth(&self) -> u32 {
+            self.queue_depth
+        }
+
+        pub fn set_queue_depth(&mut self, queue_depth: u32) {
+            self.queue_depth = queue_depth;
+        }
+    }
+
+    impl DiskStatistics {
+        pub fn default() -> DiskStatistics {
+            DiskStatistics {
+                tracks_traversed: 0,
+                seeks: 0,
+                direction_reversals: 0,
+                head_switches: 0,
+                cache_hits: 0,
+                cache_misses: 0,
+                buffered_writes: 0,
+                destaged_writes: 0,
+                dirty_sectors: 0,
+                reordered_tasks: 0,
+                seek_time: 0,
+                rotation_time: 0,
+                transfer_time: 0,
+                idle_time: 0,
+                last_direction: None,
+            }
+        }
+
+        fn record_seek(&mut self, direction: MoveDirection) {
+            self.seeks += 1;
+            if self.last_direction.is_some_and(|last| last != direction) {
+                self.direction_reversals += 1;
+            }
+            self.last_direction = Some(direction);
+        }
+
+        pub fn get_tracks_traversed(&self) -> u64 {
+            self.tracks_traversed
+        }
+
+        pub fn get_seeks(&self) -> u32 {
+            self.seeks
+        }
+
+        pub fn get_average_seek_distance(&self) -> f64 {
+            if self.seeks == 0 {
+                return 0.0;
+            }
+
+            self.tracks_traversed as f64 / self.seeks as f64
+        }
+
+        pub fn get_direction_reversals(&self) -> u32 {
+            self.direction_reversals
+        }
+
+        pub fn get_head_switches(&self) -> u32 {
+            self.head_switches
+        }
+
+        pub fn get_cache_hits(&self) -> u32 {
+            self.cache_hits
+        }
+
+        /// Share of the reads served from the read or the write cache, when
+        /// the drive had either.
+        pub fn get_cache_hit_ratio(&self) -> Option<f64> {
+            let reads = self.cache_hits + self.cache_misses;
+            if reads == 0 {
+                return None;
+            }
+
+            Some(self.cache_hits as f64 / reads as f64)
+        }
+
+        pub fn get_buffered_writes(&self) -> u32 {
+            self.buffered_writes
+        }
+
+        pub fn get_destaged_writes(&self) -> u32 {
+            self.destaged_writes
+        }
+
+        pub fn get_dirty_sectors(&self) -> u32 {
+            self.dirty_sectors
+        }
+
+        pub fn get_reordered_tasks(&self) -> u32 {
+            self.reordered_tasks
+        }
+
+        pub fn get_seek_time(&self) -> u32 {
+            self.seek_time
+        }
+
+        pub fn get_rotation_time(&self) -> u32 {
+            self.rotation_time
+        }
+
+        pub fn get_transfer_time(&self) -> u32 {
+            self.transfer_time
+        }
+
+        pub fn get_idle_time(&self) -> u32 {
+            self.idle_time
+        }
+
+        /// Counts a tick a device without a head spent moving data.
+        pub fn add_transfer_tick(&mut self) {
+            self.transfer_time += 1;
+        }
+
+        pub fn add_idle_tick(&mut self) {
+            self.idle_time += 1;
+        }
+
+        /// Counts a queued task served before one handed over earlier.
+        pub fn add_reordered_task(&mut self) {
+            self.reordered_tasks += 1;
+        }
+
+        pub fn show(&self) {
+            println!(
+                "Head movement <\n\ttracks traversed: {}, seeks: {}, average seek distance: {:.2}, direction reversals: {}, head switches: {}\n\tseeking: {}, rotating: {}, transferring: {}, idle: {} ticks\n>",
+                self.tracks_traversed,
+                self.seeks,
+                self.get_average_seek_distance(),
+                self.direction_reversals,
+                self.head_switches,
+                self.seek_time,
+                self.rotation_time,
+                self.transfer_time,
+                self.idle_time
+            );
+            if let Some(ratio) = self.get_cache_hit_ratio() {
+                println!(
+                    "Cache <\n\thits: {} of {} reads, hit ratio: {:.4}\n>",
+                    self.cache_hits,
+                    self.cache_hits + self.cache_misses,
+                    ratio
+                );
+            }
+            if self.buffered_writes > 0 {
+                println!(
+                    "Write cache <\n\tbuffered writes: {}, destaged writes: {}, dirty sectors left: {}\n>",
+                    self.buffered_writes, self.destaged_writes, self.dirty_sectors
+                );
+            }
+        }
     }
 
     impl Disk {
         pub fn new(metadata: DiskMetadata) -> Disk {
+            Disk::with_geometry(metadata, Geometry::default())
+        }
+
+        /// A disk with the heads of `geometry`. Once at its first sector a read
+        /// spends a sector's worth of rotation transferring every sector after
+        /// it, and the first one too when the disk has zones.
+        pub fn with_geometry(metadata: DiskMetadata, geometry: Geometry) -> Disk {
             Disk {
                 head: DiskHead::default(),
                 metadata: metadata,
                 cahce: 0,
+                statistics: DiskStatistics::default(),
+                seek: SeekProgress {
+                    start: 1,
+                    distance: 0,
+                    elapsed: 0,
+                    motion_time: 0,
+                },
+                time: 0,
+                sector_deadline: 0,
+                last_access: None,
+                geometry: geometry,
+                access_sector: 0,
+                access_length: 1,
+                settle: 0,
+                verify: 0,
+                moved: false,
+                transfer: 0,
+                switch: 0,
+                overlap: 0,
+                cache: metadata.cache.map(ReadCache::new),
+                write_buffer: metadata.write_cache.map(WriteBuffer::new),
+                buffered: 0,
+                destage: None,
+                buffered_task: false,
+                queue: CommandQueue::new(metadata.queue_depth),
             }
         }
 
@@ -95,124 +481,740 @@ pub mod disk {
             match &self.head.state {
                 DiskState::STOP => "STOP".to_owned(),
                 DiskState::READ(angle) => format!("READ({})", angle),
+                DiskState::WRITE(angle) => format!("WRITE({})", angle),
                 DiskState::MOVE(m) => {
                     format!("MOVE({}, {})", m.destination, match m.direction{
                     crate::disk::hardware_manager::hardware_manager::MoveDirection::FORWARD => "FORWARD".to_owned(),
                     crate::disk::hardware_manager::hardware_manager::MoveDirection::BACKWARD => "BACWARD".to_owned(),
                 })
                 }
+                DiskState::SWITCH(head) => format!("SWITCH({})", head),
             }
         }
 
-        pub fn show(&self) {
-            println!(
-                "Disk <\n\tHead <current track: {}, current angle: {}, state: {}>\n\tMetadata <forward speed: {}, spin speed: {}>\n>",
+        /// Reads `length` sectors from `sector`, which starts at `angle`, as
+        /// if they all followed each other on the current track.
+        pub fn add_reading_task(&mut self, angle: u32, sector: u32, length: u32) {
+            self.start_access(DiskState::READ(angle), angle, sector, length, 0);
+        }
+
+        /// Writes `length` sectors from `sector`, which starts at `angle`.
+        /// After a seek or a head switch the head first settles for
+        /// `write_settle` ticks, and with `write_verify` the sectors are read
+        /// back a revolution later.
+        pub fn add_writing_task(&mut self, angle: u32, sector: u32, length: u32) {
+            let settle = if self.moved {
+                self.metadata.write_settle
+            } else {
+                0
+            };
+            self.start_access(DiskState::WRITE(angle), angle, sector, length, settle);
+            let extent = self.get_extent(
                 self.head.current_track,
-                self.head.current_angle,
-                self.get_str_state(),
-                self.metadata.forward_speed,
-                self.metadata.spin_speed
+                self.head.current_head,
+                sector,
+                length,
             );
+            self.forget(&extent);
+        }
+
+        /// Drops what the caches hold of `extent`, whose sectors are being
+        /// written to the platter or were discarded.
+        fn forget(&mut self, extent: &Extent) {
+            if let Some(cache) = self.cache.as_mut() {
+                cache.invalidate(extent, self.time);
+            }
+            if let Some(write_buffer) = self.write_buffer.as_mut() {
+                write_buffer.remove(extent);
+                self.statistics.dirty_sectors = write_buffer.get_dirty_sectors();
+            }
         }
 
-        pub fn add_reading_task(&mut self, angle: u32) {
-            self.head.state = DiskState::READ(angle);
+        /// The sector `task` starts at: the one it was placed on, or the one
+        /// passing under its head at its angle.
+        fn get_task_sector(&self, task: &Task) -> u32 {
+            task.get_sector().unwrap_or_else(|| {
+                self.geometry
+                    .get_sector_at(*task.get_track(), *task.get_head(), *task.get_angle())
+            })
+        }
+
+        fn get_task_extent(&self, task: &Task) -> Extent {
+            self.get_extent(
+                *task.get_track(),
+                *task.get_head(),
+                self.get_task_sector(task),
+                *task.get_length(),
+            )
+        }
+
+        /// The sectors `length` sectors from `sector` cover on a track.
+        fn get_extent(&self, cylinder: u32, head: u32, sector: u32, length: u32) -> Extent {
+            Extent {
+                cylinder: cylinder,
+                head: head,
+                first: sector,
+                count: length.max(1),
+                sectors: self.geometry.get_sectors(cylinder),
+            }
+        }
+
+        /// Ticks a sector of `cylinder` takes to pass under the head.
+        fn get_sector_time(&self, cylinder: u32) -> u32 {
+            self.geometry
+                .zones
+                .get_transfer_time(cylinder, self.metadata.get_rotation_period())
+        }
+
+        /// Serves `task` from the drive's buffer when it can: a read whose
+        /// sectors are all in the read cache or still in the write cache, or
+        /// a write other than FUA that fits into the write cache. It then
+        /// only takes its transfer time and the head stays where it is.
+        /// Returns whether the buffer serves it.
+        pub fn start_buffered(&mut self, task: &Task) -> bool {
+            let extent = self.get_task_extent(task);
+            let time = self.time;
+            let served = match task.get_operation() {
+                Operation::READ => {
+                    let hit = self
+                        .write_buffer
+                        .as_ref()
+                        .is_some_and(|write_buffer| write_buffer.contains(&extent))
+                        || self
+                            .cache
+                            .as_mut()
+                            .is_some_and(|cache| cache.lookup(&extent, time));
+                    if hit {
+                        self.statistics.cache_hits += 1;
+                    }
+                    hit
+                }
+                Operation::WRITE if !task.is_fua() => {
+                    let written = self
+                        .write_buffer
+                        .as_mut()
+                        .is_some_and(|write_buffer| write_buffer.write(extent));
+                    if written {
+                        if let Some(cache) = self.cache.as_mut() {
+                            cache.invalidate(&extent, time);
+                        }
+                        self.statistics.buffered_writes += 1;
+                        self.statistics.dirty_sectors =
+                            self.write_buffer.as_ref().unwrap().get_dirty_sectors();
+                    }
+                    written
+                }
+                _ => false,
+            };
+
+            if served {
+                self.buffered = self.get_transferred_sectors(*task.get_length())
+                    * self.get_sector_time(*task.get_track());
+            }
+            served
+        }
+
+        /// Sends a tick's worth of the buffered task. Returns whether it is done.
+        pub fn step_buffered(&mut self) -> bool {
+            if self.buffered == 0 {
+                self.idle();
+                return true;
+            }
+
+            self.statistics.transfer_time += 1;
+            self.tick();
+            self.buffered -= 1;
+            self.buffered == 0
+        }
+
+        /// Forgets whatever the caches hold of the sectors `task` discards;
+        /// dirty ones no longer need to be written back.
+        pub fn discard(&mut self, task: &Task) {
+            let extent = self.get_task_extent(task);
+            self.forget(&extent);
+        }
+
+        /// Whether written sectors still have to reach the platter.
+        pub fn has_dirty_sectors(&self) -> bool {
+            self.destage.is_some()
+                || self
+                    .write_buffer
+                    .as_ref()
+                    .is_some_and(|write_buffer| !write_buffer.is_clean())
+        }
+
+        /// Picks the dirty sectors to write back next, those closest to the
+        /// arm, and sends the head to them.
+        pub fn start_destage(&mut self) {
+            if self.destage.is_some() {
+                return;
+            }
+            let extent = match self
+                .write_buffer
+                .as_mut()
+                .and_then(|write_buffer| write_buffer.take_nearest(self.head.current_track))
+            {
+                Some(extent) => extent,
+                None => return,
+            };
+
+            let location = Location {
+                cylinder: extent.cylinder,
+                head: extent.head,
+                sector: extent.first,
+            };
+            let mut task = Task::with_length(
+                0,
+                extent.cylinder,
+                extent.head,
+                self.geometry.get_angle(location),
+                extent.count,
+            )
+            .expect("Dirty extents cover at least one sector");
+            task.set_sector(extent.first);
+            task.set_operation(Operation::WRITE);
+            self.add_move_task(extent.cylinder);
+            self.destage = Some(task);
+        }
+
+        /// The write under way that takes dirty sectors to the platter.
+        pub fn get_destage(&self) -> Option<&Task> {
+            self.destage.as_ref()
+        }
+
+        pub fn finish_destage(&mut self) {
+            if self.destage.take().is_some() {
+                self.statistics.destaged_writes += 1;
+                self.statistics.dirty_sectors = self
+                    .write_buffer
+                    .as_ref()
+                    .map_or(0, |write_buffer| write_buffer.get_dirty_sectors());
+            }
+        }
+
+        /// Ticks the drive expects to pass before the first sector of
+        /// `task` is under the head: the seek, whatever a head switch adds to
+        /// it, and the wait for the sector. Tasks that do not touch the
+        /// platters can start at once.
+        pub fn get_positioning_time(&self, task: &Task) -> u64 {
+            if !task.get_operation().is_media_access() {
+                return 0;
+            }
+
+            let distance = task.get_track().abs_diff(self.head.current_track);
+            let seek = self.metadata.seek.get_seek_time(distance) as u64;
+            let switch = if *task.get_head() != self.head.current_head {
+                (self.geometry.head_switch as u64).saturating_sub(seek)
+            } else {
+                0
+            };
+            let latency = match self.metadata.rotation {
+                RotationModel::STEPPED => {
+                    let degrees = (task.get_angle() + 360 - self.head.current_angle) % 360;
+                    degrees as u64 * self.metadata.spin_speed as u64
+                }
+                RotationModel::CONTINUOUS(rotation) => rotation.get_latency(
+                    self.metadata.spin_speed,
+                    self.time + seek + switch,
+                    *task.get_angle(),
+                ),
+            };
+
+            seek + switch + latency
+        }
+
+        /// Works on a task that does not touch the platters. A flush lasts
+        /// until the write cache has been written back, anything else one
+        /// tick. Returns whether the task is done.
+        fn serve_command(&mut self, task: &Task) -> bool {
+            if task.get_operation() == Operation::FLUSH && self.has_dirty_sectors() {
+                self.step_destage();
+                return false;
+            }
+
+            if task.get_operation() == Operation::DISCARD {
+                self.discard(task);
+            }
+            self.idle();
+            true
+        }
+
+        /// Works on writing dirty sectors back, starting with the ones the disk
+        /// picks when none are under way. Returns whether the write is done.
+        fn step_destage(&mut self) -> bool {
+            let task = match self.get_destage() {
+                Some(task) => task.clone(),
+                None => {
+                    self.start_destage();
+                    self.idle();
+                    return false;
+                }
+            };
+
+            if self.head.current_track != *task.get_track() {
+                self.step();
+            } else if self.serve(&task) {
+                self.finish_destage();
+                return true;
+            }
+
+            false
+        }
+
+        /// Works on `task` once the arm is on its cylinder: selects its head,
+        /// then waits for its sector. Returns whether the task is done.
+        fn serve(&mut self, task: &Task) -> bool {
+            let write = task.get_operation() == Operation::WRITE;
+            if self.head.current_head != *task.get_head() {
+                if self.is_switching() {
+                    self.step();
+                } else {
+                    self.add_switch_task(*task.get_head());
+                    self.idle();
+                }
+            } else if self.is_at_angle(*task.get_angle(), *task.get_length(), write) {
+                self.idle();
+                return true;
+            } else if self.is_rotating() {
+                self.step();
+            } else if write {
+                let sector = self.get_task_sector(task);
+                self.add_writing_task(*task.get_angle(), sector, *task.get_length());
+                self.idle();
+            } else {
+                let sector = self.get_task_sector(task);
+                self.add_reading_task(*task.get_angle(), sector, *task.get_length());
+                self.idle();
+            }
+
+            false
+        }
+
+        fn start_access(
+            &mut self,
+            state: DiskState,
+            angle: u32,
+            sector: u32,
+            length: u32,
+            settle: u32,
+        ) {
+            self.head.state = state;
+            self.access_sector = sector;
+            self.access_length = length.max(1);
+            self.settle = settle;
+            self.verify = 0;
+            self.last_access = None;
+            self.overlap = 0;
+            self.moved = false;
+            if let Some(cache) = self.cache.as_mut() {
+                cache.stop_read_ahead(self.time);
+            }
+            if let RotationModel::CONTINUOUS(rotation) = self.metadata.rotation {
+                let settled = self.time + settle as u64;
+                self.sector_deadline =
+                    settled + rotation.get_latency(self.metadata.spin_speed, settled, angle);
+            }
         }
 
         pub fn add_move_task(&mut self, destination: u32) {
-            if destination != self.head.current_track {
-                if self.head.state == DiskState::STOP {
-                    let direction = if destination > self.head.current_track {
-                        MoveDirection::FORWARD
-                    } else {
-                        MoveDirection::BACKWARD
-                    };
+            if destination != self.head.current_track && self.head.state == DiskState::STOP {
+                let direction = if destination > self.head.current_track {
+                    MoveDirection::FORWARD
+                } else {
+                    MoveDirection::BACKWARD
+                };
 
-                    self.head.state = DiskState::MOVE(MoveState::new(destination, direction));
+                let distance = destination.abs_diff(self.head.current_track);
+                self.last_access = None;
+                self.moved = true;
+                if let Some(cache) = self.cache.as_mut() {
+                    cache.stop_read_ahead(self.time);
+                }
+                self.head.state = DiskState::MOVE(MoveState::new(destination, direction));
+                self.seek = SeekProgress {
+                    start: self.head.current_track,
+                    distance: distance,
+                    elapsed: 0,
+                    motion_time: self.metadata.seek.get_motion_time(distance),
+                };
+                self.statistics.record_seek(direction);
+            }
+        }
+
+        /// Selects another head on the current cylinder.
+        pub fn add_switch_task(&mut self, head: u32) {
+            if head != self.head.current_head && self.head.state == DiskState::STOP {
+                self.last_access = None;
+                self.moved = true;
+                if let Some(cache) = self.cache.as_mut() {
+                    cache.stop_read_ahead(self.time);
+                }
+                self.statistics.head_switches += 1;
+                self.switch = self.geometry.head_switch.saturating_sub(self.overlap);
+                self.overlap = 0;
+
+                if self.switch == 0 {
+                    self.head.current_head = head;
+                } else {
+                    self.head.state = DiskState::SWITCH(head);
                 }
             }
         }
 
         pub fn step(&mut self) {
             match &self.head.state {
-                DiskState::STOP => {}
-                DiskState::READ(r) => {
-                    self.cahce += 1;
-
-                    if self.cahce == self.metadata.spin_speed {
-                        self.head.current_angle += 1;
-                        self.head.current_angle %= 360;
-                        self.cahce = 0;
-                    }
-
-                    if *r == self.head.current_angle {
-                        self.head.state = DiskState::STOP;
-                    }
+                DiskState::STOP => {
+                    self.statistics.idle_time += 1;
                 }
+                DiskState::READ(r) | DiskState::WRITE(r) => {
+                    let r = *r;
+                    if self.transfer > 0 {
+                        self.statistics.transfer_time += 1;
+                        if self.metadata.rotation.is_stepped() {
+                            self.rotate_one_tick();
+                        }
 
-                DiskState::MOVE(m) => {
-                    self.cahce += 1;
+                        self.transfer -= 1;
+                        if self.transfer == 0 {
+                            self.end_transfer(r);
+                        }
+                    } else if self.verify > 0 {
+                        // Waiting for the written sectors to come round again
+                        // and reading them back.
+                        self.statistics.rotation_time += 1;
+                        if self.metadata.rotation.is_stepped() {
+                            self.rotate_one_tick();
+                        }
 
-                    match m.direction {
-                        MoveDirection::FORWARD => {
-                            if self.cahce == self.metadata.forward_speed {
-                                self.cahce = 0;
+                        self.verify -= 1;
+                        if self.verify == 0 {
+                            self.finish_access(r);
+                        }
+                    } else {
+                        self.statistics.rotation_time += 1;
+                        let reached = match self.metadata.rotation {
+                            RotationModel::STEPPED => {
+                                self.rotate_one_tick();
+                                self.settle = self.settle.saturating_sub(1);
+                                r == self.head.current_angle && self.settle == 0
                             }
+                            RotationModel::CONTINUOUS(_) => self.time + 1 >= self.sector_deadline,
+                        };
 
-                            self.head.current_track += 1;
-                        }
-                        MoveDirection::BACKWARD => {
-                            if self.cahce == self.metadata.forward_speed {
-                                self.cahce = 0;
+                        if reached {
+                            self.transfer = self.get_transferred_sectors(self.access_length)
+                                * self.geometry.zones.get_transfer_time(
+                                    self.head.current_track,
+                                    self.metadata.get_rotation_period(),
+                                );
+                            if self.transfer == 0 {
+                                self.end_transfer(r);
                             }
+                        }
+                    }
+                }
 
-                            self.head.current_track -= 1;
+                DiskState::MOVE(m) => {
+                    self.statistics.seek_time += 1;
+                    if self.metadata.seek.is_track() {
+                        // Kept from the original model, where the counter shared
+                        // with rotation wraps at `forward_speed` while seeking.
+                        self.cahce += 1;
+                        if self.cahce >= self.metadata.forward_speed {
+                            self.cahce = 0;
                         }
                     }
 
+                    // The head crosses the tracks at an even pace during the
+                    // motion time and only arrives once it has settled.
+                    let seek = &mut self.seek;
+                    seek.elapsed += 1;
+                    let moved = if seek.elapsed
+                        >= seek.motion_time + self.metadata.seek.get_settle_time()
+                    {
+                        seek.distance
+                    } else {
+                        let moved = seek.distance as u64
+                            * seek.elapsed.min(seek.motion_time) as u64
+                            / seek.motion_time as u64;
+                        (moved as u32).min(seek.distance - 1)
+                    };
+
+                    let track = match m.direction {
+                        MoveDirection::FORWARD => seek.start + moved,
+                        MoveDirection::BACKWARD => seek.start - moved,
+                    };
+                    self.statistics.tracks_traversed +=
+                        track.abs_diff(self.head.current_track) as u64;
+                    self.head.current_track = track;
+
                     if self.head.current_track == m.destination {
                         self.head.state = DiskState::STOP;
+                        self.overlap = seek.elapsed;
+                    }
+                }
+
+                DiskState::SWITCH(head) => {
+                    let head = *head;
+                    self.statistics.seek_time += 1;
+                    self.switch -= 1;
+                    if self.switch == 0 {
+                        self.head.current_head = head;
+                        self.head.state = DiskState::STOP;
                     }
                 }
             }
+
+            self.tick();
         }
 
-        pub fn is_operating(&self) -> bool {
-            self.head.state != DiskState::STOP
+        /// Turns a platter that only spins while reading by one tick's worth.
+        fn rotate_one_tick(&mut self) {
+            self.cahce += 1;
+
+            if self.cahce >= self.metadata.spin_speed {
+                self.head.current_angle += 1;
+                self.head.current_angle %= 360;
+                self.cahce = 0;
+            }
         }
 
-        pub fn detach_current_state(&mut self) -> DiskState {
-            let state = self.head.state;
+        /// Once the sectors have passed under the head a write may still
+        /// have to verify them.
+        fn end_transfer(&mut self, angle: u32) {
+            if matches!(self.head.state, DiskState::WRITE(_)) && self.metadata.write_verify {
+                self.verify = (self.metadata.get_rotation_period().round() as u32).max(1);
+            } else {
+                self.finish_access(angle);
+            }
+        }
+
+        fn finish_access(&mut self, angle: u32) {
+            if matches!(self.head.state, DiskState::READ(_)) {
+                let extent = self.get_extent(
+                    self.head.current_track,
+                    self.head.current_head,
+                    self.access_sector,
+                    self.access_length,
+                );
+                let sector_time = self.get_sector_time(self.head.current_track);
+                if self.cache.is_some() || self.write_buffer.is_some() {
+                    self.statistics.cache_misses += 1;
+                }
+                if let Some(cache) = self.cache.as_mut() {
+                    cache.fill(extent, self.time, sector_time);
+                }
+            }
+
             self.head.state = DiskState::STOP;
+            self.last_access = Some(angle);
+        }
 
-            state
+        /// Advances the clock; a continuously spinning platter turns with it.
+        fn tick(&mut self) {
+            self.time += 1;
+            if let RotationModel::CONTINUOUS(rotation) = self.metadata.rotation {
+                self.head.current_angle = rotation.get_angle(self.metadata.spin_speed, self.time);
+            }
         }
 
-        pub fn get_state(&self) -> &DiskState {
-            &self.head.state
+        /// Sectors of a `length` sector read that take time to transfer.
+        fn get_transferred_sectors(&self, length: u32) -> u32 {
+            if self.geometry.zones.is_zoned() {
+                length
+            } else {
+                length - 1
+            }
         }
 
-        pub fn get_current_track(&self) -> u32 {
-            self.head.current_track
+        /// Whether the `length` sectors from `angle` were just read or
+        /// written, or need no transfer and their first sector is under the
+        /// head. Never while an access is still waiting for its sector, nor
+        /// for a `write` that has to settle or verify first.
+        pub fn is_at_angle(&self, angle: u32, length: u32, write: bool) -> bool {
+            let instant = self.get_transferred_sectors(length) == 0
+                && !(write
+                    && (self.metadata.write_verify
+                        || (self.moved && self.metadata.write_settle > 0)));
+
+            !self.is_rotating()
+                && (self.last_access == Some(angle)
+                    || (self.head.current_angle == angle && instant))
+        }
+
+        pub fn get_geometry(&self) -> &Geometry {
+            &self.geometry
+        }
+
+        pub fn is_operating(&self) -> bool {
+            self.head.state != DiskState::STOP
+        }
+
+        pub fn get_current_head(&self) -> u32 {
+            self.head.current_head
         }
 
         pub fn get_current_angle(&self) -> u32 {
             self.head.current_angle
         }
 
+        /// Whether the disk is reading or writing, or waiting to.
         pub fn is_rotating(&self) -> bool {
-            match self.head.state {
-                DiskState::READ(_) => true,
-                _ => false,
+            matches!(self.head.state, DiskState::READ(_) | DiskState::WRITE(_))
+        }
+
+        pub fn is_switching(&self) -> bool {
+            matches!(self.head.state, DiskState::SWITCH(_))
+        }
+    }
+
+    impl Device for Disk {
+        fn show(&self) {
+            println!(
+                "Disk <\n\tHead <current track: {}, current head: {}, current angle: {}, state: {}>\n\tMetadata <forward speed: {}, spin speed: {}, seek model: {}, rotation model: {}>\n>",
+                self.head.current_track,
+                self.head.current_head,
+                self.head.current_angle,
+                self.get_str_state(),
+                self.metadata.forward_speed,
+                self.metadata.spin_speed,
+                self.metadata.seek.get_name(),
+                self.metadata.rotation.get_name()
+            );
+            if self.queue.get_depth() > 1 {
+                println!(
+                    "Command queue <\n\tdepth: {}, reordered tasks: {}\n>",
+                    self.queue.get_depth(),
+                    self.statistics.reordered_tasks
+                );
+            }
+        }
+
+        fn accepts(&self, operation: Operation) -> bool {
+            self.queue.accepts(operation)
+        }
+
+        fn queue_task(&mut self, task: &Task) {
+            self.queue.push(task);
+        }
+
+        /// The queued task the head can start on soonest.
+        fn take_queued(&mut self) -> Option<u32> {
+            let index = self
+                .queue
+                .get_tasks()
+                .iter()
+                .enumerate()
+                .min_by_key(|(index, task)| (self.get_positioning_time(task), *index))?
+                .0;
+            if index > 0 {
+                self.statistics.add_reordered_task();
             }
+
+            Some(*self.queue.take(index).get_id())
+        }
+
+        fn finish_queued(&mut self, task: &Task) {
+            self.queue.finish(task);
+        }
+
+        fn get_queue_depth(&self) -> u32 {
+            self.queue.get_depth()
         }
 
-        pub fn calculate_moving_direction(&self, task: &Task) -> MoveDirection {
-            if task.get_track() >= &self.head.current_track {
-                MoveDirection::FORWARD
+        /// Serves `task` from the drive's buffer when it can, and otherwise
+        /// sends the head to its track.
+        fn start_task(&mut self, task: &Task) {
+            self.buffered_task = self.start_buffered(task);
+            if !self.buffered_task && task.get_operation().is_media_access() {
+                self.add_move_task(*task.get_track());
+            }
+        }
+
+        fn step_task(&mut self, task: &Task) -> bool {
+            if self.buffered_task {
+                self.buffered_task = !self.step_buffered();
+                return !self.buffered_task;
+            }
+
+            if !task.get_operation().is_media_access() {
+                self.serve_command(task)
+            } else if self.head.current_track == *task.get_track() {
+                self.serve(task)
             } else {
-                MoveDirection::BACKWARD
+                self.step();
+                false
             }
         }
+
+        fn has_background_work(&self) -> bool {
+            self.has_dirty_sectors()
+        }
+
+        fn step_background(&mut self) -> bool {
+            self.step_destage()
+        }
+
+        fn idle(&mut self) {
+            self.statistics.idle_time += 1;
+            self.tick();
+        }
+
+        fn get_state(&self) -> &DiskState {
+            &self.head.state
+        }
+
+        fn get_current_track(&self) -> u32 {
+            self.head.current_track
+        }
+
+        fn stop_seek(&mut self) {
+            if let DiskState::MOVE(_) = self.head.state {
+                self.head.state = DiskState::STOP;
+                self.overlap = self.seek.elapsed;
+            }
+        }
+
+        fn get_statistics(&self) -> &DiskStatistics {
+            &self.statistics
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::geometry::geometry::{Zone, ZoneTable};
+        use crate::disk::mapping::mapping::Mapping;
+
+        #[test]
+        fn buffered_sectors_sharing_a_degree_stay_apart() {
+            let geometry = Geometry {
+                heads: 1,
+                head_switch: 0,
+                track_skew: 0,
+                cylinder_skew: 0,
+                zones: ZoneTable::from_zones(vec![Zone {
+                    first_track: 1,
+                    last_track: 4,
+                    sectors: 720,
+                }])
+                .unwrap(),
+                mapping: Mapping::ZONED,
+            };
+            let mapper = geometry.mapping.build(&geometry, 4);
+            let mut metadata = DiskMetadata::from_config(1, 1);
+            metadata.set_write_cache(Some(WriteCacheConfig { capacity: 16 }));
+            let mut disk = Disk::with_geometry(metadata, geometry);
+
+            let mut write = Task::from_lba(1, 1, 1, mapper.as_ref()).unwrap();
+            write.set_operation(Operation::WRITE);
+            assert!(disk.start_buffered(&write));
+
+            let first = Task::from_lba(2, 0, 1, mapper.as_ref()).unwrap();
+            let second = Task::from_lba(3, 1, 1, mapper.as_ref()).unwrap();
+            assert_eq!(first.get_angle(), second.get_angle());
+            assert!(!disk.start_buffered(&first));
+            assert!(disk.start_buffered(&second));
+        }
     }
 }
@@ -1,83 +1,433 @@
 pub mod driver {
-    use std::collections::HashMap;
+    use std::{
+        collections::{hash_map::DefaultHasher, HashMap},
+        hash::BuildHasherDefault,
+    };
 
-    use crate::disk::{disk::disk::Disk, hardware_manager::hardware_manager::DiskState};
+    use serde::{Deserialize, Serialize};
 
+    use crate::disk::{
+        device::device::Device,
+        hardware_manager::hardware_manager::{DiskState, MoveDirection},
+        mapping::mapping::GeometryMapper,
+    };
+
+    /// Tasks waiting on each track. The hasher is fixed so that the order
+    /// tracks are picked in, and with it every simulation, is reproducible.
+    type TrackQueue<'a> = HashMap<u32, Vec<&'a Task>, BuildHasherDefault<DefaultHasher>>;
+
+    /// What a task asks the disk to do.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Operation {
+        READ,
+        WRITE,
+        /// Makes earlier writes durable; it has no blocks of its own.
+        FLUSH,
+        /// Tells the disk its blocks are no longer in use.
+        DISCARD,
+    }
+
+    #[derive(Clone)]
     pub struct Task {
         task_id: u32,
+        operation: Operation,
+        /// Forced unit access: a write that completes only once it is on the
+        /// platter, even with a write cache.
+        fua: bool,
+        /// The cylinder when the disk has several heads.
         track: u32,
+        head: u32,
         angle: u32,
+        /// The sector at `angle`, when the task was placed on one rather
+        /// than on a whole degree: tracks with more than 360 sectors have
+        /// several starting within the same degree.
+        sector: Option<u32>,
+        /// Sectors read or written from the one at `angle` on.
+        length: u32,
+        /// Set when the task was addressed by logical block.
+        lba: Option<u64>,
     }
 
     pub enum CacheState<'a> {
         EMPTY,
         ACTIVE(&'a Task),
+        /// The device doing work of its own because no task needed it, such
+        /// as writing dirty sectors back; tasks wait until that piece is done.
+        BACKGROUND,
     }
 
     pub trait Driver<'a> {
         fn add_new_task(&mut self, task: &'a Task);
 
         fn step(&mut self) -> u32;
+
+        fn get_device(&self) -> &dyn Device;
+
+        /// The task the disk is currently working on, if any.
+        fn get_current_task(&self) -> Option<&'a Task>;
     }
 
     pub struct SimpleDriver<'a> {
-        disk: Disk,
+        device: Box<dyn Device>,
         cache: CacheState<'a>,
         task_list: Vec<&'a Task>,
+        /// Tasks handed to the drive that it has not started on yet.
+        dispatched: Vec<&'a Task>,
     }
 
     pub struct ElevetorDriver<'a> {
-        disk: Disk,
+        device: Box<dyn Device>,
         cache: CacheState<'a>,
-        same_direction_list: HashMap<u32, Vec<&'a Task>>,
-        opposite_direction_list: HashMap<u32, Vec<&'a Task>>,
+        same_direction_list: TrackQueue<'a>,
+        opposite_direction_list: TrackQueue<'a>,
+        pick_up_on_the_way: bool,
+        /// Tasks that do not touch the platters, served in arrival order
+        /// before the head is sent anywhere else. A flush is a barrier: the
+        /// tasks arriving after it wait here too, and it is only handed over
+        /// once every task that arrived before it is done.
+        commands: Vec<&'a Task>,
+        /// Tasks handed to the drive that it has not started on yet.
+        dispatched: Vec<&'a Task>,
+    }
+
+    impl Operation {
+        pub const ALL: [Operation; 4] = [
+            Operation::READ,
+            Operation::WRITE,
+            Operation::FLUSH,
+            Operation::DISCARD,
+        ];
+
+        pub fn default() -> Operation {
+            Operation::READ
+        }
+
+        pub fn from_name(name: &str) -> Result<Operation, String> {
+            match name.trim().to_lowercase().as_str() {
+                "read" => Ok(Operation::READ),
+                "write" => Ok(Operation::WRITE),
+                "flush" => Ok(Operation::FLUSH),
+                "discard" => Ok(Operation::DISCARD),
+                _ => Err(format!(
+                    "Unknown operation `{}`, expected `read`, `write`, `flush` or `discard`",
+                    name
+                )),
+            }
+        }
+
+        pub fn get_name(&self) -> &'static str {
+            match self {
+                Operation::READ => "read",
+                Operation::WRITE => "write",
+                Operation::FLUSH => "flush",
+                Operation::DISCARD => "discard",
+            }
+        }
+
+        /// Whether the head has to go to the task's sectors. Discarded
+        /// sectors are simply left as they are, and a flush only waits for
+        /// the write cache to be written back.
+        pub fn is_media_access(&self) -> bool {
+            matches!(self, Operation::READ | Operation::WRITE)
+        }
     }
 
     impl Task {
         pub fn new(task_id: u32, track: u32, angle: u32) -> Task {
-            Task {
+            Task::on_head(task_id, track, 0, angle)
+        }
+
+        pub fn on_head(task_id: u32, cylinder: u32, head: u32, angle: u32) -> Task {
+            Task::with_length(task_id, cylinder, head, angle, 1)
+                .expect("A single sector is never empty")
+        }
+
+        /// A task for `length` consecutive sectors from the one at `angle`.
+        /// Every task covers at least one sector, which the disk relies on
+        /// when it times the transfer.
+        pub fn with_length(
+            task_id: u32,
+            cylinder: u32,
+            head: u32,
+            angle: u32,
+            length: u32,
+        ) -> Result<Task, String> {
+            if length == 0 {
+                return Err(format!("Request {} has no sectors", task_id));
+            }
+
+            Ok(Task {
                 task_id: task_id,
-                track: track,
+                operation: Operation::default(),
+                fua: false,
+                track: cylinder,
+                head: head,
                 angle: angle,
+                sector: None,
+                length: length,
+                lba: None,
+            })
+        }
+
+        /// A task for `length` logical blocks from `lba`, placed by `mapper`.
+        pub fn from_lba(
+            task_id: u32,
+            lba: u64,
+            length: u32,
+            mapper: &dyn GeometryMapper,
+        ) -> Result<Task, String> {
+            if length == 0 {
+                return Err(format!("Request {} has no blocks", task_id));
             }
+            if lba + length as u64 > mapper.get_capacity() {
+                return Err(format!(
+                    "Blocks {} to {} are beyond the {} blocks of the disk",
+                    lba,
+                    lba + length as u64 - 1,
+                    mapper.get_capacity()
+                ));
+            }
+
+            let location = mapper.to_location(lba).unwrap();
+            let mut task = Task::with_length(
+                task_id,
+                location.cylinder,
+                location.head,
+                mapper.get_angle(location),
+                length,
+            )?;
+            task.sector = Some(location.sector);
+            task.lba = Some(lba);
+
+            Ok(task)
         }
 
         pub fn get_track(&self) -> &u32 {
             &self.track
         }
 
+        pub fn get_head(&self) -> &u32 {
+            &self.head
+        }
+
+        pub fn get_angle(&self) -> &u32 {
+            &self.angle
+        }
+
+        pub fn get_sector(&self) -> Option<u32> {
+            self.sector
+        }
+
+        pub fn set_sector(&mut self, sector: u32) {
+            self.sector = Some(sector);
+        }
+
+        pub fn get_length(&self) -> &u32 {
+            &self.length
+        }
+
+        pub fn get_lba(&self) -> Option<u64> {
+            self.lba
+        }
+
         pub fn get_id(&self) -> &u32 {
             &self.task_id
         }
 
+        pub fn get_operation(&self) -> Operation {
+            self.operation
+        }
+
+        pub fn set_operation(&mut self, operation: Operation) {
+            self.operation = operation;
+        }
+
+        pub fn is_fua(&self) -> bool {
+            self.fua
+        }
+
+        pub fn set_fua(&mut self, fua: bool) {
+            self.fua = fua;
+        }
+
         pub fn show_task(&self) {
             println!(
-                "Task<task_id: {}, track: {}, angle: {}>",
-                self.task_id, self.track, self.angle
+                "Task<task_id: {}, operation: {}, track: {}, head: {}, angle: {}, length: {}>",
+                self.task_id,
+                self.operation.get_name(),
+                self.track,
+                self.head,
+                self.angle,
+                self.length
             );
         }
     }
 
+    /// The task the device picked to serve next out of those handed to it.
+    fn take_queued<'a>(
+        device: &mut dyn Device,
+        dispatched: &mut Vec<&'a Task>,
+    ) -> Option<&'a Task> {
+        let id = device.take_queued()?;
+        let index = dispatched.iter().position(|task| task.task_id == id)?;
+
+        Some(dispatched.remove(index))
+    }
+
     impl<'a> SimpleDriver<'a> {
-        pub fn new(disk: Disk) -> SimpleDriver<'a> {
+        pub fn new(device: Box<dyn Device>) -> SimpleDriver<'a> {
             SimpleDriver {
-                disk: disk,
+                device: device,
                 cache: CacheState::EMPTY,
                 task_list: Vec::new(),
+                dispatched: Vec::new(),
+            }
+        }
+
+        /// Hands tasks to the device in arrival order for as long as it takes them.
+        fn dispatch(&mut self) {
+            while let Some(&task) = self.task_list.last() {
+                if !self.device.accepts(task.operation) {
+                    break;
+                }
+                self.task_list.pop();
+                self.device.queue_task(task);
+                self.dispatched.push(task);
             }
         }
+
+        fn finish(&mut self, task: &'a Task) -> u32 {
+            self.cache = CacheState::EMPTY;
+            self.device.finish_queued(task);
+            task.task_id
+        }
     }
 
     impl<'a> ElevetorDriver<'a> {
-        pub fn new(disk: Disk) -> ElevetorDriver<'a> {
+        pub fn new(device: Box<dyn Device>) -> ElevetorDriver<'a> {
+            ElevetorDriver::from_config(device, true)
+        }
+
+        /// `pick_up_on_the_way` lets the head stop at a track it is passing
+        /// when a queued task is waiting there, instead of finishing the seek
+        /// first. A drive queuing more than one task picks the order itself,
+        /// so the head then always finishes its seek.
+        pub fn from_config(
+            device: Box<dyn Device>,
+            pick_up_on_the_way: bool,
+        ) -> ElevetorDriver<'a> {
             ElevetorDriver {
-                disk: disk,
+                device: device,
                 cache: CacheState::EMPTY,
-                same_direction_list: HashMap::new(),
-                opposite_direction_list: HashMap::new(),
+                same_direction_list: TrackQueue::default(),
+                opposite_direction_list: TrackQueue::default(),
+                pick_up_on_the_way: pick_up_on_the_way,
+                commands: Vec::new(),
+                dispatched: Vec::new(),
+            }
+        }
+
+        /// Hands tasks to the device for as long as it takes them, commands
+        /// first and then in elevator order. A pending flush lets the tasks
+        /// that arrived before it go first.
+        fn dispatch(&mut self) {
+            loop {
+                let task = match self.commands.last() {
+                    Some(&command) if command.operation.is_media_access() => {
+                        // The flush this arrived after has been handed over.
+                        self.commands.pop();
+                        self.add_to_lists(command);
+                        continue;
+                    }
+                    Some(&command)
+                        if command.operation != Operation::FLUSH || !self.has_listed_tasks() =>
+                    {
+                        if !self.device.accepts(command.operation) {
+                            break;
+                        }
+                        self.commands.pop();
+                        command
+                    }
+                    _ => {
+                        if !self.device.accepts(Operation::READ) {
+                            break;
+                        }
+                        match self.next_task() {
+                            Some(task) => task,
+                            None => break,
+                        }
+                    }
+                };
+
+                self.device.queue_task(task);
+                self.dispatched.push(task);
+            }
+        }
+
+        /// The next task in elevator order: one on the current track, else
+        /// one further on in the same direction. When there is none the
+        /// elevator turns round, which takes until the next tick.
+        fn next_task(&mut self) -> Option<&'a Task> {
+            if self
+                .same_direction_list
+                .contains_key(&self.device.get_current_track())
+            {
+                return Some(self.fetch_a_task_for_current_track());
+            }
+            if !self.same_direction_list.is_empty() {
+                return self.fetch_same_direction_task();
+            }
+            if !self.opposite_direction_list.is_empty() {
+                let temp = self.same_direction_list.to_owned();
+                self.same_direction_list = self.opposite_direction_list.to_owned();
+                self.opposite_direction_list = temp;
+            }
+
+            None
+        }
+
+        fn finish(&mut self, task: &'a Task) -> u32 {
+            self.cache = CacheState::EMPTY;
+            self.device.finish_queued(task);
+            task.task_id
+        }
+
+        /// Queues a task on the platters in the list matching where the
+        /// head is going.
+        fn add_to_lists(&mut self, task: &'a Task) {
+            match self.device.get_state() {
+                DiskState::STOP => {
+                    self.add_to_same_direction_list(task);
+                }
+                DiskState::READ(_) | DiskState::WRITE(_) | DiskState::SWITCH(_) => {
+                    if self.device.get_current_track() == task.track {
+                        self.add_to_same_direction_list(task);
+                    } else {
+                        self.add_to_opposite_direction_list(task);
+                    }
+                }
+                DiskState::MOVE(state) => {
+                    let direction = if task.track >= self.device.get_current_track() {
+                        MoveDirection::FORWARD
+                    } else {
+                        MoveDirection::BACKWARD
+                    };
+                    if self.device.get_current_track() == task.track {
+                        self.add_to_opposite_direction_list(task);
+                    } else if direction == state.direction {
+                        self.add_to_same_direction_list(task);
+                    } else {
+                        self.add_to_opposite_direction_list(task);
+                    }
+                }
             }
         }
 
+        fn has_listed_tasks(&self) -> bool {
+            !self.same_direction_list.is_empty() || !self.opposite_direction_list.is_empty()
+        }
+
         fn add_to_same_direction_list(&mut self, task: &'a Task) {
             let vector = self.same_direction_list.get_mut(&task.track);
             match vector {
@@ -103,65 +453,82 @@ pub mod driver {
         }
 
         fn fetch_same_direction_task(&mut self) -> Option<&'a Task> {
-            let keys: Vec<_> = { self.same_direction_list.keys().take(1).cloned().collect() };
-            for key in keys {
-                let mut tasks = self.same_direction_list.remove(&key).unwrap();
-                let task = tasks.pop().unwrap();
-
-                if tasks.len() != 0 {
-                    self.same_direction_list.insert(key, tasks);
-                }
+            let key = *self.same_direction_list.keys().next()?;
+            let mut tasks = self.same_direction_list.remove(&key).unwrap();
+            let task = tasks.pop().unwrap();
 
-                return Some(task);
+            if !tasks.is_empty() {
+                self.same_direction_list.insert(key, tasks);
             }
 
-            None
+            Some(task)
         }
 
         fn fetch_a_task_for_current_track(&mut self) -> &'a Task {
             let mut tasks = self
                 .same_direction_list
-                .remove(&self.disk.get_current_track())
+                .remove(&self.device.get_current_track())
                 .unwrap();
             let task = tasks.pop().unwrap();
 
-            if tasks.len() != 0 {
+            if !tasks.is_empty() {
                 self.same_direction_list
-                    .insert(self.disk.get_current_track(), tasks);
+                    .insert(self.device.get_current_track(), tasks);
             }
 
             task
         }
     }
 
+    impl<'a> CacheState<'a> {
+        pub fn get_task(&self) -> Option<&'a Task> {
+            match self {
+                CacheState::EMPTY => None,
+                CacheState::ACTIVE(task) => Some(task),
+                CacheState::BACKGROUND => None,
+            }
+        }
+    }
+
     impl<'a> Driver<'a> for SimpleDriver<'a> {
         fn add_new_task(&mut self, task: &'a Task) {
             self.task_list.insert(0, task);
         }
 
+        fn get_device(&self) -> &dyn Device {
+            self.device.as_ref()
+        }
+
+        fn get_current_task(&self) -> Option<&'a Task> {
+            self.cache.get_task()
+        }
+
         fn step(&mut self) -> u32 {
+            // The device takes no new tasks while it works on its own.
+            if !matches!(self.cache, CacheState::BACKGROUND) {
+                self.dispatch();
+            }
+
             match self.cache {
                 CacheState::EMPTY => {
-                    if !self.task_list.is_empty() {
-                        let peak = self.task_list.pop().unwrap();
-                        self.disk.add_move_task(peak.track);
-                        self.cache = CacheState::ACTIVE(peak);
+                    if let Some(task) = take_queued(self.device.as_mut(), &mut self.dispatched) {
+                        self.device.start_task(task);
+                        self.cache = CacheState::ACTIVE(task);
+                    } else if self.device.has_background_work() {
+                        self.cache = CacheState::BACKGROUND;
+                        self.device.step_background();
+                        return 0;
+                    }
+                    self.device.idle();
+                }
+                CacheState::BACKGROUND => {
+                    if self.device.step_background() {
+                        self.cache = CacheState::EMPTY;
                     }
                 }
                 CacheState::ACTIVE(f) => {
-                    if self.disk.get_current_track() == f.track {
-                        if self.disk.get_current_angle() == f.angle {
-                            self.cache = CacheState::EMPTY;
-                            return f.task_id;
-                        } else {
-                            if self.disk.is_rotating() {
-                                self.disk.step()
-                            } else {
-                                self.disk.add_reading_task(f.angle);
-                            }
-                        }
-                    } else {
-                        self.disk.step();
+                    if self.device.step_task(f) {
+                        return self.finish(f);
                     }
                 }
             }
@@ -171,88 +538,78 @@ pub mod driver {
     }
 
     impl<'a> Driver<'a> for ElevetorDriver<'a> {
+        fn get_device(&self) -> &dyn Device {
+            self.device.as_ref()
+        }
+
+        fn get_current_task(&self) -> Option<&'a Task> {
+            self.cache.get_task()
+        }
+
         fn add_new_task(&mut self, task: &'a Task) {
-            match self.disk.get_state() {
-                DiskState::STOP => {
-                    self.add_to_same_direction_list(task);
-                }
-                DiskState::READ(_) => {
-                    if self.disk.get_current_track() == task.track {
-                        self.add_to_same_direction_list(task);
-                    } else {
-                        self.add_to_opposite_direction_list(task);
-                    }
-                }
-                DiskState::MOVE(state) => {
-                    if self.disk.get_current_track() == task.track {
-                        self.add_to_opposite_direction_list(task);
-                    } else {
-                        if self.disk.calculate_moving_direction(task) == state.direction {
-                            self.add_to_same_direction_list(task);
-                        } else {
-                            self.add_to_opposite_direction_list(task);
-                        }
-                    }
-                }
+            if !task.operation.is_media_access() {
+                self.commands.insert(0, task);
+                return;
+            }
+
+            // Nothing overtakes a pending flush.
+            if self
+                .commands
+                .iter()
+                .any(|command| command.operation == Operation::FLUSH)
+            {
+                self.commands.insert(0, task);
+                return;
             }
+
+            self.add_to_lists(task);
         }
 
         fn step(&mut self) -> u32 {
+            // The device takes no new tasks while it works on its own.
+            if !matches!(self.cache, CacheState::BACKGROUND) {
+                self.dispatch();
+            }
+
             match self.cache {
                 CacheState::EMPTY => {
-                    let tasks = self
-                        .same_direction_list
-                        .get_mut(&self.disk.get_current_track());
-                    match tasks {
-                        Some(tasks) => {
-                            let task = tasks.pop().unwrap();
-                            if tasks.len() == 0 {
-                                self.same_direction_list
-                                    .remove(&self.disk.get_current_track());
-                            }
-
-                            self.cache = CacheState::ACTIVE(task);
-                        }
-                        None => {
-                            if !self.same_direction_list.is_empty() {
-                                let task = self.fetch_same_direction_task().unwrap();
-                                self.cache = CacheState::ACTIVE(task);
-                                self.disk.add_move_task(task.track);
-                            } else if !self.opposite_direction_list.is_empty() {
-                                let temp = self.same_direction_list.to_owned();
-                                self.same_direction_list = self.opposite_direction_list.to_owned();
-                                self.opposite_direction_list = temp;
-                            }
-                        }
+                    if let Some(task) = take_queued(self.device.as_mut(), &mut self.dispatched) {
+                        self.device.start_task(task);
+                        self.cache = CacheState::ACTIVE(task);
+                    } else if self.same_direction_list.is_empty()
+                        && self.opposite_direction_list.is_empty()
+                        && self.device.has_background_work()
+                    {
+                        self.cache = CacheState::BACKGROUND;
+                        self.device.step_background();
+                        return 0;
+                    }
+                    self.device.idle();
+                }
+                CacheState::BACKGROUND => {
+                    if self.device.step_background() {
+                        self.cache = CacheState::EMPTY;
                     }
                 }
                 CacheState::ACTIVE(f) => {
-                    if self.disk.get_current_track() == f.track {
-                        if self.disk.get_current_angle() == f.angle {
-                            self.cache = CacheState::EMPTY;
-                            return f.task_id;
-                        } else {
-                            if self.disk.is_rotating() {
-                                self.disk.step()
-                            } else {
-                                self.disk.add_reading_task(f.angle);
-                            }
-                        }
-                    } else if self
-                        .same_direction_list
-                        .contains_key(&self.disk.get_current_track())
+                    // A flush moves the head to write cached sectors back,
+                    // and nothing may be served ahead of it.
+                    if self.pick_up_on_the_way
+                        && f.operation.is_media_access()
+                        && self.device.get_queue_depth() == 1
+                        && matches!(self.device.get_state(), DiskState::MOVE(_))
+                        && self
+                            .same_direction_list
+                            .contains_key(&self.device.get_current_track())
                     {
-                        match self.disk.get_state() {
-                            DiskState::MOVE(_) => {
-                                let new_task = self.fetch_a_task_for_current_track();
-                                self.disk.detach_current_state();
-                                self.add_to_same_direction_list(f);
-                                self.cache = CacheState::ACTIVE(new_task);
-                            }
-                            _ => {}
-                        }
-                    } else {
-                        self.disk.step();
+                        let new_task = self.fetch_a_task_for_current_track();
+                        self.device.stop_seek();
+                        self.add_to_same_direction_list(f);
+                        self.device.start_task(new_task);
+                        self.cache = CacheState::ACTIVE(new_task);
+                        self.device.idle();
+                    } else if self.device.step_task(f) {
+                        return self.finish(f);
                     }
                 }
             }
@@ -260,4 +617,156 @@ pub mod driver {
             0
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::{
+            disk::disk::{Disk, DiskMetadata},
+            geometry::geometry::Geometry,
+            mapping::mapping::Mapping,
+        };
+        use crate::simulation::{
+            scenario::scenario::Scenario,
+            simulation::simulation::{run_simulation, Algorithms, SchedulerConfig},
+            workload::workload::Arrival,
+        };
+
+        /// Reads, writes, flushes and discards arriving while earlier tasks
+        /// are still being served, on a disk with `disk` in its section.
+        fn flush_scenario(algorithm: &str, disk: &str) -> Scenario {
+            Scenario::from_toml(&format!(
+                r#"
+                [disk]
+                forward_speed = 1
+                spin_speed = 1
+                tracks = 10000
+                {}
+
+                [workload]
+                requests = 400
+                seed = 4
+                mix = {{ read = 0.5, write = 0.4, flush = 0.05, discard = 0.05 }}
+                arrival = {{ kind = "bernoulli", probability = 0.0002 }}
+
+                [scheduler]
+                algorithm = "{}"
+                "#,
+                disk, algorithm
+            ))
+            .unwrap()
+        }
+
+        #[test]
+        fn tasks_cover_at_least_one_sector() {
+            let error = Task::with_length(7, 1, 0, 0, 0).err().unwrap();
+            assert_eq!(error, "Request 7 has no sectors");
+            assert_eq!(*Task::with_length(7, 1, 0, 0, 3).unwrap().get_length(), 3);
+        }
+
+        #[test]
+        fn tasks_by_block_need_blocks_on_the_disk() {
+            let mapper = Mapping::ZONED.build(&Geometry::default(), 2);
+
+            assert!(Task::from_lba(1, 0, 0, mapper.as_ref()).is_err());
+            assert!(Task::from_lba(2, 715, 6, mapper.as_ref()).is_err());
+
+            let task = Task::from_lba(3, 714, 6, mapper.as_ref()).unwrap();
+            assert_eq!(*task.get_track(), 2);
+            assert_eq!(*task.get_angle(), 354);
+            assert_eq!(*task.get_length(), 6);
+            assert_eq!(task.get_lba(), Some(714));
+        }
+
+        #[test]
+        fn sequential_blocks_switch_heads_onto_the_skewed_sector() {
+            let mut geometry = Geometry::default();
+            geometry.heads = 2;
+            geometry.head_switch = 30;
+            geometry.track_skew = 45;
+
+            let mapper = geometry.get_mapper(10);
+            let arrivals: Vec<Arrival> = [359, 360]
+                .iter()
+                .enumerate()
+                .map(|(i, &lba)| Arrival {
+                    time: 0,
+                    task: Task::from_lba(i as u32 + 1, lba, 1, mapper.as_ref()).unwrap(),
+                    owner: 0,
+                })
+                .collect();
+            assert_eq!(*arrivals[1].task.get_head(), 1);
+            assert_eq!(*arrivals[1].task.get_angle(), 45);
+
+            let result = run_simulation(
+                SchedulerConfig::new(Algorithms::NAIVE),
+                Box::new(Disk::with_geometry(
+                    DiskMetadata::from_config(1, 1),
+                    geometry,
+                )),
+                &arrivals,
+                false,
+                None,
+            )
+            .unwrap();
+            // The first block is read after turning to degree 359; the switch
+            // then costs its 30 ticks and the second block waits for the
+            // platter to turn on to the 45 degrees it is skewed by.
+            assert_eq!(result.disk.get_head_switches(), 1);
+            assert_eq!(result.disk.get_seeks(), 0);
+            assert_eq!(result.disk.get_seek_time(), 30);
+            assert_eq!(result.disk.get_rotation_time(), 359 + 46);
+            assert_eq!(result.tasks[1].head, 1);
+            assert_eq!(result.tasks[1].angle, 45);
+            assert!(result.tasks[1].completion - result.tasks[0].completion >= 30 + 46);
+        }
+
+        #[test]
+        fn reads_missing_the_write_cache_count_against_its_hit_ratio() {
+            let result = flush_scenario("naive", "").run(false).unwrap();
+            assert!(result.disk.get_cache_hit_ratio().is_none());
+
+            let result = flush_scenario("naive", "write_cache = { capacity = 256 }")
+                .run(false)
+                .unwrap();
+            let ratio = result.disk.get_cache_hit_ratio().unwrap();
+            assert!(ratio < 1.0, "hit ratio {}", ratio);
+        }
+
+        #[test]
+        fn flushes_complete_after_every_earlier_write() {
+            let disks = [
+                "",
+                "queue_depth = 8",
+                "write_cache = { capacity = 256 }",
+                "write_cache = { capacity = 256 }\nqueue_depth = 8",
+            ];
+            for algorithm in ["naive", "elevator"] {
+                for disk in disks {
+                    let result = flush_scenario(algorithm, disk).run(false).unwrap();
+                    assert_eq!(result.tasks.len(), 400);
+
+                    for flush in result
+                        .tasks
+                        .iter()
+                        .filter(|task| task.operation == Operation::FLUSH)
+                    {
+                        let late = result.tasks.iter().find(|task| {
+                            task.operation == Operation::WRITE
+                                && task.arrival < flush.arrival
+                                && task.completion > flush.completion
+                        });
+                        assert!(
+                            late.is_none(),
+                            "{} with `{}`: write {} finished after flush {}",
+                            algorithm,
+                            disk,
+                            late.unwrap().id,
+                            flush.id
+                        );
+                    }
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,409 @@
+pub mod geometry {
+    use serde::{Deserialize, Serialize};
+
+    use crate::disk::mapping::mapping::{GeometryMapper, Mapping};
+
+    /// Sectors per track of every track without a zone table, one per degree.
+    pub const DEFAULT_SECTORS: u32 = 360;
+
+    /// A band of tracks recorded with the same number of sectors per track.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    pub struct Zone {
+        pub first_track: u32,
+        pub last_track: u32,
+        pub sectors: u32,
+    }
+
+    /// Zoned bit recording: consecutive zones from track 1 inwards, usually
+    /// with fewer sectors the further in they are. With several heads the
+    /// zones are bands of cylinders.
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    #[serde(try_from = "Vec<Zone>", into = "Vec<Zone>")]
+    pub struct ZoneTable {
+        zones: Vec<Zone>,
+    }
+
+    /// A sector addressed by cylinder, head and sector (CHS).
+    #[derive(Copy, Clone, PartialEq)]
+    pub struct Location {
+        pub cylinder: u32,
+        pub head: u32,
+        pub sector: u32,
+    }
+
+    /// Platters stacked on one spindle with a head per recording surface.
+    /// The disk's tracks are then cylinders: one track under every head at
+    /// the same arm position. Where logical blocks go depends on `mapping`.
+    #[derive(Serialize, Deserialize, Clone, PartialEq)]
+    pub struct Geometry {
+        #[serde(
+            default = "Geometry::default_heads",
+            skip_serializing_if = "Geometry::is_single_head"
+        )]
+        pub heads: u32,
+        /// Ticks to switch to another head on the same cylinder; a switch
+        /// after a seek only costs what the seek did not already cover.
+        #[serde(default, skip_serializing_if = "Geometry::is_zero")]
+        pub head_switch: u32,
+        /// Sectors sector 0 of a track is shifted by from the previous
+        /// head's, so that a head switch does not miss the next block.
+        #[serde(default, skip_serializing_if = "Geometry::is_zero")]
+        pub track_skew: u32,
+        /// Sectors sector 0 of a cylinder's first track is shifted by from
+        /// the previous cylinder's last track, to cover a one track seek.
+        #[serde(default, skip_serializing_if = "Geometry::is_zero")]
+        pub cylinder_skew: u32,
+        /// Zoned recording; without zones every track has 360 sectors.
+        #[serde(
+            default = "ZoneTable::default",
+            skip_serializing_if = "ZoneTable::is_unzoned"
+        )]
+        pub zones: ZoneTable,
+        #[serde(
+            default = "Mapping::default",
+            skip_serializing_if = "Mapping::is_zoned"
+        )]
+        pub mapping: Mapping,
+    }
+
+    impl Zone {
+        pub fn get_tracks(&self) -> u32 {
+            self.last_track - self.first_track + 1
+        }
+
+        /// Blocks the zone holds on one surface.
+        pub fn get_capacity(&self) -> u64 {
+            self.get_tracks() as u64 * self.sectors as u64
+        }
+    }
+
+    impl ZoneTable {
+        /// No zones: every track has `DEFAULT_SECTORS` sectors.
+        pub fn default() -> ZoneTable {
+            ZoneTable { zones: Vec::new() }
+        }
+
+        pub fn from_zones(zones: Vec<Zone>) -> Result<ZoneTable, String> {
+            let mut next_track = 1;
+            for zone in zones.iter() {
+                if zone.first_track != next_track {
+                    return Err(format!(
+                        "disk.zones must be contiguous from track 1, expected a zone starting at track {}",
+                        next_track
+                    ));
+                }
+                if zone.last_track < zone.first_track {
+                    return Err(format!(
+                        "The zone starting at track {} ends before it starts",
+                        zone.first_track
+                    ));
+                }
+                if zone.sectors == 0 {
+                    return Err(format!(
+                        "The zone starting at track {} needs at least one sector per track",
+                        zone.first_track
+                    ));
+                }
+                next_track = zone.last_track + 1;
+            }
+
+            Ok(ZoneTable { zones: zones })
+        }
+
+        pub fn is_zoned(&self) -> bool {
+            !self.zones.is_empty()
+        }
+
+        pub fn is_unzoned(&self) -> bool {
+            self.zones.is_empty()
+        }
+
+        pub fn get_zones(&self) -> &[Zone] {
+            &self.zones
+        }
+
+        /// Checks that the zones end on the last of the disk's `tracks`.
+        pub fn validate(&self, tracks: u32) -> Result<(), String> {
+            match self.zones.last() {
+                Some(zone) if zone.last_track != tracks => Err(format!(
+                    "disk.zones end at track {} but the disk has {} tracks",
+                    zone.last_track, tracks
+                )),
+                _ => Ok(()),
+            }
+        }
+
+        pub fn get_zone(&self, track: u32) -> Option<&Zone> {
+            self.zones
+                .iter()
+                .find(|zone| zone.first_track <= track && track <= zone.last_track)
+        }
+
+        pub fn get_sectors(&self, track: u32) -> u32 {
+            self.get_zone(track)
+                .map_or(DEFAULT_SECTORS, |zone| zone.sectors)
+        }
+
+        /// Logical blocks on a disk of `tracks` tracks.
+        pub fn get_capacity(&self, tracks: u32) -> u64 {
+            if self.is_zoned() {
+                self.zones.iter().map(|zone| zone.get_capacity()).sum()
+            } else {
+                tracks as u64 * DEFAULT_SECTORS as u64
+            }
+        }
+
+        /// The zones covering a disk of `tracks` tracks, a single one when unzoned.
+        pub fn get_zones_of(&self, tracks: u32) -> Vec<Zone> {
+            if self.is_zoned() {
+                self.zones.clone()
+            } else {
+                vec![Zone {
+                    first_track: 1,
+                    last_track: tracks,
+                    sectors: DEFAULT_SECTORS,
+                }]
+            }
+        }
+
+        /// The sector of `track` passing under the head at `angle` degrees,
+        /// counted from the start of the platter rather than of the track.
+        fn get_physical_sector_at(&self, track: u32, angle: u32) -> u32 {
+            let sectors = self.get_sectors(track);
+            ((angle as u64 * sectors as u64 / 360) as u32).min(sectors - 1)
+        }
+
+        /// Ticks one sector of `track` needs to pass under the head when a
+        /// revolution takes `period` ticks; at least one.
+        pub fn get_transfer_time(&self, track: u32, period: f64) -> u32 {
+            (period / self.get_sectors(track) as f64).ceil().max(1.0) as u32
+        }
+    }
+
+    impl Geometry {
+        /// One surface without skew or zones, the disk the simulator has always had.
+        pub fn default() -> Geometry {
+            Geometry {
+                heads: Geometry::default_heads(),
+                head_switch: 0,
+                track_skew: 0,
+                cylinder_skew: 0,
+                zones: ZoneTable::default(),
+                mapping: Mapping::default(),
+            }
+        }
+
+        fn default_heads() -> u32 {
+            1
+        }
+
+        fn is_single_head(heads: &u32) -> bool {
+            *heads == 1
+        }
+
+        fn is_zero(value: &u32) -> bool {
+            *value == 0
+        }
+
+        /// Checks the geometry of a disk with `cylinders` cylinders.
+        pub fn validate(&self, cylinders: u32) -> Result<(), String> {
+            if self.heads == 0 {
+                return Err("disk.heads must be at least 1".to_owned());
+            }
+
+            self.zones.validate(cylinders)
+        }
+
+        pub fn get_sectors(&self, cylinder: u32) -> u32 {
+            self.zones.get_sectors(cylinder)
+        }
+
+        /// Logical blocks on a disk of `cylinders` cylinders.
+        pub fn get_capacity(&self, cylinders: u32) -> u64 {
+            self.zones.get_capacity(cylinders) * self.heads as u64
+        }
+
+        /// Whether the disk, with `cylinders` cylinders, has a sector at `location`.
+        pub fn contains(&self, location: Location, cylinders: u32) -> bool {
+            location.cylinder > 0
+                && location.cylinder <= cylinders
+                && location.head < self.heads
+                && location.sector < self.get_sectors(location.cylinder)
+        }
+
+        /// The configured mapping of logical blocks onto a disk of `cylinders` cylinders.
+        pub fn get_mapper(&self, cylinders: u32) -> Box<dyn GeometryMapper> {
+            self.mapping.build(self, cylinders)
+        }
+
+        /// Sectors the track under `head` on `cylinder` is rotated by.
+        pub fn get_skew(&self, cylinder: u32, head: u32) -> u32 {
+            let per_cylinder =
+                (self.heads - 1) as u64 * self.track_skew as u64 + self.cylinder_skew as u64;
+            let skew = cylinder.saturating_sub(1) as u64 * per_cylinder
+                + head as u64 * self.track_skew as u64;
+
+            (skew % self.get_sectors(cylinder) as u64) as u32
+        }
+
+        /// Whole degree at which the sector at `location` starts, skew included.
+        /// Tracks with more than 360 sectors start several within a degree,
+        /// so tasks placed on a sector carry it along with the angle.
+        pub fn get_angle(&self, location: Location) -> u32 {
+            let sectors = self.get_sectors(location.cylinder);
+            let sector =
+                (location.sector + self.get_skew(location.cylinder, location.head)) % sectors;
+
+            (sector as u64 * 360 / sectors as u64) as u32
+        }
+
+        /// The sector under `head` on `cylinder` passing under it at `angle`
+        /// degrees: the first of those starting within that degree.
+        pub fn get_sector_at(&self, cylinder: u32, head: u32, angle: u32) -> u32 {
+            let sectors = self.get_sectors(cylinder);
+            let sector = self.zones.get_physical_sector_at(cylinder, angle);
+
+            (sector + sectors - self.get_skew(cylinder, head)) % sectors
+        }
+
+        pub fn is_multi_head(&self) -> bool {
+            self.heads > 1
+        }
+
+        /// Lists the surfaces and zones with their capacity and media rate for
+        /// a platter turning once every `period` ticks.
+        pub fn show(&self, period: f64) {
+            println!(
+                "Geometry <\n\theads: {}, head switch: {} ticks, track skew: {} sectors, cylinder skew: {} sectors, mapping: {}",
+                self.heads,
+                self.head_switch,
+                self.track_skew,
+                self.cylinder_skew,
+                self.mapping.get_name()
+            );
+            for zone in self.zones.get_zones().iter() {
+                println!(
+                    "\tcylinders {}-{}: {} sectors per track, {} blocks, {:.2} sectors per thousand ticks",
+                    zone.first_track,
+                    zone.last_track,
+                    zone.sectors,
+                    zone.get_capacity() * self.heads as u64,
+                    1000.0 * zone.sectors as f64 / period
+                );
+            }
+            println!(">");
+        }
+    }
+
+    impl TryFrom<Vec<Zone>> for ZoneTable {
+        type Error = String;
+
+        fn try_from(zones: Vec<Zone>) -> Result<ZoneTable, String> {
+            ZoneTable::from_zones(zones)
+        }
+    }
+
+    impl From<ZoneTable> for Vec<Zone> {
+        fn from(table: ZoneTable) -> Vec<Zone> {
+            table.zones
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn zone(first_track: u32, last_track: u32, sectors: u32) -> Zone {
+            Zone {
+                first_track: first_track,
+                last_track: last_track,
+                sectors: sectors,
+            }
+        }
+
+        fn zoned() -> ZoneTable {
+            ZoneTable::from_zones(vec![zone(1, 2, 720), zone(3, 5, 540), zone(6, 6, 100)]).unwrap()
+        }
+
+        #[test]
+        fn zones_have_to_cover_the_disk_from_track_1() {
+            assert!(ZoneTable::from_zones(vec![zone(2, 5, 100)]).is_err());
+            assert!(ZoneTable::from_zones(vec![zone(1, 2, 100), zone(4, 5, 100)]).is_err());
+            assert!(ZoneTable::from_zones(vec![zone(1, 2, 100), zone(3, 2, 100)]).is_err());
+            assert!(ZoneTable::from_zones(vec![zone(1, 2, 0)]).is_err());
+
+            assert!(zoned().validate(6).is_ok());
+            assert!(zoned().validate(7).is_err());
+            assert!(ZoneTable::default().validate(7).is_ok());
+        }
+
+        #[test]
+        fn capacity_adds_up_the_zones() {
+            assert_eq!(zoned().get_capacity(6), 2 * 720 + 3 * 540 + 100);
+            assert_eq!(ZoneTable::default().get_capacity(10), 3600);
+            assert_eq!(zoned().get_sectors(4), 540);
+            assert_eq!(ZoneTable::default().get_sectors(4), DEFAULT_SECTORS);
+        }
+
+        /// Two surfaces over the zones of `zoned`.
+        fn geometry() -> Geometry {
+            let mut geometry = Geometry::default();
+            geometry.heads = 2;
+            geometry.track_skew = 10;
+            geometry.cylinder_skew = 30;
+            geometry.zones = zoned();
+            geometry
+        }
+
+        fn location(cylinder: u32, head: u32, sector: u32) -> Location {
+            Location {
+                cylinder: cylinder,
+                head: head,
+                sector: sector,
+            }
+        }
+
+        #[test]
+        fn every_surface_adds_its_sectors() {
+            let geometry = geometry();
+            assert_eq!(geometry.get_capacity(6), 2 * (2 * 720 + 3 * 540 + 100));
+            assert!(geometry.contains(location(1, 1, 719), 6));
+            assert!(geometry.contains(location(6, 0, 99), 6));
+            assert!(!geometry.contains(location(0, 0, 0), 6));
+            assert!(!geometry.contains(location(7, 0, 0), 6));
+            assert!(!geometry.contains(location(1, 2, 0), 6));
+            assert!(!geometry.contains(location(6, 0, 100), 6));
+
+            let mut unzoned = Geometry::default();
+            unzoned.heads = 4;
+            assert!(unzoned.validate(10).is_ok());
+            assert_eq!(unzoned.get_capacity(10), 4 * 3600);
+            unzoned.heads = 0;
+            assert!(unzoned.validate(10).is_err());
+        }
+
+        #[test]
+        fn tracks_are_skewed_by_head_and_cylinder() {
+            let geometry = geometry();
+            assert_eq!(geometry.get_skew(1, 0), 0);
+            assert_eq!(geometry.get_skew(1, 1), 10);
+            assert_eq!(geometry.get_skew(2, 0), 40);
+            assert_eq!(geometry.get_skew(2, 1), 50);
+            assert_eq!(geometry.get_skew(6, 1), 10);
+            assert_eq!(geometry.get_skew(6, 0), 0);
+
+            assert_eq!(geometry.get_angle(location(2, 1, 0)), 25);
+            assert_eq!(geometry.get_sector_at(2, 1, 25), 0);
+            assert_eq!(geometry.get_sector_at(2, 1, 24), 718);
+        }
+
+        #[test]
+        fn sectors_are_timed_by_the_zone_they_are_in() {
+            let table = zoned();
+            assert_eq!(table.get_transfer_time(1, 7200.0), 10);
+            assert_eq!(table.get_transfer_time(6, 7200.0), 72);
+            assert_eq!(table.get_transfer_time(1, 100.0), 1);
+        }
+    }
+}
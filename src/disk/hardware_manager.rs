@@ -15,7 +15,11 @@ pub mod hardware_manager {
     pub enum DiskState {
         STOP,
         READ(u32),
+        /// Writing the sectors from this angle on.
+        WRITE(u32),
         MOVE(MoveState),
+        /// Switching to the head with this number on the same cylinder.
+        SWITCH(u32),
     }
 
     impl MoveState {
@@ -26,5 +30,4 @@ pub mod hardware_manager {
             }
         }
     }
-
 }
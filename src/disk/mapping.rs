@@ -0,0 +1,503 @@
+pub mod mapping {
+    use serde::{Deserialize, Serialize};
+
+    use crate::disk::geometry::geometry::{Geometry, Location, Zone, DEFAULT_SECTORS};
+
+    /// Translates logical block addresses to where the blocks are on the
+    /// platters and back.
+    pub trait GeometryMapper {
+        fn get_name(&self) -> &'static str;
+
+        /// Logical blocks the mapping gives access to.
+        fn get_capacity(&self) -> u64;
+
+        /// Where logical block `lba` is, if the disk has it.
+        fn to_location(&self, lba: u64) -> Option<Location>;
+
+        /// The logical block at `location`, if it holds one.
+        fn to_lba(&self, location: Location) -> Option<u64>;
+
+        /// Whole degree at which the sector at `location` starts.
+        fn get_angle(&self, location: Location) -> u32;
+
+        /// The sector under `head` on `cylinder` passing under it at `angle` degrees.
+        fn get_sector_at(&self, cylinder: u32, head: u32, angle: u32) -> u32;
+
+        /// The logical block at `sector` under `head` on `cylinder`, or for
+        /// tasks addressed by position, without one, the block passing under
+        /// it at `angle` degrees.
+        fn to_lba_at(
+            &self,
+            cylinder: u32,
+            head: u32,
+            angle: u32,
+            sector: Option<u32>,
+        ) -> Option<u64> {
+            if cylinder == 0 {
+                return None;
+            }
+
+            self.to_lba(Location {
+                cylinder: cylinder,
+                head: head,
+                sector: sector.unwrap_or_else(|| self.get_sector_at(cylinder, head, angle)),
+            })
+        }
+    }
+
+    /// How logical blocks are laid out over the cylinders, heads and sectors.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Mapping {
+        /// The same number of blocks on every track, as many as the innermost
+        /// zone has sectors, without skew.
+        LINEAR,
+        /// Every sector of every zone, cylinder by cylinder and surface by
+        /// surface within a cylinder, with skew.
+        ZONED,
+        /// Zone by zone, one surface at a time, going inwards on even heads
+        /// and back outwards on odd ones, with skew.
+        SERPENTINE,
+    }
+
+    pub struct LinearMapper {
+        geometry: Geometry,
+        cylinders: u32,
+        sectors: u32,
+    }
+
+    pub struct ZonedMapper {
+        geometry: Geometry,
+        cylinders: u32,
+    }
+
+    pub struct SerpentineMapper {
+        geometry: Geometry,
+        cylinders: u32,
+    }
+
+    impl Mapping {
+        pub fn default() -> Mapping {
+            Mapping::ZONED
+        }
+
+        pub fn is_zoned(&self) -> bool {
+            *self == Mapping::ZONED
+        }
+
+        pub fn from_name(name: &str) -> Result<Mapping, String> {
+            match name.trim().to_lowercase().as_str() {
+                "linear" => Ok(Mapping::LINEAR),
+                "zoned" => Ok(Mapping::ZONED),
+                "serpentine" => Ok(Mapping::SERPENTINE),
+                _ => Err(format!(
+                    "Unknown mapping `{}`, expected `linear`, `zoned` or `serpentine`",
+                    name
+                )),
+            }
+        }
+
+        pub fn get_name(&self) -> &'static str {
+            match self {
+                Mapping::LINEAR => "linear",
+                Mapping::ZONED => "zoned",
+                Mapping::SERPENTINE => "serpentine",
+            }
+        }
+
+        /// The mapper for a disk of `geometry` with `cylinders` cylinders.
+        pub fn build(&self, geometry: &Geometry, cylinders: u32) -> Box<dyn GeometryMapper> {
+            let geometry = geometry.clone();
+            match self {
+                Mapping::LINEAR => Box::new(LinearMapper {
+                    sectors: geometry
+                        .zones
+                        .get_zones()
+                        .iter()
+                        .map(|zone| zone.sectors)
+                        .min()
+                        .unwrap_or(DEFAULT_SECTORS),
+                    geometry: geometry,
+                    cylinders: cylinders,
+                }),
+                Mapping::ZONED => Box::new(ZonedMapper {
+                    geometry: geometry,
+                    cylinders: cylinders,
+                }),
+                Mapping::SERPENTINE => Box::new(SerpentineMapper {
+                    geometry: geometry,
+                    cylinders: cylinders,
+                }),
+            }
+        }
+    }
+
+    impl GeometryMapper for LinearMapper {
+        fn get_name(&self) -> &'static str {
+            Mapping::LINEAR.get_name()
+        }
+
+        fn get_capacity(&self) -> u64 {
+            self.cylinders as u64 * self.geometry.heads as u64 * self.sectors as u64
+        }
+
+        fn to_location(&self, lba: u64) -> Option<Location> {
+            if lba >= self.get_capacity() {
+                return None;
+            }
+
+            let track = lba / self.sectors as u64;
+            Some(Location {
+                cylinder: (track / self.geometry.heads as u64) as u32 + 1,
+                head: (track % self.geometry.heads as u64) as u32,
+                sector: (lba % self.sectors as u64) as u32,
+            })
+        }
+
+        fn to_lba(&self, location: Location) -> Option<u64> {
+            if location.cylinder == 0
+                || location.cylinder > self.cylinders
+                || location.head >= self.geometry.heads
+                || location.sector >= self.sectors
+            {
+                return None;
+            }
+
+            let track =
+                (location.cylinder - 1) as u64 * self.geometry.heads as u64 + location.head as u64;
+            Some(track * self.sectors as u64 + location.sector as u64)
+        }
+
+        fn get_angle(&self, location: Location) -> u32 {
+            let sectors = self.geometry.get_sectors(location.cylinder);
+            (location.sector as u64 * 360 / sectors as u64) as u32
+        }
+
+        fn get_sector_at(&self, cylinder: u32, _head: u32, angle: u32) -> u32 {
+            let sectors = self.geometry.get_sectors(cylinder);
+            ((angle as u64 * sectors as u64 / 360) as u32).min(self.sectors - 1)
+        }
+    }
+
+    impl GeometryMapper for ZonedMapper {
+        fn get_name(&self) -> &'static str {
+            Mapping::ZONED.get_name()
+        }
+
+        fn get_capacity(&self) -> u64 {
+            self.geometry.get_capacity(self.cylinders)
+        }
+
+        fn to_location(&self, lba: u64) -> Option<Location> {
+            let heads = self.geometry.heads as u64;
+            let mut first_block = 0;
+            for zone in self.geometry.zones.get_zones_of(self.cylinders).iter() {
+                let capacity = zone.get_capacity() * heads;
+                if lba < first_block + capacity {
+                    let sectors = zone.sectors as u64;
+                    let offset = lba - first_block;
+                    let track = offset / sectors;
+                    return Some(Location {
+                        cylinder: zone.first_track + (track / heads) as u32,
+                        head: (track % heads) as u32,
+                        sector: (offset % sectors) as u32,
+                    });
+                }
+                first_block += capacity;
+            }
+
+            None
+        }
+
+        fn to_lba(&self, location: Location) -> Option<u64> {
+            if !self.geometry.contains(location, self.cylinders) {
+                return None;
+            }
+
+            let heads = self.geometry.heads as u64;
+            let mut first_block = 0;
+            for zone in self.geometry.zones.get_zones_of(self.cylinders).iter() {
+                if location.cylinder <= zone.last_track {
+                    let track = (location.cylinder - zone.first_track) as u64 * heads
+                        + location.head as u64;
+                    return Some(
+                        first_block + track * zone.sectors as u64 + location.sector as u64,
+                    );
+                }
+                first_block += zone.get_capacity() * heads;
+            }
+
+            None
+        }
+
+        fn get_angle(&self, location: Location) -> u32 {
+            self.geometry.get_angle(location)
+        }
+
+        fn get_sector_at(&self, cylinder: u32, head: u32, angle: u32) -> u32 {
+            self.geometry.get_sector_at(cylinder, head, angle)
+        }
+    }
+
+    impl SerpentineMapper {
+        /// Cylinder of the `index`th track a head passes over in `zone`.
+        fn get_cylinder(zone: &Zone, head: u32, index: u32) -> u32 {
+            if head.is_multiple_of(2) {
+                zone.first_track + index
+            } else {
+                zone.last_track - index
+            }
+        }
+    }
+
+    impl GeometryMapper for SerpentineMapper {
+        fn get_name(&self) -> &'static str {
+            Mapping::SERPENTINE.get_name()
+        }
+
+        fn get_capacity(&self) -> u64 {
+            self.geometry.get_capacity(self.cylinders)
+        }
+
+        fn to_location(&self, lba: u64) -> Option<Location> {
+            let heads = self.geometry.heads as u64;
+            let mut first_block = 0;
+            for zone in self.geometry.zones.get_zones_of(self.cylinders).iter() {
+                let capacity = zone.get_capacity() * heads;
+                if lba < first_block + capacity {
+                    let sectors = zone.sectors as u64;
+                    let offset = lba - first_block;
+                    let head = (offset / zone.get_capacity()) as u32;
+                    let offset = offset % zone.get_capacity();
+                    return Some(Location {
+                        cylinder: SerpentineMapper::get_cylinder(
+                            zone,
+                            head,
+                            (offset / sectors) as u32,
+                        ),
+                        head: head,
+                        sector: (offset % sectors) as u32,
+                    });
+                }
+                first_block += capacity;
+            }
+
+            None
+        }
+
+        fn to_lba(&self, location: Location) -> Option<u64> {
+            if !self.geometry.contains(location, self.cylinders) {
+                return None;
+            }
+
+            let heads = self.geometry.heads as u64;
+            let mut first_block = 0;
+            for zone in self.geometry.zones.get_zones_of(self.cylinders).iter() {
+                if location.cylinder <= zone.last_track {
+                    let index = if location.head.is_multiple_of(2) {
+                        location.cylinder - zone.first_track
+                    } else {
+                        zone.last_track - location.cylinder
+                    };
+                    return Some(
+                        first_block
+                            + location.head as u64 * zone.get_capacity()
+                            + index as u64 * zone.sectors as u64
+                            + location.sector as u64,
+                    );
+                }
+                first_block += zone.get_capacity() * heads;
+            }
+
+            None
+        }
+
+        fn get_angle(&self, location: Location) -> u32 {
+            self.geometry.get_angle(location)
+        }
+
+        fn get_sector_at(&self, cylinder: u32, head: u32, angle: u32) -> u32 {
+            self.geometry.get_sector_at(cylinder, head, angle)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::driver::driver::Task;
+        use crate::disk::geometry::geometry::ZoneTable;
+
+        const CYLINDERS: u32 = 5;
+
+        /// Two skewed surfaces with 12 sectors on cylinders 1 to 3 and 8 on
+        /// cylinders 4 and 5.
+        fn geometry(mapping: Mapping) -> Geometry {
+            Geometry {
+                heads: 2,
+                head_switch: 0,
+                track_skew: 3,
+                cylinder_skew: 5,
+                zones: ZoneTable::from_zones(vec![
+                    Zone {
+                        first_track: 1,
+                        last_track: 3,
+                        sectors: 12,
+                    },
+                    Zone {
+                        first_track: 4,
+                        last_track: 5,
+                        sectors: 8,
+                    },
+                ])
+                .unwrap(),
+                mapping: mapping,
+            }
+        }
+
+        fn mapper(mapping: Mapping) -> Box<dyn GeometryMapper> {
+            mapping.build(&geometry(mapping), CYLINDERS)
+        }
+
+        fn chs(location: Location) -> (u32, u32, u32) {
+            (location.cylinder, location.head, location.sector)
+        }
+
+        #[test]
+        fn every_block_maps_to_a_sector_and_back() {
+            for mapping in [Mapping::LINEAR, Mapping::ZONED, Mapping::SERPENTINE] {
+                let mapper = mapper(mapping);
+                let geometry = geometry(mapping);
+
+                for lba in 0..mapper.get_capacity() {
+                    let location = mapper.to_location(lba).unwrap();
+                    assert!(geometry.contains(location, CYLINDERS));
+                    assert_eq!(mapper.to_lba(location), Some(lba), "{}", mapping.get_name());
+
+                    let angle = mapper.get_angle(location);
+                    assert_eq!(
+                        mapper.to_lba_at(location.cylinder, location.head, angle, None),
+                        Some(lba),
+                        "{} at {} degrees",
+                        mapping.get_name(),
+                        angle
+                    );
+                }
+                assert!(mapper.to_location(mapper.get_capacity()).is_none());
+            }
+        }
+
+        #[test]
+        fn tasks_keep_their_block_on_tracks_with_more_sectors_than_degrees() {
+            let geometry = Geometry {
+                heads: 2,
+                head_switch: 0,
+                track_skew: 3,
+                cylinder_skew: 5,
+                zones: ZoneTable::from_zones(vec![
+                    Zone {
+                        first_track: 1,
+                        last_track: 2,
+                        sectors: 720,
+                    },
+                    Zone {
+                        first_track: 3,
+                        last_track: 3,
+                        sectors: 540,
+                    },
+                ])
+                .unwrap(),
+                mapping: Mapping::ZONED,
+            };
+
+            for mapping in [Mapping::LINEAR, Mapping::ZONED, Mapping::SERPENTINE] {
+                let mapper = mapping.build(&geometry, 3);
+                for lba in 0..mapper.get_capacity() {
+                    let task = Task::from_lba(0, lba, 1, mapper.as_ref()).unwrap();
+                    assert_eq!(
+                        mapper.to_lba_at(
+                            *task.get_track(),
+                            *task.get_head(),
+                            *task.get_angle(),
+                            task.get_sector()
+                        ),
+                        Some(lba),
+                        "{}",
+                        mapping.get_name()
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn capacity_depends_on_the_mapping() {
+            assert_eq!(mapper(Mapping::LINEAR).get_capacity(), 5 * 2 * 8);
+            assert_eq!(mapper(Mapping::ZONED).get_capacity(), (3 * 12 + 2 * 8) * 2);
+            assert_eq!(
+                mapper(Mapping::SERPENTINE).get_capacity(),
+                (3 * 12 + 2 * 8) * 2
+            );
+        }
+
+        #[test]
+        fn sectors_outside_the_mapping_have_no_block() {
+            for mapping in [Mapping::LINEAR, Mapping::ZONED, Mapping::SERPENTINE] {
+                let mapper = mapper(mapping);
+                for (cylinder, head, sector) in [(0, 0, 0), (6, 0, 0), (1, 2, 0), (4, 0, 8)] {
+                    let location = Location {
+                        cylinder: cylinder,
+                        head: head,
+                        sector: sector,
+                    };
+                    assert!(mapper.to_lba(location).is_none(), "{}", mapping.get_name());
+                }
+                assert!(mapper.to_lba_at(0, 0, 0, None).is_none());
+            }
+
+            // Linear mapping only uses as many sectors as the innermost zone has.
+            let location = Location {
+                cylinder: 1,
+                head: 0,
+                sector: 8,
+            };
+            assert!(mapper(Mapping::LINEAR).to_lba(location).is_none());
+        }
+
+        #[test]
+        fn zoned_mapping_fills_a_cylinder_before_the_next() {
+            let mapper = mapper(Mapping::ZONED);
+
+            assert_eq!(chs(mapper.to_location(11).unwrap()), (1, 0, 11));
+            assert_eq!(chs(mapper.to_location(12).unwrap()), (1, 1, 0));
+            assert_eq!(chs(mapper.to_location(24).unwrap()), (2, 0, 0));
+            assert_eq!(chs(mapper.to_location(72).unwrap()), (4, 0, 0));
+        }
+
+        #[test]
+        fn serpentine_mapping_comes_back_out_on_odd_heads() {
+            let mapper = mapper(Mapping::SERPENTINE);
+
+            assert_eq!(chs(mapper.to_location(35).unwrap()), (3, 0, 11));
+            assert_eq!(chs(mapper.to_location(36).unwrap()), (3, 1, 0));
+            assert_eq!(chs(mapper.to_location(71).unwrap()), (1, 1, 11));
+            assert_eq!(chs(mapper.to_location(72).unwrap()), (4, 0, 0));
+            assert_eq!(chs(mapper.to_location(88).unwrap()), (5, 1, 0));
+        }
+
+        #[test]
+        fn skew_shifts_where_a_track_starts() {
+            let mapper = mapper(Mapping::ZONED);
+            let first = |cylinder, head| {
+                mapper.get_angle(Location {
+                    cylinder: cylinder,
+                    head: head,
+                    sector: 0,
+                })
+            };
+
+            assert_eq!(first(1, 0), 0);
+            // 3 sectors of 12 for the head, then 3 + 5 for the cylinder.
+            assert_eq!(first(1, 1), 90);
+            assert_eq!(first(2, 0), 240);
+        }
+    }
+}
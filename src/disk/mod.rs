@@ -1,3 +1,47 @@
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod cache;
+#[allow(clippy::module_inception)]
+pub mod device;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
 pub mod disk;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
+pub mod driver;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
+pub mod geometry;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
 pub mod hardware_manager;
-pub mod driver;
\ No newline at end of file
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
+pub mod mapping;
+#[allow(clippy::module_inception)]
+pub mod queue;
+#[allow(clippy::module_inception, clippy::should_implement_trait)]
+pub mod rotation;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait,
+    clippy::upper_case_acronyms
+)]
+pub mod seek;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
+pub mod ssd;
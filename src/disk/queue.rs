@@ -0,0 +1,145 @@
+pub mod queue {
+    use crate::disk::driver::driver::{Operation, Task};
+
+    /// Native command queuing: the tasks the host has handed to the drive
+    /// and that it has not finished yet. The drive picks which queued task
+    /// to serve next itself, whatever order they came in.
+    pub struct CommandQueue {
+        depth: u32,
+        /// Waiting for the drive, in the order they were handed over.
+        tasks: Vec<Task>,
+        /// Handed over and not finished, the one being served included.
+        outstanding: u32,
+        /// Whether a flush is outstanding; the drive takes nothing else
+        /// until it is done, as flushes cannot be queued.
+        flushing: bool,
+    }
+
+    impl CommandQueue {
+        pub fn new(depth: u32) -> CommandQueue {
+            CommandQueue {
+                depth: depth.max(1),
+                tasks: Vec::new(),
+                outstanding: 0,
+                flushing: false,
+            }
+        }
+
+        pub fn get_depth(&self) -> u32 {
+            self.depth
+        }
+
+        /// The queued tasks, oldest first.
+        pub fn get_tasks(&self) -> &[Task] {
+            &self.tasks
+        }
+
+        /// Whether a task doing `operation` can be handed over now. A flush
+        /// has to wait until nothing else is outstanding.
+        pub fn accepts(&self, operation: Operation) -> bool {
+            if self.flushing {
+                return false;
+            }
+            if operation == Operation::FLUSH {
+                return self.outstanding == 0;
+            }
+
+            self.outstanding < self.depth
+        }
+
+        pub fn push(&mut self, task: &Task) {
+            if task.get_operation() == Operation::FLUSH {
+                self.flushing = true;
+            }
+            self.outstanding += 1;
+            self.tasks.push(task.clone());
+        }
+
+        /// Takes the `index`th queued task out for the drive to serve.
+        pub fn take(&mut self, index: usize) -> Task {
+            self.tasks.remove(index)
+        }
+
+        /// The drive finished a task it took out of the queue.
+        pub fn finish(&mut self, task: &Task) {
+            if task.get_operation() == Operation::FLUSH {
+                self.flushing = false;
+            }
+            self.outstanding = self.outstanding.saturating_sub(1);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn task(id: u32, operation: Operation) -> Task {
+            let mut task = Task::new(id, 1, 0);
+            task.set_operation(operation);
+            task
+        }
+
+        #[test]
+        fn depth_is_at_least_one() {
+            assert_eq!(CommandQueue::new(0).get_depth(), 1);
+            assert_eq!(CommandQueue::new(8).get_depth(), 8);
+        }
+
+        #[test]
+        fn takes_tasks_up_to_its_depth() {
+            let mut queue = CommandQueue::new(2);
+            queue.push(&task(1, Operation::READ));
+            assert!(queue.accepts(Operation::WRITE));
+            queue.push(&task(2, Operation::WRITE));
+            assert!(!queue.accepts(Operation::READ));
+
+            // A task being served still counts until it is finished.
+            let served = queue.take(0);
+            assert!(!queue.accepts(Operation::READ));
+            queue.finish(&served);
+            assert!(queue.accepts(Operation::READ));
+        }
+
+        #[test]
+        fn takes_any_queued_task() {
+            let mut queue = CommandQueue::new(4);
+            for id in 1..=3 {
+                queue.push(&task(id, Operation::READ));
+            }
+
+            assert_eq!(*queue.take(1).get_id(), 2);
+            let left: Vec<u32> = queue
+                .get_tasks()
+                .iter()
+                .map(|task| *task.get_id())
+                .collect();
+            assert_eq!(left, vec![1, 3]);
+        }
+
+        #[test]
+        fn flush_waits_for_everything_outstanding() {
+            let mut queue = CommandQueue::new(4);
+            queue.push(&task(1, Operation::WRITE));
+            assert!(!queue.accepts(Operation::FLUSH));
+
+            let write = queue.take(0);
+            assert!(!queue.accepts(Operation::FLUSH));
+            queue.finish(&write);
+            assert!(queue.accepts(Operation::FLUSH));
+        }
+
+        #[test]
+        fn nothing_is_taken_while_a_flush_is_outstanding() {
+            let mut queue = CommandQueue::new(4);
+            queue.push(&task(1, Operation::FLUSH));
+            assert!(!queue.accepts(Operation::READ));
+            assert!(!queue.accepts(Operation::FLUSH));
+
+            let flush = queue.take(0);
+            assert!(!queue.accepts(Operation::WRITE));
+            queue.finish(&flush);
+            assert!(queue.accepts(Operation::WRITE));
+            assert!(queue.accepts(Operation::FLUSH));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+pub mod rotation {
+    use serde::{Deserialize, Serialize};
+
+    /// A platter that keeps spinning whatever the head does; its angle is a
+    /// function of the simulation time alone.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    pub struct ContinuousRotation {
+        /// Revolutions per minute; without it the platter turns one degree
+        /// every `spin_speed` ticks.
+        #[serde(default)]
+        pub rpm: Option<f64>,
+        /// Nanoseconds per tick, used to turn `rpm` into ticks.
+        #[serde(default = "ContinuousRotation::default_tick")]
+        pub tick: f64,
+    }
+
+    /// How the platter turns under the head.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    #[serde(tag = "model", rename_all = "lowercase")]
+    pub enum RotationModel {
+        /// The platter only turns while the head waits for a sector, one
+        /// degree every `spin_speed` ticks, as it always has.
+        STEPPED,
+        CONTINUOUS(ContinuousRotation),
+    }
+
+    impl ContinuousRotation {
+        fn default_tick() -> f64 {
+            1000.0
+        }
+
+        pub fn from_rpm(rpm: f64) -> ContinuousRotation {
+            ContinuousRotation {
+                rpm: Some(rpm),
+                tick: ContinuousRotation::default_tick(),
+            }
+        }
+
+        /// Ticks one revolution takes.
+        pub fn get_period(&self, spin_speed: u32) -> f64 {
+            match self.rpm {
+                Some(rpm) => 60.0 * 1000.0 * 1000.0 * 1000.0 / (rpm * self.tick),
+                None => 360.0 * spin_speed as f64,
+            }
+        }
+
+        /// Phase of the platter at `time`, in [0, 1) revolutions.
+        pub fn get_phase(&self, spin_speed: u32, time: u64) -> f64 {
+            (time as f64 / self.get_period(spin_speed)).fract()
+        }
+
+        /// Whole degree under the head at `time`.
+        pub fn get_angle(&self, spin_speed: u32, time: u64) -> u32 {
+            ((self.get_phase(spin_speed, time) * 360.0) as u32).min(359)
+        }
+
+        /// Ticks from `time` until `angle` is under the head.
+        pub fn get_latency(&self, spin_speed: u32, time: u64, angle: u32) -> u64 {
+            let phase = self.get_phase(spin_speed, time);
+            let distance = (angle as f64 / 360.0 - phase).rem_euclid(1.0);
+
+            (distance * self.get_period(spin_speed)).ceil() as u64
+        }
+
+        pub fn validate(&self) -> Result<(), String> {
+            if self.rpm.is_some_and(|rpm| rpm <= 0.0) {
+                return Err("disk.rotation.rpm must be positive".to_owned());
+            }
+            if self.tick <= 0.0 {
+                return Err("disk.rotation.tick must be positive".to_owned());
+            }
+
+            Ok(())
+        }
+    }
+
+    impl RotationModel {
+        pub fn default() -> RotationModel {
+            RotationModel::STEPPED
+        }
+
+        pub fn is_stepped(&self) -> bool {
+            *self == RotationModel::STEPPED
+        }
+
+        pub fn get_name(&self) -> &'static str {
+            match self {
+                RotationModel::STEPPED => "stepped",
+                RotationModel::CONTINUOUS(_) => "continuous",
+            }
+        }
+
+        pub fn validate(&self) -> Result<(), String> {
+            match self {
+                RotationModel::STEPPED => Ok(()),
+                RotationModel::CONTINUOUS(rotation) => rotation.validate(),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn the_platter_is_back_after_a_full_period() {
+            // 6000 revolutions a minute at a microsecond a tick: 10000 ticks each.
+            let rotation = ContinuousRotation::from_rpm(6000.0);
+
+            assert_eq!(rotation.get_period(100), 10000.0);
+            assert_eq!(rotation.get_angle(100, 0), 0);
+            assert_eq!(rotation.get_angle(100, 2500), 90);
+            assert_eq!(rotation.get_angle(100, 10000), 0);
+            assert_eq!(rotation.get_angle(100, 12500), 90);
+            assert_eq!(rotation.get_angle(100, 9999), 359);
+
+            let stepped = ContinuousRotation {
+                rpm: None,
+                tick: 1000.0,
+            };
+            assert_eq!(stepped.get_period(2), 720.0);
+            assert_eq!(stepped.get_angle(2, 180), 90);
+            assert_eq!(stepped.get_angle(2, 720 + 180), 90);
+        }
+
+        #[test]
+        fn latency_wraps_around_past_zero() {
+            let rotation = ContinuousRotation::from_rpm(6000.0);
+
+            assert_eq!(rotation.get_latency(100, 7500, 270), 0);
+            assert_eq!(rotation.get_latency(100, 7500, 0), 2500);
+            assert_eq!(rotation.get_latency(100, 7500, 90), 5000);
+            assert_eq!(rotation.get_latency(100, 17500, 90), 5000);
+            // Just missed it: almost a whole revolution.
+            assert_eq!(rotation.get_latency(100, 7501, 270), 9999);
+        }
+
+        #[test]
+        fn odd_speeds_stay_within_one_revolution() {
+            for rpm in [5400.0, 7000.0, 10033.3] {
+                let rotation = ContinuousRotation::from_rpm(rpm);
+                let period = rotation.get_period(100);
+
+                for time in (0..100000).step_by(37) {
+                    let angle = rotation.get_angle(100, time);
+                    assert!(angle < 360);
+                    let next = (angle + 1) % 360;
+                    assert!(rotation.get_latency(100, time, angle) as f64 <= period.ceil());
+                    assert!(rotation.get_latency(100, time, next) as f64 <= period / 360.0 + 1.0);
+                }
+            }
+        }
+
+        #[test]
+        fn a_platter_has_to_turn() {
+            assert!(ContinuousRotation::from_rpm(0.0).validate().is_err());
+            assert!(ContinuousRotation::from_rpm(-7200.0).validate().is_err());
+            assert!(ContinuousRotation::from_rpm(5400.0).validate().is_ok());
+            assert!(ContinuousRotation {
+                rpm: None,
+                tick: 0.0
+            }
+            .validate()
+            .is_err());
+            assert!(RotationModel::default().validate().is_ok());
+            assert!(RotationModel::CONTINUOUS(ContinuousRotation::from_rpm(0.0))
+                .validate()
+                .is_err());
+        }
+    }
+}
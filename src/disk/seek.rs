@@ -0,0 +1,351 @@
+pub mod seek {
+    use std::fs;
+
+    use serde::{Deserialize, Serialize};
+
+    /// Seek time over distance, in ticks: `short_base + short_factor * √d` up
+    /// to `boundary` tracks, `long_base + long_factor * d` beyond, and the
+    /// head needs `settle` more ticks on the destination track before it can read.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    pub struct SeekCurve {
+        pub short_base: f64,
+        pub short_factor: f64,
+        pub long_base: f64,
+        pub long_factor: f64,
+        pub boundary: u32,
+        #[serde(default)]
+        pub settle: u32,
+    }
+
+    /// How long moving the head takes.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    #[serde(try_from = "SeekSpec", into = "SeekSpec")]
+    pub enum SeekModel {
+        /// One track per tick whatever the distance, as the head has always
+        /// moved; `forward_speed` is not applied so earlier results reproduce.
+        TRACK,
+        CURVE(SeekCurve),
+    }
+
+    /// What `[disk.seek]` may contain; measured tables are fitted once when read.
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "model", rename_all = "lowercase")]
+    enum SeekSpec {
+        TRACK,
+        CURVE(SeekCurve),
+        /// `[distance, ticks]` pairs, settle time included.
+        MEASURED {
+            measurements: Vec<(u32, f64)>,
+            #[serde(default)]
+            settle: u32,
+        },
+    }
+
+    /// Least squares line through the points: (intercept, slope, squared error).
+    fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64, f64)> {
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        if points.len() < 2 || sxx == 0.0 {
+            return None;
+        }
+
+        let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+        let slope = sxy / sxx;
+        let intercept = mean_y - slope * mean_x;
+        let error = points
+            .iter()
+            .map(|p| (p.1 - intercept - slope * p.0).powi(2))
+            .sum();
+
+        Some((intercept, slope, error))
+    }
+
+    /// Reads `distance,ticks` lines of measured seek times.
+    pub fn read_seek_table(path: &str) -> Result<Vec<(u32, f64)>, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read seek table {}: {}", path, e))?;
+
+        let mut measurements: Vec<(u32, f64)> = Vec::new();
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("distance") || line.starts_with('#') {
+                continue;
+            }
+
+            let measurement = line.split_once(',').and_then(|(distance, time)| {
+                Some((
+                    distance.trim().parse::<u32>().ok()?,
+                    time.trim().parse::<f64>().ok()?,
+                ))
+            });
+            match measurement {
+                Some(measurement) => measurements.push(measurement),
+                None => {
+                    return Err(format!(
+                        "{}:{}: expected `distance,ticks`, found `{}`",
+                        path,
+                        line_number + 1,
+                        line
+                    ))
+                }
+            }
+        }
+
+        Ok(measurements)
+    }
+
+    impl SeekCurve {
+        /// Fits a curve to measured `(distance, ticks)` pairs, settle time
+        /// included. The boundary between the square root and the linear part
+        /// is the measured distance that leaves the smallest squared error;
+        /// without enough long seeks the whole table is fitted by the square root.
+        pub fn fit(measurements: &[(u32, f64)], settle: u32) -> Result<SeekCurve, String> {
+            let mut points: Vec<(u32, f64)> = measurements
+                .iter()
+                .map(|&(distance, time)| (distance, time - settle as f64))
+                .collect();
+            points.sort_by_key(|p| p.0);
+
+            let short = |points: &[(u32, f64)]| {
+                let points: Vec<(f64, f64)> =
+                    points.iter().map(|p| ((p.0 as f64).sqrt(), p.1)).collect();
+                fit_line(&points)
+            };
+            let long = |points: &[(u32, f64)]| {
+                let points: Vec<(f64, f64)> = points.iter().map(|p| (p.0 as f64, p.1)).collect();
+                fit_line(&points)
+            };
+
+            let Some((short_base, short_factor, error)) = short(&points) else {
+                return Err(
+                    "A seek curve needs measurements at two or more different distances".to_owned(),
+                );
+            };
+            let mut best = SeekCurve {
+                short_base: short_base,
+                short_factor: short_factor,
+                long_base: 0.0,
+                long_factor: 0.0,
+                boundary: u32::MAX,
+                settle: settle,
+            };
+            let mut best_error = error;
+
+            for split in 2..points.len() {
+                let (near, far) = points.split_at(split);
+                if near.last().map(|p| p.0) == far.first().map(|p| p.0) {
+                    continue;
+                }
+                if let (Some(s), Some(l)) = (short(near), long(far)) {
+                    if s.2 + l.2 < best_error {
+                        best_error = s.2 + l.2;
+                        best = SeekCurve {
+                            short_base: s.0,
+                            short_factor: s.1,
+                            long_base: l.0,
+                            long_factor: l.1,
+                            boundary: near.last().unwrap().0,
+                            settle: settle,
+                        };
+                    }
+                }
+            }
+
+            Ok(best)
+        }
+
+        /// Ticks the head moves for before it starts settling; at least one.
+        pub fn get_motion_time(&self, distance: u32) -> u32 {
+            let time = if distance <= self.boundary {
+                self.short_base + self.short_factor * (distance as f64).sqrt()
+            } else {
+                self.long_base + self.long_factor * distance as f64
+            };
+
+            time.round().max(1.0) as u32
+        }
+
+        pub fn show(&self) {
+            println!(
+                "[disk.seek]\nmodel = \"curve\"\nshort_base = {:.3}\nshort_factor = {:.3}\nlong_base = {:.3}\nlong_factor = {:.3}\nboundary = {}\nsettle = {}",
+                self.short_base,
+                self.short_factor,
+                self.long_base,
+                self.long_factor,
+                self.boundary,
+                self.settle
+            );
+        }
+    }
+
+    impl SeekModel {
+        pub fn default() -> SeekModel {
+            SeekModel::TRACK
+        }
+
+        pub fn is_track(&self) -> bool {
+            *self == SeekModel::TRACK
+        }
+
+        /// Ticks the head spends moving over `distance` tracks.
+        pub fn get_motion_time(&self, distance: u32) -> u32 {
+            match self {
+                SeekModel::TRACK => distance,
+                SeekModel::CURVE(curve) => curve.get_motion_time(distance),
+            }
+        }
+
+        pub fn get_settle_time(&self) -> u32 {
+            match self {
+                SeekModel::TRACK => 0,
+                SeekModel::CURVE(curve) => curve.settle,
+            }
+        }
+
+        /// Motion and settle time of a seek over `distance` tracks.
+        pub fn get_seek_time(&self, distance: u32) -> u32 {
+            if distance == 0 {
+                return 0;
+            }
+
+            self.get_motion_time(distance) + self.get_settle_time()
+        }
+
+        pub fn get_name(&self) -> &'static str {
+            match self {
+                SeekModel::TRACK => "track",
+                SeekModel::CURVE(_) => "curve",
+            }
+        }
+    }
+
+    impl TryFrom<SeekSpec> for SeekModel {
+        type Error = String;
+
+        fn try_from(spec: SeekSpec) -> Result<SeekModel, String> {
+            match spec {
+                SeekSpec::TRACK => Ok(SeekModel::TRACK),
+                SeekSpec::CURVE(curve) => Ok(SeekModel::CURVE(curve)),
+                SeekSpec::MEASURED {
+                    measurements,
+                    settle,
+                } => Ok(SeekModel::CURVE(SeekCurve::fit(&measurements, settle)?)),
+            }
+        }
+    }
+
+    impl From<SeekModel> for SeekSpec {
+        fn from(model: SeekModel) -> SeekSpec {
+            match model {
+                SeekModel::TRACK => SeekSpec::TRACK,
+                SeekModel::CURVE(curve) => SeekSpec::CURVE(curve),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn assert_close(actual: f64, expected: f64) {
+            assert!(
+                (actual - expected).abs() < 1e-6,
+                "{} is not {}",
+                actual,
+                expected
+            );
+        }
+
+        #[test]
+        fn fits_a_square_root_curve_to_short_seeks() {
+            let measurements: Vec<(u32, f64)> = [1, 4, 9, 16, 25]
+                .iter()
+                .map(|&d| (d, 2.0 + 3.0 * (d as f64).sqrt() + 5.0))
+                .collect();
+            let curve = SeekCurve::fit(&measurements, 5).unwrap();
+
+            assert_close(curve.short_base, 2.0);
+            assert_close(curve.short_factor, 3.0);
+            assert_eq!(curve.boundary, u32::MAX);
+            assert_eq!(curve.settle, 5);
+        }
+
+        #[test]
+        fn finds_the_boundary_between_the_two_parts() {
+            let mut measurements: Vec<(u32, f64)> = [1, 4, 9, 16, 25, 64, 100]
+                .iter()
+                .map(|&d| (d, 1.0 + 0.5 * (d as f64).sqrt()))
+                .collect();
+            measurements.extend(
+                [200, 400, 800, 1600]
+                    .iter()
+                    .map(|&d| (d, 3.0 + 0.02 * d as f64)),
+            );
+            // The order of the table does not matter.
+            measurements.reverse();
+            let curve = SeekCurve::fit(&measurements, 0).unwrap();
+
+            assert_eq!(curve.boundary, 100);
+            assert_close(curve.short_base, 1.0);
+            assert_close(curve.short_factor, 0.5);
+            assert_close(curve.long_base, 3.0);
+            assert_close(curve.long_factor, 0.02);
+            assert_eq!(curve.get_motion_time(100), 6);
+            assert_eq!(curve.get_motion_time(1000), 23);
+        }
+
+        #[test]
+        fn fitting_needs_two_distances() {
+            assert!(SeekCurve::fit(&[(10, 5.0), (10, 6.0)], 0).is_err());
+            assert!(SeekCurve::fit(&[], 0).is_err());
+        }
+
+        #[test]
+        fn seeks_take_at_least_a_tick_and_then_settle() {
+            let model = SeekModel::CURVE(SeekCurve {
+                short_base: 0.0,
+                short_factor: 0.1,
+                long_base: 0.0,
+                long_factor: 0.0,
+                boundary: u32::MAX,
+                settle: 3,
+            });
+
+            assert_eq!(model.get_seek_time(0), 0);
+            assert_eq!(model.get_seek_time(1), 4);
+            assert_eq!(model.get_seek_time(400), 5);
+            assert_eq!(SeekModel::TRACK.get_seek_time(7), 7);
+        }
+
+        #[test]
+        fn measured_tables_are_fitted_when_read() {
+            let model: SeekModel = toml::from_str(
+                "model = \"measured\"\nmeasurements = [[1, 4.0], [4, 5.0], [9, 6.0]]\nsettle = 1",
+            )
+            .unwrap();
+
+            match model {
+                SeekModel::CURVE(curve) => {
+                    assert_close(curve.short_base, 2.0);
+                    assert_close(curve.short_factor, 1.0);
+                    assert_eq!(curve.settle, 1);
+                }
+                SeekModel::TRACK => panic!("expected a fitted curve"),
+            }
+        }
+
+        #[test]
+        fn seek_tables_report_the_line_they_can_not_read() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("seek-table.csv");
+            fs::write(&path, "distance,ticks\n1,3.5\n# comment\n4,x\n").unwrap();
+            let path = path.to_str().unwrap();
+
+            let error = read_seek_table(path).unwrap_err();
+
+            assert!(error.starts_with(&format!("{}:4:", path)), "{}", error);
+        }
+    }
+}
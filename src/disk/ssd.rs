@@ -0,0 +1,702 @@
+pub mod ssd {
+    use serde::{Deserialize, Serialize};
+
+    use crate::disk::{
+        device::device::Device,
+        disk::disk::DiskStatistics,
+        driver::driver::{Operation, Task},
+        hardware_manager::hardware_manager::DiskState,
+        mapping::mapping::GeometryMapper,
+        queue::queue::CommandQueue,
+    };
+
+    /// Marks a logical page without data, or a physical page whose data is
+    /// no longer current.
+    const NO_PAGE: u32 = u32::MAX;
+
+    /// A flash drive: dies spread over channels, each reading and
+    /// programming whole pages and erasing whole blocks of them. Times are
+    /// in ticks, of a microsecond with the defaults.
+    #[derive(Serialize, Deserialize, Copy, Clone, PartialEq)]
+    #[serde(default = "SsdConfig::default", deny_unknown_fields)]
+    pub struct SsdConfig {
+        pub channels: u32,
+        /// Dies on every channel.
+        pub dies: u32,
+        pub pages_per_block: u32,
+        /// Logical blocks a page holds.
+        pub sectors_per_page: u32,
+        /// Ticks a die takes to read a page into its register.
+        pub read_latency: u32,
+        pub program_latency: u32,
+        pub erase_latency: u32,
+        /// Ticks a page takes to cross the channel.
+        pub transfer_time: u32,
+        /// Pages the dies have beyond the logical ones, as a share of those.
+        pub over_provisioning: f64,
+        /// Free blocks below which a die collects garbage.
+        pub gc_threshold: u32,
+    }
+
+    /// What the flash did for the host and for itself.
+    #[derive(Copy, Clone)]
+    pub struct FlashStatistics {
+        page_reads: u64,
+        page_programs: u64,
+        /// Valid pages garbage collection moved out of blocks it erased.
+        gc_copies: u64,
+        block_erases: u32,
+    }
+
+    struct Die {
+        /// Pages of each block that hold current data.
+        valid: Vec<u32>,
+        /// Erased blocks, in the order they get written.
+        free: Vec<u32>,
+        /// The block pages are being programmed into, and how many it has.
+        active: u32,
+        written: u32,
+        /// When the die is done with everything it was given.
+        busy_until: u64,
+    }
+
+    /// An SSD with a page mapping flash translation layer. Logical pages
+    /// are striped over the dies, a rewritten page goes to the next free
+    /// page of its die, and a die short of free blocks erases the one with
+    /// the fewest valid pages after moving those. Garbage collection runs
+    /// on the die after the write that triggered it, so that only tasks
+    /// needing that die wait for it.
+    ///
+    /// The drive starts out with every logical block written once, in
+    /// order. Writes cover whole pages and flushes have nothing to wait
+    /// for. Tasks keep the blocks they were given: their logical block,
+    /// or the one `mapper` finds at their position, and a task with neither
+    /// is turned down before the simulation starts. The drive has no head,
+    /// so all of a task's ticks count as transfer time.
+    pub struct Ssd {
+        config: SsdConfig,
+        mapper: Box<dyn GeometryMapper>,
+        blocks_per_die: u32,
+        dies: Vec<Die>,
+        /// When every channel is free again.
+        channels: Vec<u64>,
+        /// Physical page of every logical page.
+        map: Vec<u32>,
+        /// Logical page of every physical page holding current data.
+        owner: Vec<u32>,
+        queue: CommandQueue,
+        state: DiskState,
+        /// The track of the last task, which drivers order tasks around.
+        current_track: u32,
+        /// When the task being served is done.
+        done: u64,
+        time: u64,
+        statistics: DiskStatistics,
+        flash: FlashStatistics,
+    }
+
+    impl SsdConfig {
+        pub fn default() -> SsdConfig {
+            SsdConfig {
+                channels: 8,
+                dies: 4,
+                pages_per_block: 256,
+                sectors_per_page: 8,
+                read_latency: 50,
+                program_latency: 600,
+                erase_latency: 3000,
+                transfer_time: 10,
+                over_provisioning: 0.07,
+                gc_threshold: 2,
+            }
+        }
+
+        pub fn validate(&self) -> Result<(), String> {
+            if self.channels == 0 || self.dies == 0 {
+                return Err("disk.ssd needs at least one channel and one die".to_owned());
+            }
+            if self.pages_per_block == 0 || self.sectors_per_page == 0 {
+                return Err(
+                    "disk.ssd.pages_per_block and sectors_per_page must be at least 1".to_owned(),
+                );
+            }
+            if self.over_provisioning <= 0.0 {
+                return Err("disk.ssd.over_provisioning must be positive".to_owned());
+            }
+            if self.gc_threshold < 2 {
+                return Err("disk.ssd.gc_threshold must be at least 2".to_owned());
+            }
+
+            Ok(())
+        }
+
+        fn get_die_count(&self) -> u32 {
+            self.channels * self.dies
+        }
+    }
+
+    impl FlashStatistics {
+        pub fn default() -> FlashStatistics {
+            FlashStatistics {
+                page_reads: 0,
+                page_programs: 0,
+                gc_copies: 0,
+                block_erases: 0,
+            }
+        }
+
+        pub fn get_page_reads(&self) -> u64 {
+            self.page_reads
+        }
+
+        pub fn get_page_programs(&self) -> u64 {
+            self.page_programs
+        }
+
+        pub fn get_gc_copies(&self) -> u64 {
+            self.gc_copies
+        }
+
+        pub fn get_block_erases(&self) -> u32 {
+            self.block_erases
+        }
+
+        /// Pages programmed per page the host wrote, when it wrote any.
+        pub fn get_write_amplification(&self) -> Option<f64> {
+            if self.page_programs == 0 {
+                return None;
+            }
+
+            Some((self.page_programs + self.gc_copies) as f64 / self.page_programs as f64)
+        }
+
+        pub fn show(&self) {
+            println!(
+                "Flash <\n\tpage reads: {}, page programs: {}, pages moved by garbage collection: {}, block erases: {}, write amplification: {}\n>",
+                self.page_reads,
+                self.page_programs,
+                self.gc_copies,
+                self.block_erases,
+                self.get_write_amplification()
+                    .map_or("-".to_owned(), |amplification| format!("{:.3}", amplification))
+            );
+        }
+    }
+
+    impl Ssd {
+        /// A drive holding the logical blocks of `mapper`, queuing up to
+        /// `queue_depth` tasks.
+        pub fn new(config: SsdConfig, mapper: Box<dyn GeometryMapper>, queue_depth: u32) -> Ssd {
+            let die_count = config.get_die_count();
+            let pages = mapper
+                .get_capacity()
+                .div_ceil(config.sectors_per_page as u64) as u32;
+            let pages_per_die = pages.div_ceil(die_count) as f64;
+            let blocks_per_die = (pages_per_die * (1.0 + config.over_provisioning)
+                / config.pages_per_block as f64)
+                .ceil() as u32
+                + config.gc_threshold
+                + 1;
+
+            let mut ssd = Ssd {
+                config: config,
+                mapper: mapper,
+                blocks_per_die: blocks_per_die,
+                dies: (0..die_count)
+                    .map(|_| Die {
+                        valid: vec![0; blocks_per_die as usize],
+                        free: (1..blocks_per_die).collect(),
+                        active: 0,
+                        written: 0,
+                        busy_until: 0,
+                    })
+                    .collect(),
+                channels: vec![0; config.channels as usize],
+                map: vec![NO_PAGE; pages as usize],
+                owner: vec![
+                    NO_PAGE;
+                    (die_count * blocks_per_die * config.pages_per_block) as usize
+                ],
+                queue: CommandQueue::new(queue_depth),
+                state: DiskState::STOP,
+                current_track: 1,
+                done: 0,
+                time: 0,
+                statistics: DiskStatistics::default(),
+                flash: FlashStatistics::default(),
+            };
+            for page in 0..pages {
+                let physical = ssd.allocate(ssd.get_die(page));
+                ssd.bind(page, physical);
+            }
+
+            ssd
+        }
+
+        fn get_die(&self, page: u32) -> u32 {
+            page % self.config.get_die_count()
+        }
+
+        fn get_channel(&self, die: u32) -> usize {
+            (die % self.config.channels) as usize
+        }
+
+        fn get_block(&self, physical: u32) -> u32 {
+            physical / self.config.pages_per_block % self.blocks_per_die
+        }
+
+        /// The next free page of `die`, opening a new block once the active
+        /// one is full.
+        fn allocate(&mut self, die: u32) -> u32 {
+            let pages_per_block = self.config.pages_per_block;
+            let blocks_per_die = self.blocks_per_die;
+            let state = &mut self.dies[die as usize];
+            if state.written == pages_per_block {
+                state.active = state.free.remove(0);
+                state.written = 0;
+            }
+
+            let physical = (die * blocks_per_die + state.active) * pages_per_block + state.written;
+            state.written += 1;
+            physical
+        }
+
+        /// Points logical `page` at `physical`; the page it was at no longer
+        /// holds current data.
+        fn bind(&mut self, page: u32, physical: u32) {
+            self.unmap(page);
+            self.map[page as usize] = physical;
+            self.owner[physical as usize] = page;
+            let block = self.get_block(physical);
+            let die = self.get_die(page);
+            self.dies[die as usize].valid[block as usize] += 1;
+        }
+
+        fn unmap(&mut self, page: u32) {
+            let physical = self.map[page as usize];
+            if physical == NO_PAGE {
+                return;
+            }
+
+            self.owner[physical as usize] = NO_PAGE;
+            let block = self.get_block(physical);
+            let die = self.get_die(page);
+            self.dies[die as usize].valid[block as usize] -= 1;
+            self.map[page as usize] = NO_PAGE;
+        }
+
+        /// Erases the block of `die` with the fewest valid pages once they
+        /// are moved, and returns the ticks that keeps the die busy for.
+        /// Nothing is erased when every written block is full of valid pages.
+        fn collect_garbage(&mut self, die: u32) -> Option<u64> {
+            let pages_per_block = self.config.pages_per_block;
+            let state = &self.dies[die as usize];
+            let victim = (0..self.blocks_per_die)
+                .filter(|block| *block != state.active && !state.free.contains(block))
+                .min_by_key(|block| state.valid[*block as usize])?;
+            if state.valid[victim as usize] == pages_per_block {
+                return None;
+            }
+
+            let first = (die * self.blocks_per_die + victim) * pages_per_block;
+            let mut copies = 0;
+            for physical in first..first + pages_per_block {
+                let page = self.owner[physical as usize];
+                if page != NO_PAGE {
+                    let target = self.allocate(die);
+                    self.bind(page, target);
+                    copies += 1;
+                }
+            }
+            self.dies[die as usize].free.push(victim);
+            self.flash.gc_copies += copies;
+            self.flash.block_erases += 1;
+
+            Some(
+                copies * (self.config.read_latency + self.config.program_latency) as u64
+                    + self.config.erase_latency as u64,
+            )
+        }
+
+        /// Reads logical `page` from `start` on and returns when it has
+        /// crossed the channel. A page without data is not read from flash.
+        fn read_page(&mut self, page: u32, start: u64) -> u64 {
+            let die = self.get_die(page);
+            let channel = self.get_channel(die);
+            let mut ready = start;
+            if self.map[page as usize] != NO_PAGE {
+                ready =
+                    start.max(self.dies[die as usize].busy_until) + self.config.read_latency as u64;
+                self.flash.page_reads += 1;
+            }
+
+            let done = ready.max(self.channels[channel]) + self.config.transfer_time as u64;
+            self.channels[channel] = done;
+            if self.map[page as usize] != NO_PAGE {
+                self.dies[die as usize].busy_until = done;
+            }
+            done
+        }
+
+        /// Writes logical `page` from `start` on and returns when it is
+        /// programmed. Any garbage collection it triggers follows on the die.
+        fn write_page(&mut self, page: u32, start: u64) -> u64 {
+            let die = self.get_die(page);
+            let channel = self.get_channel(die);
+            let sent = start
+                .max(self.channels[channel])
+                .max(self.dies[die as usize].busy_until)
+                + self.config.transfer_time as u64;
+            self.channels[channel] = sent;
+            let programmed = sent + self.config.program_latency as u64;
+
+            let physical = self.allocate(die);
+            self.bind(page, physical);
+            self.flash.page_programs += 1;
+
+            let mut busy_until = programmed;
+            while self.dies[die as usize].free.len() < self.config.gc_threshold as usize {
+                match self.collect_garbage(die) {
+                    Some(ticks) => busy_until += ticks,
+                    None => break,
+                }
+            }
+            self.dies[die as usize].busy_until = busy_until;
+
+            programmed
+        }
+
+        /// The logical block `task` starts at: its own, or the one `mapper`
+        /// finds at its position, if the drive holds it.
+        fn get_lba(&self, task: &Task) -> Option<u64> {
+            task.get_lba()
+                .or_else(|| {
+                    self.mapper.to_lba_at(
+                        *task.get_track(),
+                        *task.get_head(),
+                        *task.get_angle(),
+                        task.get_sector(),
+                    )
+                })
+                .filter(|lba| *lba < self.mapper.get_capacity())
+        }
+
+        /// The logical pages `task` covers, and the block it starts at.
+        fn get_pages(&self, task: &Task) -> (std::ops::Range<u32>, u64) {
+            let lba = self
+                .get_lba(task)
+                .expect("Tasks are checked before they reach the SSD");
+            let sectors = self.config.sectors_per_page as u64;
+            let last = (lba + *task.get_length() as u64 - 1) / sectors;
+
+            (
+                (lba / sectors) as u32..(last as u32 + 1).min(self.map.len() as u32),
+                lba,
+            )
+        }
+
+        /// Forgets the pages `task` discards entirely.
+        fn discard(&mut self, task: &Task) {
+            let (pages, lba) = self.get_pages(task);
+            let sectors = self.config.sectors_per_page as u64;
+            for page in pages {
+                let first = page as u64 * sectors;
+                if first >= lba && first + sectors <= lba + *task.get_length() as u64 {
+                    self.unmap(page);
+                }
+            }
+        }
+
+        fn tick(&mut self) {
+            self.time += 1;
+        }
+    }
+
+    impl Device for Ssd {
+        fn show(&self) {
+            println!(
+                "SSD <\n\tchannels: {}, dies per channel: {}, blocks per die: {}, pages per block: {}, sectors per page: {}\n\tread: {}, program: {}, erase: {}, transfer: {} ticks\n>",
+                self.config.channels,
+                self.config.dies,
+                self.blocks_per_die,
+                self.config.pages_per_block,
+                self.config.sectors_per_page,
+                self.config.read_latency,
+                self.config.program_latency,
+                self.config.erase_latency,
+                self.config.transfer_time
+            );
+        }
+
+        /// Tasks reaching the flash need a logical block the drive holds.
+        fn check_task(&self, task: &Task) -> Result<(), String> {
+            if task.get_operation() == Operation::FLUSH || self.get_lba(task).is_some() {
+                return Ok(());
+            }
+
+            Err(format!(
+                "Request {} at track {}, head {}, angle {} has no logical block on the SSD",
+                task.get_id(),
+                task.get_track(),
+                task.get_head(),
+                task.get_angle()
+            ))
+        }
+
+        fn accepts(&self, operation: Operation) -> bool {
+            self.queue.accepts(operation)
+        }
+
+        fn queue_task(&mut self, task: &Task) {
+            self.queue.push(task);
+        }
+
+        /// The queued task whose dies are free soonest.
+        fn take_queued(&mut self) -> Option<u32> {
+            let index = self
+                .queue
+                .get_tasks()
+                .iter()
+                .enumerate()
+                .min_by_key(|(index, task)| {
+                    let wait = if task.get_operation().is_media_access() {
+                        self.get_pages(task)
+                            .0
+                            .map(|page| self.dies[self.get_die(page) as usize].busy_until)
+                            .max()
+                            .unwrap_or(0)
+                            .saturating_sub(self.time)
+                    } else {
+                        0
+                    };
+                    (wait, *index)
+                })?
+                .0;
+            if index > 0 {
+                self.statistics.add_reordered_task();
+            }
+
+            Some(*self.queue.take(index).get_id())
+        }
+
+        fn finish_queued(&mut self, task: &Task) {
+            self.queue.finish(task);
+        }
+
+        fn get_queue_depth(&self) -> u32 {
+            self.queue.get_depth()
+        }
+
+        /// Works out when the pages of `task` are done, all of its dies and
+        /// channels working at once.
+        fn start_task(&mut self, task: &Task) {
+            self.current_track = *task.get_track();
+            let start = self.time;
+            self.done = start;
+            match task.get_operation() {
+                Operation::READ => {
+                    for page in self.get_pages(task).0 {
+                        self.done = self.done.max(self.read_page(page, start));
+                    }
+                    self.state = DiskState::READ(*task.get_angle());
+                }
+                Operation::WRITE => {
+                    for page in self.get_pages(task).0 {
+                        self.done = self.done.max(self.write_page(page, start));
+                    }
+                    self.state = DiskState::WRITE(*task.get_angle());
+                }
+                Operation::DISCARD => self.discard(task),
+                Operation::FLUSH => {}
+            }
+        }
+
+        fn step_task(&mut self, task: &Task) -> bool {
+            if !task.get_operation().is_media_access() {
+                self.idle();
+                return true;
+            }
+
+            self.statistics.add_transfer_tick();
+            self.tick();
+            if self.time < self.done {
+                return false;
+            }
+
+            self.state = DiskState::STOP;
+            true
+        }
+
+        fn has_background_work(&self) -> bool {
+            false
+        }
+
+        fn step_background(&mut self) -> bool {
+            self.idle();
+            true
+        }
+
+        fn idle(&mut self) {
+            self.statistics.add_idle_tick();
+            self.tick();
+        }
+
+        fn get_state(&self) -> &DiskState {
+            &self.state
+        }
+
+        fn get_current_track(&self) -> u32 {
+            self.current_track
+        }
+
+        fn get_statistics(&self) -> &DiskStatistics {
+            &self.statistics
+        }
+
+        fn get_flash_statistics(&self) -> Option<&FlashStatistics> {
+            Some(&self.flash)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::geometry::geometry::Geometry;
+
+        /// One die of blocks of 4 pages of 36 sectors, holding the 10 pages
+        /// of a single track: blocks 0 and 1 are full, block 2 has pages 8
+        /// and 9, and blocks 3 to 5 are free.
+        fn small_ssd() -> Ssd {
+            let config = SsdConfig {
+                channels: 1,
+                dies: 1,
+                pages_per_block: 4,
+                sectors_per_page: 36,
+                ..SsdConfig::default()
+            };
+
+            Ssd::new(config, Geometry::default().get_mapper(1), 1)
+        }
+
+        fn write(ssd: &mut Ssd, pages: &[u32]) {
+            for page in pages {
+                ssd.write_page(*page, 0);
+            }
+        }
+
+        fn discard_task(lba: u64, length: u32, ssd: &Ssd) -> Task {
+            let mut task = Task::from_lba(1, lba, length, ssd.mapper.as_ref()).unwrap();
+            task.set_operation(Operation::DISCARD);
+            task
+        }
+
+        #[test]
+        fn starts_with_every_page_written_in_order() {
+            let ssd = small_ssd();
+
+            assert_eq!(ssd.blocks_per_die, 6);
+            assert_eq!(ssd.map, (0..10).collect::<Vec<u32>>());
+            assert_eq!(ssd.dies[0].valid, vec![4, 4, 2, 0, 0, 0]);
+            assert_eq!(ssd.dies[0].free, vec![3, 4, 5]);
+        }
+
+        #[test]
+        fn overwrite_invalidates_the_old_page() {
+            let mut ssd = small_ssd();
+            write(&mut ssd, &[0]);
+
+            assert_eq!(ssd.map[0], 10);
+            assert_eq!(ssd.owner[0], NO_PAGE);
+            assert_eq!(ssd.owner[10], 0);
+            assert_eq!(ssd.dies[0].valid, vec![3, 4, 3, 0, 0, 0]);
+            assert_eq!(ssd.flash.get_page_programs(), 1);
+        }
+
+        #[test]
+        fn collects_garbage_once_free_blocks_fall_below_the_threshold() {
+            let mut ssd = small_ssd();
+
+            // Fills block 2 and opens block 3, leaving the threshold of 2.
+            write(&mut ssd, &[0, 1, 2, 3, 4, 5]);
+            assert_eq!(ssd.dies[0].free, vec![4, 5]);
+            assert_eq!(ssd.flash.get_block_erases(), 0);
+
+            // Opening block 4 leaves one free block, so block 0, whose
+            // pages were all rewritten, is erased.
+            write(&mut ssd, &[6]);
+            assert_eq!(ssd.flash.get_block_erases(), 1);
+            assert_eq!(ssd.flash.get_gc_copies(), 0);
+            assert_eq!(ssd.dies[0].free, vec![5, 0]);
+        }
+
+        #[test]
+        fn garbage_collection_picks_the_block_with_fewest_valid_pages() {
+            let mut ssd = small_ssd();
+
+            // Leaves blocks 0 and 2 with two valid pages and block 1 with
+            // page 7 alone, then opens block 4.
+            write(&mut ssd, &[4, 5, 6, 0, 1, 8, 9]);
+
+            assert_eq!(ssd.flash.get_block_erases(), 1);
+            assert_eq!(ssd.flash.get_gc_copies(), 1);
+            assert!(ssd.dies[0].free.contains(&1));
+            assert!(!ssd.dies[0].free.contains(&0));
+            assert_eq!(ssd.get_block(ssd.map[7]), 4);
+            assert_eq!(ssd.owner[ssd.map[7] as usize], 7);
+            assert_eq!(ssd.dies[0].valid, vec![2, 0, 2, 4, 2, 0]);
+        }
+
+        #[test]
+        fn write_amplification_counts_pages_moved_by_garbage_collection() {
+            let mut ssd = small_ssd();
+            assert_eq!(ssd.flash.get_write_amplification(), None);
+
+            write(&mut ssd, &[0]);
+            assert_eq!(ssd.flash.get_write_amplification(), Some(1.0));
+
+            // Block 0 has the 3 valid pages left after the overwrite.
+            let ticks = ssd.collect_garbage(0);
+            assert_eq!(ticks, Some(3 * (50 + 600) + 3000));
+            assert_eq!(ssd.flash.get_gc_copies(), 3);
+            assert_eq!(ssd.flash.get_write_amplification(), Some(4.0));
+        }
+
+        #[test]
+        fn garbage_collection_skips_blocks_full_of_valid_pages() {
+            let mut ssd = small_ssd();
+
+            assert_eq!(ssd.collect_garbage(0), None);
+            assert_eq!(ssd.flash.get_block_erases(), 0);
+        }
+
+        #[test]
+        fn discard_forgets_only_whole_pages() {
+            let mut ssd = small_ssd();
+
+            let partial = discard_task(40, 60, &ssd);
+            ssd.discard(&partial);
+            assert!(ssd.map.iter().all(|physical| *physical != NO_PAGE));
+
+            let whole = discard_task(30, 80, &ssd);
+            ssd.discard(&whole);
+            assert_eq!(ssd.map[1], NO_PAGE);
+            assert_eq!(ssd.map[2], NO_PAGE);
+            assert_ne!(ssd.map[0], NO_PAGE);
+            assert_ne!(ssd.map[3], NO_PAGE);
+            assert_eq!(ssd.dies[0].valid, vec![2, 4, 2, 0, 0, 0]);
+        }
+
+        #[test]
+        fn tasks_without_a_logical_block_are_rejected() {
+            let ssd = small_ssd();
+
+            assert!(ssd.check_task(&Task::new(1, 1, 90)).is_ok());
+            assert!(ssd.check_task(&Task::new(2, 2, 90)).is_err());
+            assert!(ssd.check_task(&Task::new(3, 0, 90)).is_err());
+
+            let mut flush = Task::new(4, 0, 0);
+            flush.set_operation(Operation::FLUSH);
+            assert!(ssd.check_task(&flush).is_ok());
+        }
+    }
+}
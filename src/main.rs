@@ -1,8 +1,25 @@
+use std::process::exit;
+
+use cli::cli::cli::run_cli;
 use menues::menues::menues::main_menu;
 
+// Every file wraps its items in a module named after it, builds structs with
+// `field: field` and gives types inherent `default` constructors. The modules
+// that do so allow the matching clippy lints where they are declared.
+pub mod analysis;
+pub mod cli;
 pub mod disk;
 pub mod menues;
+pub mod simulation;
 
 fn main() {
-    main_menu();
+    if std::env::args().len() <= 1 {
+        main_menu();
+        return;
+    }
+
+    if let Err(e) = run_cli() {
+        eprintln!("error: {}", e);
+        exit(1);
+    }
 }
@@ -1,17 +1,21 @@
 pub mod menues {
     use std::{
-        collections::HashMap,
-        fs::File,
         io::{stdin, stdout, Read, Write},
         num::ParseIntError,
-        time::SystemTime,
     };
 
-    use rand::Rng;
-
-    use crate::disk::{
-        disk::disk::{Disk, DiskMetadata},
-        driver::driver::{Driver, ElevetorDriver, SimpleDriver, Task},
+    use crate::analysis::{
+        comparison::comparison::{compare_algorithms, show_comparison},
+        fairness::fairness::FairnessReport,
+        stats::stats::ResponseStatistics,
+    };
+    use crate::disk::disk::disk::{Disk, DiskMetadata};
+    use crate::simulation::{
+        logger::logger::{log_data_to_file, open_log_file, LogHeader},
+        scenario::scenario::DiskConfig,
+        simulation::simulation::{run_simulation, Algorithms, SchedulerConfig, SimulationResult},
+        sweep::sweep::SweepSpec,
+        workload::workload::WorkloadConfig,
     };
 
     #[derive(Eq, PartialEq)]
@@ -21,22 +25,11 @@ pub mod menues {
         LOG,
         INFO,
         EXIT,
+        COMPARE,
 
         INVALID,
     }
 
-    #[derive(Copy, Clone)]
-    enum Algorithms {
-        NAIVE,
-        ELEVATOR,
-    }
-
-    struct LogHeader {
-        metadata: DiskMetadata,
-        max_tracks: u32,
-        steps: u32,
-    }
-
     fn clear() {
         clearscreen::clear().expect("There was an error while clearing the screen");
     }
@@ -57,7 +50,7 @@ pub mod menues {
         }
 
         println!(
-            "1- Simulate Naive Approach\n2- Simulate Elevator Algorithm\n3- Log\n4- Info\n5- Exit"
+            "1- Simulate Naive Approach\n2- Simulate Elevator Algorithm\n3- Log\n4- Info\n5- Exit\n6- Compare Both Algorithms"
         );
         print!(">> ");
 
@@ -97,6 +90,7 @@ pub mod menues {
             Ok(3) => MainMenuOptions::LOG,
             Ok(4) => MainMenuOptions::INFO,
             Ok(5) => MainMenuOptions::EXIT,
+            Ok(6) => MainMenuOptions::COMPARE,
             _ => MainMenuOptions::INVALID,
         }
     }
@@ -116,77 +110,22 @@ pub mod menues {
         pause();
     }
 
-    fn generate_random_request(task_id: u32, max_track: u32) -> Task {
-        let track = rand::thread_rng().gen_range(1..=max_track) as u32;
-        let angle = rand::thread_rng().gen_range(0..=359) as u32;
-
-        Task::new(task_id, track, angle)
-    }
-
-    fn run_simulation(
+    fn simulate(
         algorithm: Algorithms,
         metadata: DiskMetadata,
         max_track: u32,
         requests: u32,
-    ) -> Vec<u32> {
-        println!("Here is the disk:");
-        let disk = build_disk(metadata);
-        disk.show();
-
-        let mut tasks: Vec<Task> = Vec::new();
-        let mut insertion_times: HashMap<u32, u32> = HashMap::new();
-        let mut response_times: Vec<u32> = Vec::new();
-
-        let mut driver: Box<dyn Driver> = match algorithm {
-            Algorithms::NAIVE => Box::new(SimpleDriver::new(disk)),
-            Algorithms::ELEVATOR => Box::new(ElevetorDriver::new(disk)),
-        };
-
-        let mut remaining_tasks = 0;
-
-        let mut added_tasks = 0;
-        let threshould = requests as f32 / 10000000.0;
-        let mut time = 0;
+    ) -> Result<SimulationResult, String> {
+        let workload = WorkloadConfig::new(requests, None);
+        let arrivals = workload.generate(max_track, 1, workload.resolve_seed())?;
 
-        for i in 1..=requests {
-            let task = generate_random_request(i, max_track);
-            tasks.push(task);
-        }
-
-        while added_tasks != requests || remaining_tasks != 0 {
-            let prob: f32 = rand::thread_rng().gen();
-
-            if prob < threshould && added_tasks != requests {
-                let task = &tasks[added_tasks as usize];
-
-                driver.add_new_task(task);
-                insertion_times.insert(*task.get_id(), time);
-
-                added_tasks += 1;
-                remaining_tasks += 1;
-            }
-
-            time += 1;
-            let result = driver.step();
-
-            if result != 0 {
-                remaining_tasks -= 1;
-                let response_length = time - insertion_times[&result];
-                insertion_times.remove(&result);
-
-                response_times.push(response_length);
-
-                if (added_tasks - remaining_tasks) % 10 == 0 {
-                    println!(
-                        "{}/{} more responses are done.",
-                        added_tasks - remaining_tasks,
-                        requests
-                    );
-                }
-            }
-        }
-
-        response_times
+        run_simulation(
+            SchedulerConfig::new(algorithm),
+            Box::new(Disk::new(metadata)),
+            &arrivals,
+            true,
+            None,
+        )
     }
 
     fn read_hard_metadata() -> (DiskMetadata, u32) {
@@ -213,62 +152,6 @@ pub mod menues {
         }
     }
 
-    fn build_disk(metadata: DiskMetadata) -> Disk {
-        let disk = Disk::new(metadata);
-        disk
-    }
-
-    fn open_log_file() -> File {
-        let log_file_path = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_millis()
-            .to_string();
-        let log_file_path = format!("{}.txt", log_file_path);
-
-        let data_file =
-            File::create(log_file_path).expect("There was a problem creating the log file");
-
-        data_file
-    }
-
-    fn log_data_to_file(
-        data_file: &mut File,
-        algortihm: Algorithms,
-        log_header: LogHeader,
-        times: Vec<u32>,
-    ) {
-        let algorithm = match algortihm {
-            Algorithms::NAIVE => "Naive",
-            Algorithms::ELEVATOR => "Elevator",
-        };
-
-        let header = format!(
-            "algorithm: {}, forward_speed: {}, spin_speed: {}, max_track: {}, steps: {}\n",
-            algorithm,
-            log_header.metadata.get_forward_speed(),
-            log_header.metadata.get_spin_speed(),
-            log_header.max_tracks,
-            log_header.steps,
-        );
-
-        data_file
-            .write(header.as_bytes())
-            .expect("There was an error while write header to the log file");
-
-        for time in times.iter() {
-            data_file
-                .write(time.to_string().as_bytes())
-                .expect("There was an error while write data to the log file");
-            data_file
-                .write(",".as_bytes())
-                .expect("There was an error while write data to the log file");
-        }
-        data_file
-            .write("\n".as_bytes())
-            .expect("There was an error while write data to the log file");
-    }
-
     fn simulation_menu(algorithm: Algorithms) {
         clear();
         let (metadata, max_track) = read_hard_metadata();
@@ -276,59 +159,53 @@ pub mod menues {
 
         let steps = safe_read_int_value();
         let log_header = LogHeader {
-            metadata: metadata.clone(),
+            metadata: metadata,
             max_tracks: max_track,
             steps: steps,
         };
-        let response_times = run_simulation(algorithm, metadata, max_track, steps);
+        let result = match simulate(algorithm, metadata, max_track, steps) {
+            Ok(result) => result,
+            Err(e) => {
+                println!("{}", e);
+                pause();
+                return;
+            }
+        };
 
         let mut log_file = open_log_file();
-        // show_stats(response_times);
-        log_data_to_file(&mut log_file, algorithm, log_header, response_times);
+        ResponseStatistics::from_times(&result.response_times, Some(result.total_time)).show();
+        result.disk.show();
+        FairnessReport::from_tasks(&result.tasks).show();
+        log_data_to_file(&mut log_file, algorithm, log_header, &result.response_times);
         pause();
     }
 
-    fn generate_experience(
-        log_file: &mut File,
-        forward_speed: u32,
-        spin_speed: u32,
-        steps: u32,
-        max_tracks: u32,
-        algorithm: Algorithms,
-    ) {
-        let metadata = DiskMetadata::from_config(forward_speed, spin_speed);
-        let log_header = LogHeader {
-            metadata: metadata.clone(),
-            max_tracks: max_tracks,
-            steps: steps,
-        };
+    fn compare_menu() {
+        clear();
+        let (metadata, max_track) = read_hard_metadata();
+        println!("Enter the number of requests you want to simulate:");
 
-        let response_times = run_simulation(algorithm, metadata, max_tracks, steps);
-        log_data_to_file(log_file, algorithm, log_header, response_times);
+        let steps = safe_read_int_value();
+        let workload = WorkloadConfig::new(steps, None);
+        let seed = workload.resolve_seed();
+        println!("Workload seed: {}", seed);
+        match workload.generate(max_track, 1, seed).and_then(|arrivals| {
+            compare_algorithms(
+                SchedulerConfig::new(Algorithms::NAIVE),
+                &DiskConfig::new(metadata, max_track),
+                &arrivals,
+                &[Algorithms::NAIVE, Algorithms::ELEVATOR],
+            )
+        }) {
+            Ok(results) => show_comparison(&results, Algorithms::NAIVE),
+            Err(e) => println!("{}", e),
+        }
+        pause();
     }
 
     fn log_all_configs() {
-        let mut log_file = open_log_file();
-
-        for algorithm in [Algorithms::NAIVE, Algorithms::ELEVATOR] {
-            for forward_speed in [1, 5, 10, 15, 20, 25] {
-                for spin_speed in [25, 50, 100, 250, 500] {
-                    for max_tracks in [1000, 5000, 10000, 50000] {
-                        for steps in [100, 500, 1000, 5000] {
-                            for _ in 0..10 {
-                                generate_experience(
-                                    &mut log_file,
-                                    forward_speed,
-                                    spin_speed,
-                                    steps,
-                                    max_tracks,
-                                    algorithm,
-                                );
-                            }
-                        }
-                    }
-                }
-            }
+        if let Err(e) = SweepSpec::legacy().run(true, true) {
+            println!("{}", e);
         }
     }
 
@@ -356,6 +233,10 @@ pub mod menues {
                     print_info();
                     details = true;
                 }
+                MainMenuOptions::COMPARE => {
+                    compare_menu();
+                    details = true;
+                }
                 MainMenuOptions::INVALID => {
                     print_error_message();
                     details = false;
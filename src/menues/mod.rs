@@ -1 +1,6 @@
-pub mod menues;
\ No newline at end of file
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::upper_case_acronyms
+)]
+pub mod menues;
@@ -0,0 +1,224 @@
+pub mod checkpoint {
+    use std::{
+        collections::HashSet,
+        fs::{self, File, OpenOptions},
+        io::Write,
+    };
+
+    /// Records which runs of a sweep already made it into the output file, so an
+    /// interrupted sweep can continue instead of starting over.
+    ///
+    /// The manifest lives next to the output as `<output>.manifest`. Its first
+    /// lines identify the sweep, then every finished run appends the length of
+    /// each output file after its rows followed by its key.
+    pub struct Manifest {
+        file: File,
+        fingerprint: u64,
+        seed: u64,
+        offsets: Vec<u64>,
+        completed: HashSet<String>,
+    }
+
+    pub fn fingerprint(text: &str) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in text.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+
+        hash
+    }
+
+    impl Manifest {
+        pub fn path_for(output: &str) -> String {
+            format!("{}.manifest", output)
+        }
+
+        pub fn create(output: &str, fingerprint: u64, seed: u64) -> Result<Manifest, String> {
+            let path = Manifest::path_for(output);
+            let mut file = File::create(&path)
+                .map_err(|e| format!("Could not create manifest {}: {}", path, e))?;
+
+            file.write_all(format!("fingerprint {}\nseed {}\n", fingerprint, seed).as_bytes())
+                .and_then(|_| file.sync_data())
+                .map_err(|e| format!("Could not write manifest {}: {}", path, e))?;
+
+            Ok(Manifest {
+                file: file,
+                fingerprint: fingerprint,
+                seed: seed,
+                offsets: Vec::new(),
+                completed: HashSet::new(),
+            })
+        }
+
+        /// Loads the manifest of `output` if there is one.
+        pub fn open(output: &str) -> Result<Option<Manifest>, String> {
+            let path = Manifest::path_for(output);
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(format!("Could not read manifest {}: {}", path, e)),
+            };
+            let invalid = || format!("{} is not a sweep manifest", path);
+
+            let mut lines = content.split_inclusive('\n');
+            let mut header_value = |name: &str| -> Result<u64, String> {
+                lines
+                    .next()
+                    .and_then(|line| line.trim().strip_prefix(name))
+                    .and_then(|value| value.trim().parse::<u64>().ok())
+                    .ok_or_else(invalid)
+            };
+            let fingerprint = header_value("fingerprint")?;
+            let seed = header_value("seed")?;
+
+            let mut offsets = Vec::new();
+            let mut completed = HashSet::new();
+            for line in lines {
+                // A line without its newline was cut off by the interruption.
+                if !line.ends_with('\n') {
+                    break;
+                }
+
+                let (end, key) = line.trim_end().split_once('\t').ok_or_else(invalid)?;
+                offsets = end
+                    .split(',')
+                    .map(|offset| offset.parse::<u64>())
+                    .collect::<Result<Vec<u64>, _>>()
+                    .map_err(|_| invalid())?;
+                completed.insert(key.to_owned());
+            }
+
+            // Drop the cut off line, or the next run would be appended to it.
+            let length = content.rfind('\n').map_or(0, |end| end + 1) as u64;
+            let file = OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .and_then(|file| file.set_len(length).map(|_| file))
+                .map_err(|e| format!("Could not open manifest {}: {}", path, e))?;
+
+            Ok(Some(Manifest {
+                file: file,
+                fingerprint: fingerprint,
+                seed: seed,
+                offsets: offsets,
+                completed: completed,
+            }))
+        }
+
+        pub fn get_fingerprint(&self) -> u64 {
+            self.fingerprint
+        }
+
+        pub fn get_seed(&self) -> u64 {
+            self.seed
+        }
+
+        /// Lengths of the output files up to the last completed run.
+        pub fn get_offsets(&self) -> &[u64] {
+            &self.offsets
+        }
+
+        pub fn count_completed(&self) -> usize {
+            self.completed.len()
+        }
+
+        pub fn is_completed(&self, key: &str) -> bool {
+            self.completed.contains(key)
+        }
+
+        pub fn record(&mut self, key: &str, offsets: Vec<u64>) -> Result<(), String> {
+            let end: Vec<String> = offsets.iter().map(|offset| offset.to_string()).collect();
+            self.file
+                .write_all(format!("{}\t{}\n", end.join(","), key).as_bytes())
+                .and_then(|_| self.file.sync_data())
+                .map_err(|e| format!("Could not update the manifest: {}", e))?;
+
+            self.offsets = offsets;
+            self.completed.insert(key.to_owned());
+
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// The output path of a sweep writing into `dir`.
+        fn output(dir: &tempfile::TempDir) -> String {
+            dir.path()
+                .join("sweep.jsonl")
+                .to_string_lossy()
+                .into_owned()
+        }
+
+        #[test]
+        fn fingerprints_are_fnv_1a() {
+            assert_eq!(fingerprint(""), 0xcbf29ce484222325);
+            assert_eq!(fingerprint("a"), 0xaf63dc4c8601ec8c);
+            assert_ne!(fingerprint("ab"), fingerprint("ba"));
+        }
+
+        #[test]
+        fn a_missing_manifest_is_not_an_error() {
+            let dir = tempfile::tempdir().unwrap();
+            assert!(Manifest::open(&output(&dir)).unwrap().is_none());
+        }
+
+        #[test]
+        fn resumes_from_the_last_recorded_run() {
+            let dir = tempfile::tempdir().unwrap();
+            let output = output(&dir);
+            let mut manifest = Manifest::create(&output, 42, 7).unwrap();
+            manifest.record("a=1,repetition=0", vec![10, 3]).unwrap();
+            manifest.record("a=2,repetition=0", vec![25, 6]).unwrap();
+            drop(manifest);
+
+            let manifest = Manifest::open(&output).unwrap().unwrap();
+            assert_eq!(manifest.get_fingerprint(), 42);
+            assert_eq!(manifest.get_seed(), 7);
+            assert_eq!(manifest.get_offsets(), &[25, 6]);
+            assert_eq!(manifest.count_completed(), 2);
+            assert!(manifest.is_completed("a=1,repetition=0"));
+            assert!(!manifest.is_completed("a=3,repetition=0"));
+        }
+
+        #[test]
+        fn drops_a_cut_off_line_before_appending() {
+            let dir = tempfile::tempdir().unwrap();
+            let output = output(&dir);
+            let path = Manifest::path_for(&output);
+            fs::write(&path, "fingerprint 1\nseed 2\n10\ta=1\n25\ta=").unwrap();
+
+            let mut manifest = Manifest::open(&output).unwrap().unwrap();
+            assert_eq!(manifest.get_offsets(), &[10]);
+            assert_eq!(manifest.count_completed(), 1);
+
+            manifest.record("a=2", vec![30]).unwrap();
+            drop(manifest);
+            assert_eq!(
+                fs::read_to_string(&path).unwrap(),
+                "fingerprint 1\nseed 2\n10\ta=1\n30\ta=2\n"
+            );
+
+            let manifest = Manifest::open(&output).unwrap().unwrap();
+            assert_eq!(manifest.get_offsets(), &[30]);
+            assert!(manifest.is_completed("a=2"));
+        }
+
+        #[test]
+        fn rejects_files_that_are_not_manifests() {
+            let dir = tempfile::tempdir().unwrap();
+            let output = output(&dir);
+            fs::write(
+                Manifest::path_for(&output),
+                "fingerprint 1\nseed 2\nnot a run\n",
+            )
+            .unwrap();
+
+            assert!(Manifest::open(&output).is_err());
+        }
+    }
+}
@@ -0,0 +1,287 @@
+pub mod logger {
+    use std::{
+        collections::HashMap,
+        fs::{self, File},
+        io::Write,
+        time::SystemTime,
+    };
+
+    use crate::disk::disk::disk::DiskMetadata;
+    use crate::simulation::{
+        scenario::scenario::{DiskConfig, Scenario},
+        simulation::simulation::Algorithms,
+    };
+
+    pub struct LogHeader {
+        pub metadata: DiskMetadata,
+        pub max_tracks: u32,
+        pub steps: u32,
+    }
+
+    pub fn default_log_file_path() -> String {
+        default_output_path("txt")
+    }
+
+    /// A timestamped file name in the working directory, like the menu has always used.
+    pub fn default_output_path(extension: &str) -> String {
+        let log_file_path = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .to_string();
+
+        format!("{}.{}", log_file_path, extension)
+    }
+
+    pub fn open_log_file() -> File {
+        open_log_file_at(&default_log_file_path())
+    }
+
+    pub fn open_log_file_at(log_file_path: &str) -> File {
+        File::create(log_file_path).expect("There was a problem creating the log file")
+    }
+
+    pub fn log_data_to_file(
+        data_file: &mut File,
+        algortihm: Algorithms,
+        log_header: LogHeader,
+        times: &[u32],
+    ) {
+        let header = format!(
+            "algorithm: {}, forward_speed: {}, spin_speed: {}, max_track: {}, steps: {}\n",
+            algortihm.get_name(),
+            log_header.metadata.get_forward_speed(),
+            log_header.metadata.get_spin_speed(),
+            log_header.max_tracks,
+            log_header.steps,
+        );
+
+        data_file
+            .write_all(header.as_bytes())
+            .expect("There was an error while write header to the log file");
+
+        for time in times.iter() {
+            data_file
+                .write_all(time.to_string().as_bytes())
+                .expect("There was an error while write data to the log file");
+            data_file
+                .write_all(",".as_bytes())
+                .expect("There was an error while write data to the log file");
+        }
+        data_file
+            .write_all("\n".as_bytes())
+            .expect("There was an error while write data to the log file");
+    }
+
+    /// One header line of a log and the response times written under it.
+    pub struct LogRecord {
+        pub algorithm: Algorithms,
+        pub header: LogHeader,
+        pub response_times: Vec<u32>,
+    }
+
+    pub struct LogFile {
+        pub records: Vec<LogRecord>,
+        /// The file ended in the middle of a record, which was left out.
+        pub truncated: bool,
+    }
+
+    impl LogRecord {
+        /// The scenario the record was produced with, as far as the log tells.
+        pub fn to_scenario(&self) -> Scenario {
+            let mut scenario = Scenario::default();
+            scenario.disk = DiskConfig::new(self.header.metadata, self.header.max_tracks);
+            scenario.workload.requests = self.header.steps;
+            scenario.scheduler.algorithm = self.algorithm;
+
+            scenario
+        }
+
+        /// Identifies the configuration, shared by the repetitions of a log sweep.
+        pub fn get_configuration(&self) -> String {
+            format!(
+                "algorithm={},forward_speed={},spin_speed={},max_track={},steps={}",
+                self.algorithm.get_name(),
+                self.header.metadata.get_forward_speed(),
+                self.header.metadata.get_spin_speed(),
+                self.header.max_tracks,
+                self.header.steps
+            )
+        }
+    }
+
+    /// Parses `algorithm: Naive, forward_speed: 1, spin_speed: 100, max_track: 10000, steps: 50`.
+    pub fn parse_log_header(line: &str) -> Result<(Algorithms, LogHeader), String> {
+        let mut fields: HashMap<&str, &str> = HashMap::new();
+        for field in line.split(',') {
+            let (name, value) = field
+                .split_once(':')
+                .ok_or_else(|| format!("`{}` is not a `name: value` pair", field.trim()))?;
+            fields.insert(name.trim(), value.trim());
+        }
+
+        let number = |name: &str| -> Result<u32, String> {
+            let value = fields
+                .get(name)
+                .ok_or_else(|| format!("the header has no `{}`", name))?;
+            value
+                .parse::<u32>()
+                .map_err(|e| format!("invalid `{}` value `{}`: {}", name, value, e))
+        };
+
+        let algorithm = Algorithms::from_name(
+            fields
+                .get("algorithm")
+                .ok_or_else(|| "the header has no `algorithm`".to_owned())?,
+        )?;
+        let header = LogHeader {
+            metadata: DiskMetadata::from_config(number("forward_speed")?, number("spin_speed")?),
+            max_tracks: number("max_track")?,
+            steps: number("steps")?,
+        };
+
+        Ok((algorithm, header))
+    }
+
+    /// Reads a log written by `log_data_to_file`.
+    pub fn read_log_file(path: &str) -> Result<LogFile, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read log file {}: {}", path, e))?;
+
+        let mut records = Vec::new();
+        let mut lines = content.split_inclusive('\n').enumerate();
+        while let Some((line_number, header)) = lines.next() {
+            if header.trim().is_empty() {
+                continue;
+            }
+
+            let data = match lines.next() {
+                Some((_, data)) if data.ends_with('\n') => data,
+                _ => {
+                    return Ok(LogFile {
+                        records: records,
+                        truncated: true,
+                    })
+                }
+            };
+
+            let (algorithm, header) = parse_log_header(header.trim())
+                .map_err(|e| format!("{}:{}: {}", path, line_number + 1, e))?;
+            let response_times: Result<Vec<u32>, _> = data
+                .split(',')
+                .map(|t| t.trim())
+                .filter(|t| !t.is_empty())
+                .map(|t| t.parse::<u32>())
+                .collect();
+            let response_times =
+                response_times.map_err(|e| format!("{}:{}: {}", path, line_number + 2, e))?;
+
+            records.push(LogRecord {
+                algorithm: algorithm,
+                header: header,
+                response_times: response_times,
+            });
+        }
+
+        Ok(LogFile {
+            records: records,
+            truncated: false,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn header(forward_speed: u32, max_tracks: u32) -> LogHeader {
+            LogHeader {
+                metadata: DiskMetadata::from_config(forward_speed, 100),
+                max_tracks: max_tracks,
+                steps: 3,
+            }
+        }
+
+        const HEADER: &str =
+            "algorithm: Naive, forward_speed: 1, spin_speed: 100, max_track: 10, steps: 2";
+
+        /// Writes `content` to a log file and reads it back.
+        fn read(content: &str) -> Result<LogFile, String> {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("log.txt");
+            fs::write(&path, content).unwrap();
+
+            read_log_file(path.to_str().unwrap())
+        }
+
+        #[test]
+        fn logs_read_back_what_was_written() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("log.txt");
+            let path = path.to_str().unwrap();
+            let mut log_file = open_log_file_at(path);
+            log_data_to_file(
+                &mut log_file,
+                Algorithms::NAIVE,
+                header(1, 500),
+                &[7, 0, 12],
+            );
+            log_data_to_file(&mut log_file, Algorithms::ELEVATOR, header(5, 1000), &[3]);
+            drop(log_file);
+
+            let log = read_log_file(path).unwrap();
+            assert!(!log.truncated);
+            assert_eq!(log.records.len(), 2);
+            assert!(log.records[0].algorithm == Algorithms::NAIVE);
+            assert_eq!(log.records[0].response_times, vec![7, 0, 12]);
+            assert_eq!(log.records[0].header.max_tracks, 500);
+            assert!(log.records[1].algorithm == Algorithms::ELEVATOR);
+            assert_eq!(*log.records[1].header.metadata.get_forward_speed(), 5);
+            assert_eq!(*log.records[1].header.metadata.get_spin_speed(), 100);
+            assert_eq!(log.records[1].header.steps, 3);
+            assert_eq!(log.records[1].response_times, vec![3]);
+            assert_eq!(
+                log.records[1].get_configuration(),
+                "algorithm=Elevator,forward_speed=5,spin_speed=100,max_track=1000,steps=3"
+            );
+        }
+
+        #[test]
+        fn records_without_response_times_are_kept() {
+            let log = read(&format!("{}\n\n", HEADER)).unwrap();
+
+            assert!(!log.truncated);
+            assert_eq!(log.records.len(), 1);
+            assert!(log.records[0].response_times.is_empty());
+        }
+
+        #[test]
+        fn a_cut_off_record_is_left_out() {
+            let complete = format!("{}\n4,5,\n", HEADER);
+
+            let log = read(&format!("{}{}\n6,", complete, HEADER)).unwrap();
+            assert!(log.truncated);
+            assert_eq!(log.records.len(), 1);
+            assert_eq!(log.records[0].response_times, vec![4, 5]);
+
+            let log = read(&format!("{}algorithm: Nai", complete)).unwrap();
+            assert!(log.truncated);
+            assert_eq!(log.records.len(), 1);
+        }
+
+        #[test]
+        fn malformed_headers_are_errors() {
+            for header in [
+                "algorithm Naive, forward_speed: 1, spin_speed: 100, max_track: 10, steps: 2",
+                "algorithm: Naive, forward_speed: 1, spin_speed: 100, max_track: 10",
+                "algorithm: Naive, forward_speed: x, spin_speed: 100, max_track: 10, steps: 2",
+                "algorithm: LIFO, forward_speed: 1, spin_speed: 100, max_track: 10, steps: 2",
+            ] {
+                let error = read(&format!("\n{}\n4,5,\n", header)).err().unwrap();
+                assert!(error.contains(":2: "), "{}", error);
+            }
+
+            let error = read(&format!("{}\n4,x,\n", HEADER)).err().unwrap();
+            assert!(error.contains(":2: "), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod checkpoint;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod logger;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::upper_case_acronyms
+)]
+pub mod results;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
+pub mod scenario;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod simulation;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
+pub mod sweep;
+#[allow(clippy::module_inception, clippy::redundant_field_names)]
+pub mod timeseries;
+#[allow(
+    clippy::module_inception,
+    clippy::redundant_field_names,
+    clippy::should_implement_trait
+)]
+pub mod workload;
@@ -0,0 +1,1115 @@
+pub mod results {
+    use std::{
+        collections::HashMap,
+        fs::{File, OpenOptions},
+        io::{BufRead, BufReader, Seek, SeekFrom, Write},
+        sync::Arc,
+    };
+
+    use parquet::{
+        column::writer::ColumnWriter,
+        data_type::ByteArray,
+        file::{
+            properties::WriterProperties,
+            reader::{FileReader, SerializedFileReader},
+            writer::SerializedFileWriter,
+        },
+        record::Field,
+        schema::parser::parse_message_type,
+    };
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+    use crate::analysis::{
+        fairness::fairness::FairnessReport, operations::operations::OperationReport,
+        stats::stats::ResponseStatistics,
+    };
+    use crate::disk::{driver::driver::Operation, mapping::mapping::GeometryMapper};
+    use crate::simulation::{
+        logger::logger::{log_data_to_file, read_log_file, LogFile, LogHeader, LogRecord},
+        scenario::scenario::{OutputFormat, Scenario},
+        simulation::simulation::{SimulationResult, TaskRecord},
+    };
+
+    #[derive(Copy, Clone)]
+    pub enum ColumnType {
+        INTEGER,
+        /// An integer that may not fit in an `INTEGER`, such as a seed.
+        UNSIGNED,
+        REAL,
+        TEXT,
+    }
+
+    /// A flat record that can be written as a CSV, JSON Lines or Parquet row.
+    pub trait Row: Serialize {
+        const NAME: &'static str;
+        const COLUMNS: &'static [(&'static str, ColumnType)];
+    }
+
+    /// One summary row per simulated run.
+    #[derive(Serialize, Deserialize, Clone)]
+    pub struct RunSummary {
+        pub key: String,
+        /// The key without the repetition, shared by all repetitions of a configuration.
+        pub configuration: String,
+        pub repetition: u32,
+        /// Unknown for runs converted from legacy logs, as are the other optional fields.
+        pub seed: Option<u64>,
+        pub algorithm: String,
+        pub forward_speed: u32,
+        pub spin_speed: u32,
+        /// `track` or `curve`, see `[disk.seek]`.
+        pub seek_model: Option<String>,
+        /// `stepped` or `continuous`, see `[disk.rotation]`.
+        pub rotation_model: Option<String>,
+        pub tracks: u32,
+        pub heads: Option<u32>,
+        /// `linear`, `zoned` or `serpentine`, see `disk.mapping`.
+        pub mapping: Option<String>,
+        /// Tasks the drive queues and reorders, see `disk.queue_depth`.
+        pub queue_depth: Option<u32>,
+        /// `disk` or `ssd`, see `[disk.ssd]`.
+        pub device: Option<String>,
+        pub requests: u32,
+        pub completed: u32,
+        pub total_time: Option<u32>,
+        pub mean_response_time: f64,
+        pub median_response_time: f64,
+        pub p90_response_time: f64,
+        pub p95_response_time: f64,
+        pub p99_response_time: f64,
+        pub p999_response_time: f64,
+        pub max_response_time: u32,
+        pub std_dev_response_time: f64,
+        /// Completed requests per million ticks.
+        pub throughput: Option<f64>,
+        pub head_movement: Option<u64>,
+        pub seeks: Option<u32>,
+        pub average_seek_distance: Option<f64>,
+        pub direction_reversals: Option<u32>,
+        pub head_switches: Option<u32>,
+        /// Unknown when the drive had no cache or the run no reads.
+        pub cache_hits: Option<u32>,
+        pub cache_hit_ratio: Option<f64>,
+        /// Unknown when the drive had no write cache.
+        pub buffered_writes: Option<u32>,
+        pub destaged_writes: Option<u32>,
+        /// Written sectors that had not reached the platter when the run ended.
+        pub dirty_sectors: Option<u32>,
+        /// Queued tasks the drive served ahead of older ones.
+        pub reordered_tasks: Option<u32>,
+        /// Unknown unless the device was an SSD, or the run wrote nothing.
+        pub block_erases: Option<u32>,
+        pub write_amplification: Option<f64>,
+        pub seek_time: Option<u32>,
+        pub rotation_time: Option<u32>,
+        pub transfer_time: Option<u32>,
+        pub idle_time: Option<u32>,
+        pub mean_wait_time: Option<f64>,
+        pub mean_service_time: Option<f64>,
+        pub max_bypassed: Option<u32>,
+        /// Jain's index over the owners' mean response times.
+        pub fairness_index: Option<f64>,
+        pub reads: Option<u32>,
+        pub writes: Option<u32>,
+        pub flushes: Option<u32>,
+        pub discards: Option<u32>,
+        /// Unknown when the run had no reads, or writes.
+        pub mean_read_response_time: Option<f64>,
+        pub mean_write_response_time: Option<f64>,
+    }
+
+    /// One row per completed task.
+    #[derive(Serialize, Deserialize, Clone)]
+    pub struct TaskRow {
+        pub key: String,
+        pub id: u32,
+        pub arrival: Option<u32>,
+        pub start: Option<u32>,
+        pub completion: Option<u32>,
+        pub response_time: u32,
+        pub track: Option<u32>,
+        pub head: Option<u32>,
+        pub angle: Option<u32>,
+        /// First logical block, mapped back from the position for tasks
+        /// that were not addressed by block.
+        pub lba: Option<u64>,
+        pub length: Option<u32>,
+        /// `read`, `write`, `flush` or `discard`.
+        pub operation: Option<String>,
+        pub seek_distance: Option<u32>,
+        pub wait_time: Option<u32>,
+        pub service_time: Option<u32>,
+        pub owner: Option<u32>,
+        pub bypassed: Option<u32>,
+    }
+
+    /// Everything we keep about one finished simulation.
+    pub struct RunRecord {
+        pub summary: RunSummary,
+        /// Tasks in completion order.
+        pub tasks: Vec<TaskRow>,
+    }
+
+    enum Sink {
+        CSV(csv::Writer<File>),
+        JSONL(File),
+        PARQUET(ParquetSink),
+    }
+
+    struct ParquetSink {
+        writer: SerializedFileWriter<File>,
+        columns: &'static [(&'static str, ColumnType)],
+        rows: Vec<serde_json::Map<String, serde_json::Value>>,
+    }
+
+    /// Writes the run summaries to the output path and, unless disabled, the
+    /// task rows next to it as `<name>.tasks.<extension>`.
+    pub struct ResultWriter {
+        legacy: Option<File>,
+        summaries: Option<Sink>,
+        tasks: Option<Sink>,
+    }
+
+    impl Row for RunSummary {
+        const NAME: &'static str = "run";
+        const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+            ("key", ColumnType::TEXT),
+            ("configuration", ColumnType::TEXT),
+            ("repetition", ColumnType::INTEGER),
+            ("seed", ColumnType::UNSIGNED),
+            ("algorithm", ColumnType::TEXT),
+            ("forward_speed", ColumnType::INTEGER),
+            ("spin_speed", ColumnType::INTEGER),
+            ("seek_model", ColumnType::TEXT),
+            ("rotation_model", ColumnType::TEXT),
+            ("tracks", ColumnType::INTEGER),
+            ("heads", ColumnType::INTEGER),
+            ("mapping", ColumnType::TEXT),
+            ("queue_depth", ColumnType::INTEGER),
+            ("device", ColumnType::TEXT),
+            ("requests", ColumnType::INTEGER),
+            ("completed", ColumnType::INTEGER),
+            ("total_time", ColumnType::INTEGER),
+            ("mean_response_time", ColumnType::REAL),
+            ("median_response_time", ColumnType::REAL),
+            ("p90_response_time", ColumnType::REAL),
+            ("p95_response_time", ColumnType::REAL),
+            ("p99_response_time", ColumnType::REAL),
+            ("p999_response_time", ColumnType::REAL),
+            ("max_response_time", ColumnType::INTEGER),
+            ("std_dev_response_time", ColumnType::REAL),
+            ("throughput", ColumnType::REAL),
+            ("head_movement", ColumnType::INTEGER),
+            ("seeks", ColumnType::INTEGER),
+            ("average_seek_distance", ColumnType::REAL),
+            ("direction_reversals", ColumnType::INTEGER),
+            ("head_switches", ColumnType::INTEGER),
+            ("cache_hits", ColumnType::INTEGER),
+            ("cache_hit_ratio", ColumnType::REAL),
+            ("buffered_writes", ColumnType::INTEGER),
+            ("destaged_writes", ColumnType::INTEGER),
+            ("dirty_sectors", ColumnType::INTEGER),
+            ("reordered_tasks", ColumnType::INTEGER),
+            ("block_erases", ColumnType::INTEGER),
+            ("write_amplification", ColumnType::REAL),
+            ("seek_time", ColumnType::INTEGER),
+            ("rotation_time", ColumnType::INTEGER),
+            ("transfer_time", ColumnType::INTEGER),
+            ("idle_time", ColumnType::INTEGER),
+            ("mean_wait_time", ColumnType::REAL),
+            ("mean_service_time", ColumnType::REAL),
+            ("max_bypassed", ColumnType::INTEGER),
+            ("fairness_index", ColumnType::REAL),
+            ("reads", ColumnType::INTEGER),
+            ("writes", ColumnType::INTEGER),
+            ("flushes", ColumnType::INTEGER),
+            ("discards", ColumnType::INTEGER),
+            ("mean_read_response_time", ColumnType::REAL),
+            ("mean_write_response_time", ColumnType::REAL),
+        ];
+    }
+
+    impl Row for TaskRow {
+        const NAME: &'static str = "task";
+        const COLUMNS: &'static [(&'static str, ColumnType)] = &[
+            ("key", ColumnType::TEXT),
+            ("id", ColumnType::INTEGER),
+            ("arrival", ColumnType::INTEGER),
+            ("start", ColumnType::INTEGER),
+            ("completion", ColumnType::INTEGER),
+            ("response_time", ColumnType::INTEGER),
+            ("track", ColumnType::INTEGER),
+            ("head", ColumnType::INTEGER),
+            ("angle", ColumnType::INTEGER),
+            ("lba", ColumnType::INTEGER),
+            ("length", ColumnType::INTEGER),
+            ("operation", ColumnType::TEXT),
+            ("seek_distance", ColumnType::INTEGER),
+            ("wait_time", ColumnType::INTEGER),
+            ("service_time", ColumnType::INTEGER),
+            ("owner", ColumnType::INTEGER),
+            ("bypassed", ColumnType::INTEGER),
+        ];
+    }
+
+    impl TaskRow {
+        pub fn new(key: &str, task: &TaskRecord, mapper: &dyn GeometryMapper) -> TaskRow {
+            TaskRow {
+                key: key.to_owned(),
+                id: task.id,
+                arrival: Some(task.arrival),
+                start: Some(task.start.unwrap_or(task.completion)),
+                completion: Some(task.completion),
+                response_time: task.get_response_time(),
+                track: Some(task.track),
+                head: Some(task.head),
+                angle: Some(task.angle),
+                lba: task.lba.or_else(|| {
+                    mapper.to_lba_at(task.track, task.head, task.angle, task.sector)
+                }),
+                length: Some(task.length),
+                operation: Some(task.operation.get_name().to_owned()),
+                seek_distance: Some(task.seek_distance),
+                wait_time: Some(task.get_wait_time()),
+                service_time: Some(task.get_service_time()),
+                owner: Some(task.owner),
+                bypassed: Some(task.bypassed),
+            }
+        }
+    }
+
+    impl RunRecord {
+        pub fn new(
+            key: String,
+            configuration: String,
+            repetition: u32,
+            scenario: &Scenario,
+            result: SimulationResult,
+        ) -> RunRecord {
+            let mapper = scenario.disk.geometry.get_mapper(scenario.disk.tracks);
+            let tasks = result
+                .tasks
+                .iter()
+                .map(|task| TaskRow::new(&key, task, mapper.as_ref()))
+                .collect();
+
+            RunRecord::build(
+                key,
+                configuration,
+                repetition,
+                scenario,
+                Some(&result),
+                tasks,
+            )
+        }
+
+        /// Rebuilds what a legacy log kept about a run: its configuration and
+        /// the response times, in completion order.
+        pub fn from_legacy(
+            key: String,
+            configuration: String,
+            repetition: u32,
+            record: &LogRecord,
+        ) -> RunRecord {
+            let tasks = record
+                .response_times
+                .iter()
+                .enumerate()
+                .map(|(i, &response_time)| TaskRow {
+                    key: key.clone(),
+                    id: i as u32 + 1,
+                    arrival: None,
+                    start: None,
+                    completion: None,
+                    response_time: response_time,
+                    track: None,
+                    head: None,
+                    angle: None,
+                    lba: None,
+                    length: None,
+                    operation: None,
+                    seek_distance: None,
+                    wait_time: None,
+                    service_time: None,
+                    owner: None,
+                    bypassed: None,
+                })
+                .collect();
+
+            RunRecord::build(
+                key,
+                configuration,
+                repetition,
+                &record.to_scenario(),
+                None,
+                tasks,
+            )
+        }
+
+        fn build(
+            key: String,
+            configuration: String,
+            repetition: u32,
+            scenario: &Scenario,
+            result: Option<&SimulationResult>,
+            tasks: Vec<TaskRow>,
+        ) -> RunRecord {
+            let total_time = result.map(|r| r.total_time);
+            let disk = result.map(|r| r.disk);
+            let flash = result.and_then(|r| r.flash);
+            let write_cache = disk.filter(|_| scenario.disk.metadata.get_write_cache().is_some());
+            let fairness = result.map(|r| FairnessReport::from_tasks(&r.tasks));
+            let operations = result.map(|r| OperationReport::from_tasks(&r.tasks));
+            let count = |operation: Operation| {
+                operations
+                    .as_ref()
+                    .map(|o| o.get(operation).map_or(0, |summary| summary.tasks))
+            };
+            let mean = |operation: Operation| {
+                operations
+                    .as_ref()
+                    .and_then(|o| o.get(operation))
+                    .map(|summary| summary.mean_response_time)
+            };
+            let response_times: Vec<u32> = tasks.iter().map(|task| task.response_time).collect();
+            let statistics = ResponseStatistics::from_times(&response_times, total_time);
+
+            RunRecord {
+                summary: RunSummary {
+                    key: key,
+                    configuration: configuration,
+                    repetition: repetition,
+                    seed: scenario.workload.seed,
+                    algorithm: scenario.scheduler.algorithm.get_name().to_owned(),
+                    forward_speed: *scenario.disk.metadata.get_forward_speed(),
+                    spin_speed: *scenario.disk.metadata.get_spin_speed(),
+                    seek_model: Some(
+                        scenario
+                            .disk
+                            .metadata
+                            .get_seek_model()
+                            .get_name()
+                            .to_owned(),
+                    ),
+                    rotation_model: Some(
+                        scenario
+                            .disk
+                            .metadata
+                            .get_rotation_model()
+                            .get_name()
+                            .to_owned(),
+                    ),
+                    tracks: scenario.disk.tracks,
+                    heads: Some(scenario.disk.geometry.heads),
+                    mapping: Some(scenario.disk.geometry.mapping.get_name().to_owned()),
+                    queue_depth: Some(scenario.disk.metadata.get_queue_depth()),
+                    device: Some(scenario.disk.get_device_name().to_owned()),
+                    requests: scenario.workload.requests,
+                    completed: response_times.len() as u32,
+                    total_time: total_time,
+                    mean_response_time: statistics.mean,
+                    median_response_time: statistics.median,
+                    p90_response_time: statistics.p90,
+                    p95_response_time: statistics.p95,
+                    p99_response_time: statistics.p99,
+                    p999_response_time: statistics.p999,
+                    max_response_time: statistics.max,
+                    std_dev_response_time: statistics.std_dev,
+                    throughput: statistics.throughput,
+                    head_movement: disk.map(|d| d.get_tracks_traversed()),
+                    seeks: disk.map(|d| d.get_seeks()),
+                    average_seek_distance: disk.map(|d| d.get_average_seek_distance()),
+                    direction_reversals: disk.map(|d| d.get_direction_reversals()),
+                    head_switches: disk.map(|d| d.get_head_switches()),
+                    cache_hits: disk
+                        .filter(|d| d.get_cache_hit_ratio().is_some())
+                        .map(|d| d.get_cache_hits()),
+                    cache_hit_ratio: disk.and_then(|d| d.get_cache_hit_ratio()),
+                    buffered_writes: write_cache.map(|d| d.get_buffered_writes()),
+                    destaged_writes: write_cache.map(|d| d.get_destaged_writes()),
+                    dirty_sectors: write_cache.map(|d| d.get_dirty_sectors()),
+                    reordered_tasks: disk.map(|d| d.get_reordered_tasks()),
+                    block_erases: flash.map(|f| f.get_block_erases()),
+                    write_amplification: flash.and_then(|f| f.get_write_amplification()),
+                    seek_time: disk.map(|d| d.get_seek_time()),
+                    rotation_time: disk.map(|d| d.get_rotation_time()),
+                    transfer_time: disk.map(|d| d.get_transfer_time()),
+                    idle_time: disk.map(|d| d.get_idle_time()),
+                    mean_wait_time: fairness.as_ref().map(|f| f.mean_wait_time),
+                    mean_service_time: fairness.as_ref().map(|f| f.mean_service_time),
+                    max_bypassed: fairness.as_ref().map(|f| f.max_bypassed),
+                    fairness_index: fairness.as_ref().map(|f| f.fairness_index),
+                    reads: count(Operation::READ),
+                    writes: count(Operation::WRITE),
+                    flushes: count(Operation::FLUSH),
+                    discards: count(Operation::DISCARD),
+                    mean_read_response_time: mean(Operation::READ),
+                    mean_write_response_time: mean(Operation::WRITE),
+                },
+                tasks: tasks,
+            }
+        }
+
+        pub fn get_response_times(&self) -> Vec<u32> {
+            self.tasks.iter().map(|task| task.response_time).collect()
+        }
+    }
+
+    /// `results.csv` -> `results.tasks.csv`
+    pub fn tasks_path(path: &str) -> String {
+        match path.rsplit_once('.') {
+            Some((stem, extension)) if !extension.contains('/') => {
+                format!("{}.tasks.{}", stem, extension)
+            }
+            _ => format!("{}.tasks", path),
+        }
+    }
+
+    fn open_file(path: &str, offset: Option<u64>) -> Result<File, String> {
+        match offset {
+            None => File::create(path)
+                .map_err(|e| format!("Could not create output file {}: {}", path, e)),
+            Some(offset) => {
+                let mut file = OpenOptions::new()
+                    .write(true)
+                    .open(path)
+                    .map_err(|e| format!("Could not reopen output file {}: {}", path, e))?;
+                file.set_len(offset)
+                    .and_then(|_| file.seek(SeekFrom::End(0)))
+                    .map_err(|e| format!("Could not truncate output file {}: {}", path, e))?;
+
+                Ok(file)
+            }
+        }
+    }
+
+    fn sync_file(mut file: &File) -> Result<u64, String> {
+        file.sync_data()
+            .and_then(|_| file.stream_position())
+            .map_err(|e| format!("Could not flush the output file: {}", e))
+    }
+
+    fn parquet_error(e: parquet::errors::ParquetError) -> String {
+        format!("Could not write the Parquet output: {}", e)
+    }
+
+    /// The non-null `values` of column `name` converted by `convert`, with
+    /// the definition level of every value. A value `convert` rejects is an
+    /// error rather than a null, so both always describe the same rows.
+    fn column_data<T>(
+        name: &str,
+        values: &[&serde_json::Value],
+        convert: impl Fn(&serde_json::Value) -> Option<T>,
+    ) -> Result<(Vec<T>, Vec<i16>), String> {
+        let mut data = Vec::new();
+        let mut levels = Vec::with_capacity(values.len());
+        for value in values.iter() {
+            if value.is_null() {
+                levels.push(0);
+                continue;
+            }
+
+            let converted = convert(value)
+                .ok_or_else(|| format!("{} does not fit in the Parquet column {}", value, name))?;
+            data.push(converted);
+            levels.push(1);
+        }
+
+        Ok((data, levels))
+    }
+
+    impl ParquetSink {
+        const ROW_GROUP_SIZE: usize = 65536;
+
+        fn create<R: Row>(file: File) -> Result<ParquetSink, String> {
+            let fields: Vec<String> = R::COLUMNS
+                .iter()
+                .map(|(name, column_type)| match column_type {
+                    ColumnType::INTEGER => format!("OPTIONAL INT64 {};", name),
+                    ColumnType::UNSIGNED => format!("OPTIONAL INT64 {} (UINT_64);", name),
+                    ColumnType::REAL => format!("OPTIONAL DOUBLE {};", name),
+                    ColumnType::TEXT => format!("OPTIONAL BYTE_ARRAY {} (UTF8);", name),
+                })
+                .collect();
+            let schema =
+                parse_message_type(&format!("message {} {{ {} }}", R::NAME, fields.join(" ")))
+                    .map_err(parquet_error)?;
+            let properties = WriterProperties::builder().build();
+
+            Ok(ParquetSink {
+                writer: SerializedFileWriter::new(file, Arc::new(schema), Arc::new(properties))
+                    .map_err(parquet_error)?,
+                columns: R::COLUMNS,
+                rows: Vec::new(),
+            })
+        }
+
+        fn push<R: Row>(&mut self, row: &R) -> Result<(), String> {
+            match serde_json::to_value(row) {
+                Ok(serde_json::Value::Object(row)) => {
+                    self.rows.push(row);
+                    if self.rows.len() >= ParquetSink::ROW_GROUP_SIZE {
+                        self.flush()?;
+                    }
+
+                    Ok(())
+                }
+                _ => Err(format!("A {} row is not a flat record", R::NAME)),
+            }
+        }
+
+        /// Writes the buffered rows as one row group.
+        fn flush(&mut self) -> Result<(), String> {
+            if self.rows.is_empty() {
+                return Ok(());
+            }
+
+            let mut row_group = self.writer.next_row_group().map_err(parquet_error)?;
+            for (name, column_type) in self.columns.iter() {
+                let mut column = row_group
+                    .next_column()
+                    .map_err(parquet_error)?
+                    .ok_or_else(|| format!("The Parquet schema has no column {}", name))?;
+
+                let values: Vec<&serde_json::Value> = self
+                    .rows
+                    .iter()
+                    .map(|row| row.get(*name).unwrap_or(&serde_json::Value::Null))
+                    .collect();
+
+                let written = match (column_type, column.untyped()) {
+                    (ColumnType::UNSIGNED, ColumnWriter::Int64ColumnWriter(writer)) => {
+                        // Parquet keeps unsigned integers in the bits of signed ones.
+                        let (data, levels) =
+                            column_data(name, &values, |v| v.as_u64().map(|v| v as i64))?;
+                        writer.write_batch(&data, Some(&levels), None)
+                    }
+                    (_, ColumnWriter::Int64ColumnWriter(writer)) => {
+                        let (data, levels) = column_data(name, &values, |v| v.as_i64())?;
+                        writer.write_batch(&data, Some(&levels), None)
+                    }
+                    (_, ColumnWriter::DoubleColumnWriter(writer)) => {
+                        let (data, levels) = column_data(name, &values, |v| v.as_f64())?;
+                        writer.write_batch(&data, Some(&levels), None)
+                    }
+                    (_, ColumnWriter::ByteArrayColumnWriter(writer)) => {
+                        let (data, levels) =
+                            column_data(name, &values, |v| v.as_str().map(ByteArray::from))?;
+                        writer.write_batch(&data, Some(&levels), None)
+                    }
+                    _ => return Err(format!("Unsupported Parquet column {}", name)),
+                };
+                written.map_err(parquet_error)?;
+                column.close().map_err(parquet_error)?;
+            }
+            row_group.close().map_err(parquet_error)?;
+            self.rows.clear();
+
+            Ok(())
+        }
+
+        fn close(mut self) -> Result<(), String> {
+            self.flush()?;
+            self.writer.close().map_err(parquet_error)?;
+
+            Ok(())
+        }
+    }
+
+    impl Sink {
+        fn create<R: Row>(
+            path: &str,
+            format: OutputFormat,
+            offset: Option<u64>,
+        ) -> Result<Sink, String> {
+            let file = open_file(path, offset)?;
+
+            match format {
+                OutputFormat::CSV => Ok(Sink::CSV(
+                    csv::WriterBuilder::new()
+                        .has_headers(offset.unwrap_or(0) == 0)
+                        .from_writer(file),
+                )),
+                OutputFormat::JSONL => Ok(Sink::JSONL(file)),
+                OutputFormat::PARQUET => {
+                    if offset.is_some() {
+                        return Err(format!(
+                            "{}: a Parquet output can not be resumed, start over with --fresh",
+                            path
+                        ));
+                    }
+
+                    Ok(Sink::PARQUET(ParquetSink::create::<R>(file)?))
+                }
+                OutputFormat::LEGACY => Err("The legacy format has no rows".to_owned()),
+            }
+        }
+
+        fn write<R: Row>(&mut self, row: &R) -> Result<(), String> {
+            match self {
+                Sink::CSV(writer) => writer
+                    .serialize(row)
+                    .map_err(|e| format!("Could not write a {} row: {}", R::NAME, e)),
+                Sink::JSONL(file) => {
+                    let mut line = serde_json::to_string(row)
+                        .map_err(|e| format!("Could not serialize a {} row: {}", R::NAME, e))?;
+                    line.push('\n');
+
+                    file.write_all(line.as_bytes())
+                        .map_err(|e| format!("Could not write a {} row: {}", R::NAME, e))
+                }
+                Sink::PARQUET(sink) => sink.push(row),
+            }
+        }
+
+        /// Makes the rows written so far durable and returns the file length.
+        fn sync(&mut self) -> Result<u64, String> {
+            match self {
+                Sink::CSV(writer) => {
+                    writer
+                        .flush()
+                        .map_err(|e| format!("Could not flush the output file: {}", e))?;
+                    sync_file(writer.get_ref())
+                }
+                Sink::JSONL(file) => sync_file(file),
+                // Parquet files are only readable once closed, so there is
+                // nothing to resume from and no offset to report.
+                Sink::PARQUET(_) => Ok(0),
+            }
+        }
+
+        fn close(self) -> Result<(), String> {
+            match self {
+                Sink::CSV(mut writer) => writer
+                    .flush()
+                    .map_err(|e| format!("Could not flush the output file: {}", e)),
+                Sink::JSONL(_) => Ok(()),
+                Sink::PARQUET(sink) => sink.close(),
+            }
+        }
+    }
+
+    impl ResultWriter {
+        pub fn create(
+            path: &str,
+            format: OutputFormat,
+            tasks: bool,
+        ) -> Result<ResultWriter, String> {
+            ResultWriter::open(path, format, tasks, None)
+        }
+
+        /// Reopens a partially written output, dropping anything after the
+        /// offsets `sync` returned for the last run known to be complete.
+        pub fn resume(
+            path: &str,
+            format: OutputFormat,
+            tasks: bool,
+            offsets: &[u64],
+        ) -> Result<ResultWriter, String> {
+            ResultWriter::open(path, format, tasks, Some(offsets))
+        }
+
+        fn open(
+            path: &str,
+            format: OutputFormat,
+            tasks: bool,
+            offsets: Option<&[u64]>,
+        ) -> Result<ResultWriter, String> {
+            let offset = |i: usize| offsets.map(|o| o.get(i).copied().unwrap_or(0));
+
+            if format == OutputFormat::LEGACY {
+                return Ok(ResultWriter {
+                    legacy: Some(open_file(path, offset(0))?),
+                    summaries: None,
+                    tasks: None,
+                });
+            }
+
+            let task_sink = if tasks {
+                Some(Sink::create::<TaskRow>(
+                    &tasks_path(path),
+                    format,
+                    offset(1),
+                )?)
+            } else {
+                None
+            };
+
+            Ok(ResultWriter {
+                legacy: None,
+                summaries: Some(Sink::create::<RunSummary>(path, format, offset(0))?),
+                tasks: task_sink,
+            })
+        }
+
+        pub fn write_run(&mut self, scenario: &Scenario, record: &RunRecord) -> Result<(), String> {
+            if let Some(file) = &mut self.legacy {
+                let log_header = LogHeader {
+                    metadata: scenario.disk.metadata,
+                    max_tracks: scenario.disk.tracks,
+                    steps: scenario.workload.requests,
+                };
+                log_data_to_file(
+                    file,
+                    scenario.scheduler.algorithm,
+                    log_header,
+                    &record.get_response_times(),
+                );
+            }
+
+            if let Some(summaries) = &mut self.summaries {
+                summaries.write(&record.summary)?;
+            }
+            if let Some(tasks) = &mut self.tasks {
+                for task in record.tasks.iter() {
+                    tasks.write(task)?;
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Makes everything written so far durable and returns the length of
+        /// each output file, for the sweep manifest.
+        pub fn sync(&mut self) -> Result<Vec<u64>, String> {
+            let mut offsets = Vec::new();
+
+            if let Some(file) = &self.legacy {
+                offsets.push(sync_file(file)?);
+            }
+            if let Some(summaries) = &mut self.summaries {
+                offsets.push(summaries.sync()?);
+            }
+            if let Some(tasks) = &mut self.tasks {
+                offsets.push(tasks.sync()?);
+            }
+
+            Ok(offsets)
+        }
+
+        pub fn finish(self) -> Result<(), String> {
+            if let Some(summaries) = self.summaries {
+                summaries.close()?;
+            }
+            if let Some(tasks) = self.tasks {
+                tasks.close()?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Only the column types `ParquetSink` writes.
+    fn field_to_json(field: &Field) -> serde_json::Value {
+        match field {
+            Field::Long(value) => serde_json::Value::from(*value),
+            Field::ULong(value) => serde_json::Value::from(*value),
+            Field::Double(value) => serde_json::Value::from(*value),
+            Field::Str(value) => serde_json::Value::from(value.as_str()),
+            _ => serde_json::Value::Null,
+        }
+    }
+
+    /// Reads back the rows of a CSV, JSON Lines or Parquet output.
+    pub fn read_rows<R: Row + DeserializeOwned>(
+        path: &str,
+        format: OutputFormat,
+    ) -> Result<Vec<R>, String> {
+        let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path, e))?;
+        let invalid = |e: String| format!("{}: invalid {} row: {}", path, R::NAME, e);
+
+        match format {
+            OutputFormat::CSV => csv::Reader::from_reader(file)
+                .deserialize()
+                .map(|row| row.map_err(|e| invalid(e.to_string())))
+                .collect(),
+            OutputFormat::JSONL => BufReader::new(file)
+                .lines()
+                .map(|line| line.map_err(|e| format!("Could not read {}: {}", path, e)))
+                .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+                .map(|line| serde_json::from_str(&line?).map_err(|e| invalid(e.to_string())))
+                .collect(),
+            OutputFormat::PARQUET => {
+                let reader = SerializedFileReader::new(file)
+                    .map_err(|e| format!("Could not read {}: {}", path, e))?;
+                reader
+                    .get_row_iter(None)
+                    .map_err(|e| format!("Could not read {}: {}", path, e))?
+                    .map(|row| {
+                        let row = row.map_err(|e| invalid(e.to_string()))?;
+                        let values = row
+                            .get_column_iter()
+                            .map(|(name, field)| (name.clone(), field_to_json(field)))
+                            .collect();
+                        serde_json::from_value(serde_json::Value::Object(values))
+                            .map_err(|e| invalid(e.to_string()))
+                    })
+                    .collect()
+            }
+            OutputFormat::LEGACY => Err(format!("{}: the legacy format has no rows", path)),
+        }
+    }
+
+    fn read_legacy_logs(files: &[String]) -> Result<Vec<LogFile>, String> {
+        let mut logs = Vec::new();
+        for file in files.iter() {
+            let log = read_log_file(file)?;
+            if log.truncated {
+                eprintln!(
+                    "{}: ignoring an incomplete record at the end of the file",
+                    file
+                );
+            }
+            logs.push(log);
+        }
+
+        Ok(logs)
+    }
+
+    /// Numbers the repetitions of each configuration in the order they were logged.
+    pub fn read_legacy_runs(files: &[String]) -> Result<Vec<(Scenario, RunRecord)>, String> {
+        let mut repetitions: HashMap<String, u32> = HashMap::new();
+        let mut runs = Vec::new();
+
+        for log in read_legacy_logs(files)? {
+            for record in log.records.iter() {
+                let configuration = record.get_configuration();
+                let repetition = repetitions.entry(configuration.clone()).or_insert(0);
+                let key = format!("{},repetition={}", configuration, repetition);
+
+                runs.push((
+                    record.to_scenario(),
+                    RunRecord::from_legacy(key, configuration, *repetition, record),
+                ));
+                *repetition += 1;
+            }
+        }
+
+        Ok(runs)
+    }
+
+    /// Reads runs back from summary outputs or legacy logs. With `tasks`, the
+    /// task rows of structured outputs are read from their tasks file too.
+    pub fn read_runs(files: &[String], tasks: bool) -> Result<Vec<RunRecord>, String> {
+        let mut runs = Vec::new();
+        for file in files.iter() {
+            let extension = file.rsplit_once('.').map(|(_, e)| e).unwrap_or("");
+            let format = OutputFormat::from_name(extension)
+                .map_err(|_| format!("{}: unknown result file type", file))?;
+
+            if let OutputFormat::LEGACY = format {
+                runs.extend(
+                    read_legacy_runs(std::slice::from_ref(file))?
+                        .into_iter()
+                        .map(|(_, run)| run),
+                );
+                continue;
+            }
+
+            let mut rows: HashMap<String, Vec<TaskRow>> = HashMap::new();
+            if tasks {
+                let path = tasks_path(file);
+                for row in read_rows::<TaskRow>(&path, format)
+                    .map_err(|e| format!("{} (was it written with --no-tasks?)", e))?
+                {
+                    rows.entry(row.key.clone()).or_default().push(row);
+                }
+            }
+            for summary in read_rows::<RunSummary>(file, format)? {
+                runs.push(RunRecord {
+                    tasks: rows.remove(&summary.key).unwrap_or_default(),
+                    summary: summary,
+                });
+            }
+        }
+
+        Ok(runs)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::simulation::workload::workload::ArrivalProcess;
+
+        fn record(key: &str) -> (Scenario, RunRecord) {
+            record_with_seed(key, key.len() as u64)
+        }
+
+        fn record_with_seed(key: &str, seed: u64) -> (Scenario, RunRecord) {
+            let mut scenario = Scenario::default();
+            scenario.workload.requests = 5;
+            scenario.disk.tracks = 100;
+            scenario.disk.metadata.set_spin_speed(5);
+            scenario.workload.seed = Some(seed);
+            scenario.workload.arrival = Some(ArrivalProcess::PERIODIC { interval: 100 });
+            let result = scenario.run(false).unwrap();
+            let record = RunRecord::new(key.to_owned(), key.to_owned(), 0, &scenario, result);
+
+            (scenario, record)
+        }
+
+        /// Writes `keys` as one run each and returns the summary and task paths.
+        fn write_runs(
+            dir: &tempfile::TempDir,
+            format: OutputFormat,
+            keys: &[&str],
+        ) -> (String, String) {
+            let path = dir
+                .path()
+                .join(format!("runs.{}", format.get_extension()))
+                .to_string_lossy()
+                .into_owned();
+            let mut writer = ResultWriter::create(&path, format, true).unwrap();
+            for key in keys {
+                let (scenario, record) = record(key);
+                writer.write_run(&scenario, &record).unwrap();
+            }
+            writer.finish().unwrap();
+
+            let tasks = tasks_path(&path);
+            (path, tasks)
+        }
+
+        fn read_csv<R: serde::de::DeserializeOwned>(path: &str) -> Vec<R> {
+            csv::Reader::from_path(path)
+                .unwrap()
+                .deserialize()
+                .map(|row| row.unwrap())
+                .collect()
+        }
+
+        fn read_jsonl<R: serde::de::DeserializeOwned>(path: &str) -> Vec<R> {
+            std::fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+
+        fn same<R: Serialize>(read: &[R], written: &[R]) -> bool {
+            serde_json::to_value(read).unwrap() == serde_json::to_value(written).unwrap()
+        }
+
+        #[test]
+        fn tasks_are_written_next_to_the_summaries() {
+            assert_eq!(tasks_path("out/results.csv"), "out/results.tasks.csv");
+            assert_eq!(tasks_path("results"), "results.tasks");
+            assert_eq!(tasks_path("out.d/results"), "out.d/results.tasks");
+        }
+
+        #[test]
+        fn csv_rows_read_back_unchanged() {
+            let dir = tempfile::tempdir().unwrap();
+            let (path, tasks) = write_runs(&dir, OutputFormat::CSV, &["a", "bb"]);
+            let (_, a) = record("a");
+            let (_, b) = record("bb");
+
+            let summaries: Vec<RunSummary> = read_csv(&path);
+            assert!(same(&summaries, &[a.summary.clone(), b.summary.clone()]));
+            let rows: Vec<TaskRow> = read_csv(&tasks);
+            assert!(same(&rows, &[a.tasks, b.tasks].concat()));
+        }
+
+        #[test]
+        fn jsonl_rows_read_back_unchanged() {
+            let dir = tempfile::tempdir().unwrap();
+            let (path, tasks) = write_runs(&dir, OutputFormat::JSONL, &["a", "bb"]);
+            let (_, a) = record("a");
+            let (_, b) = record("bb");
+
+            let summaries: Vec<RunSummary> = read_jsonl(&path);
+            assert!(same(&summaries, &[a.summary.clone(), b.summary.clone()]));
+            let rows: Vec<TaskRow> = read_jsonl(&tasks);
+            assert_eq!(rows.len(), 10);
+            assert!(same(&rows, &[a.tasks, b.tasks].concat()));
+        }
+
+        #[test]
+        fn resuming_drops_everything_after_the_synced_offsets() {
+            for format in [OutputFormat::CSV, OutputFormat::JSONL] {
+                let dir = tempfile::tempdir().unwrap();
+                let path = dir
+                    .path()
+                    .join(format!("runs.{}", format.get_extension()))
+                    .to_string_lossy()
+                    .into_owned();
+
+                let mut writer = ResultWriter::create(&path, format, true).unwrap();
+                let (scenario, first) = record("a");
+                writer.write_run(&scenario, &first).unwrap();
+                let offsets = writer.sync().unwrap();
+                let (scenario, lost) = record("bb");
+                writer.write_run(&scenario, &lost).unwrap();
+                writer.finish().unwrap();
+
+                let mut writer = ResultWriter::resume(&path, format, true, &offsets).unwrap();
+                assert_eq!(writer.sync().unwrap(), offsets);
+                let (scenario, last) = record("ccc");
+                writer.write_run(&scenario, &last).unwrap();
+                writer.finish().unwrap();
+
+                let (summaries, rows): (Vec<RunSummary>, Vec<TaskRow>) =
+                    if format == OutputFormat::CSV {
+                        (read_csv(&path), read_csv(&tasks_path(&path)))
+                    } else {
+                        (read_jsonl(&path), read_jsonl(&tasks_path(&path)))
+                    };
+                let keys: Vec<&str> = summaries.iter().map(|s| s.key.as_str()).collect();
+                assert_eq!(keys, vec!["a", "ccc"]);
+                assert!(same(&rows, &[first.tasks, last.tasks].concat()));
+            }
+        }
+
+        #[test]
+        fn parquet_outputs_can_not_be_resumed() {
+            let dir = tempfile::tempdir().unwrap();
+            let (path, _) = write_runs(&dir, OutputFormat::PARQUET, &["a"]);
+
+            assert!(ResultWriter::resume(&path, OutputFormat::PARQUET, true, &[0, 0]).is_err());
+        }
+
+        #[test]
+        fn column_data_skips_nulls_in_the_data_only() {
+            let values = [
+                serde_json::json!(3),
+                serde_json::Value::Null,
+                serde_json::json!(5),
+            ];
+            let values: Vec<&serde_json::Value> = values.iter().collect();
+
+            let (data, levels) = column_data("count", &values, |v| v.as_i64()).unwrap();
+            assert_eq!(data, vec![3, 5]);
+            assert_eq!(levels, vec![1, 0, 1]);
+        }
+
+        #[test]
+        fn column_data_rejects_values_the_column_can_not_hold() {
+            let values = [serde_json::json!(u64::MAX)];
+            let values: Vec<&serde_json::Value> = values.iter().collect();
+
+            let error = column_data("count", &values, |v| v.as_i64()).err().unwrap();
+            assert!(error.contains("count"), "{}", error);
+            let (data, _) = column_data("seed", &values, |v| v.as_u64().map(|v| v as i64)).unwrap();
+            assert_eq!(data, vec![-1]);
+        }
+
+        #[test]
+        fn parquet_keeps_seeds_above_the_signed_range() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir
+                .path()
+                .join("runs.parquet")
+                .to_string_lossy()
+                .into_owned();
+            let (scenario, record) = record_with_seed("run", u64::MAX);
+            let mut writer = ResultWriter::create(&path, OutputFormat::PARQUET, true).unwrap();
+            writer.write_run(&scenario, &record).unwrap();
+            writer.finish().unwrap();
+
+            let runs = read_runs(std::slice::from_ref(&path), true).unwrap();
+            assert_eq!(runs.len(), 1);
+            assert_eq!(runs[0].summary.seed, Some(u64::MAX));
+            assert_eq!(runs[0].tasks.len(), 5);
+        }
+    }
+}
@@ -0,0 +1,357 @@
+pub mod scenario {
+    use std::{collections::BTreeMap, fs};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::analysis::charts::charts::{draw_run_charts, ChartFormat};
+    use crate::disk::{
+        device::device::Device,
+        disk::disk::{Disk, DiskMetadata},
+        geometry::geometry::Geometry,
+        ssd::ssd::{Ssd, SsdConfig},
+    };
+    use crate::simulation::{
+        logger::logger::default_output_path,
+        results::results::{ResultWriter, RunRecord},
+        simulation::simulation::{run_simulation, Algorithms, SchedulerConfig, SimulationResult},
+        timeseries::timeseries::write_time_series,
+        workload::workload::WorkloadConfig,
+    };
+
+    #[derive(Serialize, Deserialize, Clone)]
+    pub struct DiskConfig {
+        #[serde(flatten)]
+        pub metadata: DiskMetadata,
+        /// Cylinders when the disk has several heads.
+        pub tracks: u32,
+        #[serde(flatten)]
+        pub geometry: Geometry,
+        /// Simulates an SSD holding the disk's logical blocks instead of the
+        /// disk itself. The queue depth still applies.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub ssd: Option<SsdConfig>,
+        /// Keys of `[disk]` none of the above knows, rejected when validating:
+        /// flattened fields would otherwise let misspelled keys through.
+        #[serde(flatten, skip_serializing)]
+        pub unknown: BTreeMap<String, toml::Value>,
+    }
+
+    #[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    pub enum OutputFormat {
+        LEGACY,
+        CSV,
+        JSONL,
+        PARQUET,
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct OutputConfig {
+        #[serde(default)]
+        pub path: Option<String>,
+        #[serde(default = "OutputConfig::default_format")]
+        pub format: OutputFormat,
+        /// Also write one row per task next to the run summaries.
+        #[serde(default = "OutputConfig::default_tasks")]
+        pub tasks: bool,
+        /// CSV file to sample the queue and disk into over time; not used by sweeps.
+        #[serde(default)]
+        pub timeseries: Option<String>,
+        /// Ticks between two time series samples.
+        #[serde(default = "OutputConfig::default_sample_interval")]
+        pub sample_interval: u32,
+        /// Directory to draw the run's charts into; not used by sweeps.
+        #[serde(default)]
+        pub charts: Option<String>,
+        #[serde(default = "OutputConfig::default_chart_format")]
+        pub chart_format: ChartFormat,
+        /// Write an HTML report next to the results; only used by sweeps.
+        #[serde(default = "OutputConfig::default_report")]
+        pub report: bool,
+    }
+
+    /// A complete, repeatable description of one simulation: the disk, the
+    /// requests thrown at it, the scheduler serving them and where results go.
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct Scenario {
+        pub disk: DiskConfig,
+        pub workload: WorkloadConfig,
+        pub scheduler: SchedulerConfig,
+        #[serde(default = "OutputConfig::default")]
+        pub output: OutputConfig,
+    }
+
+    impl DiskConfig {
+        pub fn new(metadata: DiskMetadata, tracks: u32) -> DiskConfig {
+            DiskConfig {
+                metadata: metadata,
+                tracks: tracks,
+                geometry: Geometry::default(),
+                ssd: None,
+                unknown: BTreeMap::new(),
+            }
+        }
+
+        pub fn build(&self) -> Box<dyn Device> {
+            match self.ssd {
+                Some(config) => Box::new(Ssd::new(
+                    config,
+                    self.geometry.get_mapper(self.tracks),
+                    self.metadata.get_queue_depth(),
+                )),
+                None => Box::new(Disk::with_geometry(self.metadata, self.geometry.clone())),
+            }
+        }
+
+        /// What kind of device `build` makes.
+        pub fn get_device_name(&self) -> &'static str {
+            match self.ssd {
+                Some(_) => "ssd",
+                None => "disk",
+            }
+        }
+    }
+
+    impl OutputConfig {
+        pub fn default() -> OutputConfig {
+            OutputConfig {
+                path: None,
+                format: OutputConfig::default_format(),
+                tasks: OutputConfig::default_tasks(),
+                timeseries: None,
+                sample_interval: OutputConfig::default_sample_interval(),
+                charts: None,
+                chart_format: OutputConfig::default_chart_format(),
+                report: OutputConfig::default_report(),
+            }
+        }
+
+        fn default_report() -> bool {
+            true
+        }
+
+        fn default_chart_format() -> ChartFormat {
+            ChartFormat::SVG
+        }
+
+        fn default_sample_interval() -> u32 {
+            1000
+        }
+
+        /// The sampling interval, when a time series or the head trace chart was asked for.
+        pub fn get_sample_interval(&self) -> Option<u32> {
+            if self.timeseries.is_some() || self.charts.is_some() {
+                Some(self.sample_interval)
+            } else {
+                None
+            }
+        }
+
+        fn default_format() -> OutputFormat {
+            OutputFormat::LEGACY
+        }
+
+        fn default_tasks() -> bool {
+            true
+        }
+
+        pub fn resolve_path(&self) -> String {
+            match &self.path {
+                Some(path) => path.clone(),
+                None => default_output_path(self.format.get_extension()),
+            }
+        }
+    }
+
+    impl OutputFormat {
+        pub fn from_name(name: &str) -> Result<OutputFormat, String> {
+            match name.trim().to_lowercase().as_str() {
+                "legacy" | "txt" => Ok(OutputFormat::LEGACY),
+                "csv" => Ok(OutputFormat::CSV),
+                "jsonl" => Ok(OutputFormat::JSONL),
+                "parquet" => Ok(OutputFormat::PARQUET),
+                _ => Err(format!(
+                    "Unknown output format `{}`, expected `legacy`, `csv`, `jsonl` or `parquet`",
+                    name
+                )),
+            }
+        }
+
+        pub fn get_extension(&self) -> &'static str {
+            match self {
+                OutputFormat::LEGACY => "txt",
+                OutputFormat::CSV => "csv",
+                OutputFormat::JSONL => "jsonl",
+                OutputFormat::PARQUET => "parquet",
+            }
+        }
+    }
+
+    impl Scenario {
+        /// The configuration the interactive menu falls back to.
+        pub fn default() -> Scenario {
+            Scenario {
+                disk: DiskConfig::new(DiskMetadata::default(), 10000),
+                workload: WorkloadConfig::new(100, None),
+                scheduler: SchedulerConfig::new(Algorithms::ELEVATOR),
+                output: OutputConfig::default(),
+            }
+        }
+
+        pub fn from_file(path: &str) -> Result<Scenario, String> {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Could not read scenario file {}: {}", path, e))?;
+
+            Scenario::from_toml(&content).map_err(|e| format!("{}: {}", path, e))
+        }
+
+        pub fn from_toml(content: &str) -> Result<Scenario, String> {
+            let scenario: Scenario =
+                toml::from_str(content).map_err(|e| format!("Invalid scenario: {}", e))?;
+            scenario.validate()?;
+
+            Ok(scenario)
+        }
+
+        pub fn from_value(value: toml::Value) -> Result<Scenario, String> {
+            let scenario: Scenario = value
+                .try_into()
+                .map_err(|e| format!("Invalid scenario: {}", e))?;
+            scenario.validate()?;
+
+            Ok(scenario)
+        }
+
+        pub fn to_value(&self) -> toml::Value {
+            toml::Value::try_from(self).expect("A scenario can always be written as TOML")
+        }
+
+        pub fn validate(&self) -> Result<(), String> {
+            if let Some(key) = self.disk.unknown.keys().next() {
+                return Err(format!("Unknown key disk.{}", key));
+            }
+            if self.disk.tracks == 0 {
+                return Err("disk.tracks must be at least 1".to_owned());
+            }
+            if *self.disk.metadata.get_forward_speed() == 0 {
+                return Err("disk.forward_speed must be at least 1".to_owned());
+            }
+            if *self.disk.metadata.get_spin_speed() == 0 {
+                return Err("disk.spin_speed must be at least 1".to_owned());
+            }
+            self.disk.metadata.get_rotation_model().validate()?;
+            if let Some(cache) = self.disk.metadata.get_cache() {
+                cache.validate()?;
+            }
+            if let Some(write_cache) = self.disk.metadata.get_write_cache() {
+                write_cache.validate()?;
+            }
+            if self.disk.metadata.get_queue_depth() == 0 {
+                return Err("disk.queue_depth must be at least 1".to_owned());
+            }
+            self.disk.geometry.validate(self.disk.tracks)?;
+            if let Some(ssd) = &self.disk.ssd {
+                ssd.validate()?;
+            }
+            if self.workload.requests == 0 {
+                return Err("workload.requests must be at least 1".to_owned());
+            }
+            if self.workload.owners == 0 {
+                return Err("workload.owners must be at least 1".to_owned());
+            }
+            if self.workload.length == 0 {
+                return Err("workload.length must be at least 1".to_owned());
+            }
+            if let Some(arrival) = &self.workload.arrival {
+                arrival.validate()?;
+            }
+            self.workload.spatial.validate(self.disk.tracks)?;
+            if let Some(mix) = &self.workload.mix {
+                mix.validate()?;
+            }
+            if self.output.sample_interval == 0 {
+                return Err("output.sample_interval must be at least 1".to_owned());
+            }
+
+            Ok(())
+        }
+
+        /// Runs the scenario, pinning the workload seed so the run can be reproduced.
+        pub fn run(&mut self, verbose: bool) -> Result<SimulationResult, String> {
+            let seed = self.workload.resolve_seed();
+            self.workload.seed = Some(seed);
+            if verbose {
+                println!("Workload seed: {}", seed);
+            }
+
+            let arrivals = self
+                .workload
+                .generate(self.disk.tracks, self.disk.geometry.heads, seed)?;
+            run_simulation(
+                self.scheduler,
+                self.disk.build(),
+                &arrivals,
+                verbose,
+                self.output.get_sample_interval(),
+            )
+        }
+
+        /// Writes the result to the configured output and returns the path used.
+        pub fn write_output(&self, result: SimulationResult) -> Result<String, String> {
+            let path = self.output.resolve_path();
+            if let Some(timeseries) = &self.output.timeseries {
+                write_time_series(timeseries, &result.samples)?;
+            }
+            if let Some(charts) = &self.output.charts {
+                draw_run_charts(
+                    charts,
+                    self.output.chart_format,
+                    self.scheduler.algorithm.get_name(),
+                    &result,
+                    self.disk.tracks,
+                )?;
+            }
+            let record = RunRecord::new(String::new(), String::new(), 0, self, result);
+
+            let mut writer = ResultWriter::create(&path, self.output.format, self.output.tasks)?;
+            writer.write_run(self, &record)?;
+            writer.finish()?;
+
+            Ok(path)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const DEFAULT: &str = include_str!("../../scenarios/default.toml");
+
+        #[test]
+        fn the_default_scenario_is_valid() {
+            let scenario = Scenario::from_toml(DEFAULT).unwrap();
+
+            assert_eq!(scenario.disk.tracks, 10000);
+            assert_eq!(scenario.workload.requests, 100);
+            assert_eq!(scenario.workload.seed, Some(42));
+        }
+
+        #[test]
+        fn invalid_scenarios_are_rejected() {
+            for (from, to) in [
+                ("tracks = 10000", "tracks = 0"),
+                ("spin_speed = 100", "spin_speed = 0"),
+                ("forward_speed = 1", "forward_speed = 0"),
+                ("tracks = 10000", "tracks = 10000\nhead = 4"),
+                ("tracks = 10000", "tracks = 10000\nwrite_cache = { capacity = 8 }\nwritecache = 1"),
+                ("requests = 100", "requests = 0"),
+                ("seed = 42", "seed = 42\nrequest = 7"),
+            ] {
+                assert!(DEFAULT.contains(from));
+                assert!(Scenario::from_toml(&DEFAULT.replace(from, to)).is_err());
+            }
+        }
+    }
+}
@@ -0,0 +1,307 @@
+pub mod simulation {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::disk::{
+        device::device::Device,
+        disk::disk::DiskStatistics,
+        driver::driver::{Driver, ElevetorDriver, Operation, SimpleDriver, Task},
+        ssd::ssd::FlashStatistics,
+    };
+    use crate::simulation::{
+        timeseries::timeseries::{Sample, Sampler},
+        workload::workload::Arrival,
+    };
+
+    #[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Algorithms {
+        NAIVE,
+        ELEVATOR,
+    }
+
+    #[derive(Serialize, Deserialize, Copy, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct SchedulerConfig {
+        pub algorithm: Algorithms,
+        #[serde(default = "SchedulerConfig::default_pick_up_on_the_way")]
+        pub pick_up_on_the_way: bool,
+    }
+
+    /// What happened to one task during a simulation, in ticks.
+    pub struct TaskRecord {
+        pub id: u32,
+        pub arrival: u32,
+        /// First tick the driver worked on the task.
+        pub start: Option<u32>,
+        pub completion: u32,
+        pub track: u32,
+        pub head: u32,
+        pub angle: u32,
+        /// Set when the task was placed on a sector rather than a degree.
+        pub sector: Option<u32>,
+        pub length: u32,
+        /// Set when the task was addressed by logical block.
+        pub lba: Option<u64>,
+        pub operation: Operation,
+        /// Tracks the head travelled while this task was being served.
+        pub seek_distance: u32,
+        pub owner: u32,
+        /// Tasks that arrived after this one but completed before it.
+        pub bypassed: u32,
+    }
+
+    pub struct SimulationResult {
+        /// Response times in completion order.
+        pub response_times: Vec<u32>,
+        /// Completed tasks in completion order.
+        pub tasks: Vec<TaskRecord>,
+        pub total_time: u32,
+        pub disk: DiskStatistics,
+        /// Set when the simulated device was an SSD.
+        pub flash: Option<FlashStatistics>,
+        /// Empty unless the simulation was sampled.
+        pub samples: Vec<Sample>,
+    }
+
+    impl TaskRecord {
+        fn new(task: &Task, arrival: u32, owner: u32) -> TaskRecord {
+            TaskRecord {
+                id: *task.get_id(),
+                arrival: arrival,
+                start: None,
+                completion: 0,
+                track: *task.get_track(),
+                head: *task.get_head(),
+                angle: *task.get_angle(),
+                sector: task.get_sector(),
+                length: *task.get_length(),
+                lba: task.get_lba(),
+                operation: task.get_operation(),
+                seek_distance: 0,
+                owner: owner,
+                bypassed: 0,
+            }
+        }
+
+        pub fn get_response_time(&self) -> u32 {
+            self.completion - self.arrival
+        }
+
+        /// Ticks spent queued before the driver picked the task up.
+        pub fn get_wait_time(&self) -> u32 {
+            self.start.unwrap_or(self.completion) - self.arrival
+        }
+
+        /// Ticks from being picked up to completion.
+        pub fn get_service_time(&self) -> u32 {
+            self.completion - self.start.unwrap_or(self.completion)
+        }
+    }
+
+    impl Algorithms {
+        pub fn from_name(name: &str) -> Result<Algorithms, String> {
+            match name.trim().to_lowercase().as_str() {
+                "naive" | "fcfs" => Ok(Algorithms::NAIVE),
+                "elevator" | "scan" => Ok(Algorithms::ELEVATOR),
+                _ => Err(format!(
+                    "Unknown algorithm `{}`, expected `naive` or `elevator`",
+                    name
+                )),
+            }
+        }
+
+        pub fn get_name(&self) -> &'static str {
+            match self {
+                Algorithms::NAIVE => "Naive",
+                Algorithms::ELEVATOR => "Elevator",
+            }
+        }
+    }
+
+    impl SchedulerConfig {
+        pub fn new(algorithm: Algorithms) -> SchedulerConfig {
+            SchedulerConfig {
+                algorithm: algorithm,
+                pick_up_on_the_way: SchedulerConfig::default_pick_up_on_the_way(),
+            }
+        }
+
+        fn default_pick_up_on_the_way() -> bool {
+            true
+        }
+
+        pub fn build_driver<'a>(&self, device: Box<dyn Device>) -> Box<dyn Driver<'a> + 'a> {
+            match self.algorithm {
+                Algorithms::NAIVE => Box::new(SimpleDriver::new(device)),
+                Algorithms::ELEVATOR => {
+                    Box::new(ElevetorDriver::from_config(device, self.pick_up_on_the_way))
+                }
+            }
+        }
+    }
+
+    pub fn run_simulation(
+        scheduler: SchedulerConfig,
+        device: Box<dyn Device>,
+        arrivals: &[Arrival],
+        verbose: bool,
+        sample_interval: Option<u32>,
+    ) -> Result<SimulationResult, String> {
+        for arrival in arrivals {
+            device.check_task(&arrival.task)?;
+        }
+
+        if verbose {
+            println!("Here is the disk:");
+            device.show();
+        }
+
+        let mut pending_tasks: HashMap<u32, TaskRecord> = HashMap::new();
+        let mut response_times: Vec<u32> = Vec::new();
+        let mut tasks: Vec<TaskRecord> = Vec::new();
+
+        let mut driver = scheduler.build_driver(device);
+        let mut sampler = sample_interval.map(Sampler::new);
+
+        let requests = arrivals.len() as u32;
+        let mut remaining_tasks = 0;
+        let mut added_tasks = 0;
+        let mut time = 0;
+
+        while added_tasks != requests || remaining_tasks != 0 {
+            while added_tasks != requests && arrivals[added_tasks as usize].time <= time {
+                let arrival = &arrivals[added_tasks as usize];
+                let task = &arrival.task;
+
+                driver.add_new_task(task);
+                pending_tasks.insert(*task.get_id(), TaskRecord::new(task, time, arrival.owner));
+
+                added_tasks += 1;
+                remaining_tasks += 1;
+            }
+
+            let serving = driver.get_current_task().map(|task| *task.get_id());
+            let track_before = driver.get_device().get_current_track();
+
+            time += 1;
+            let result = driver.step();
+
+            if let Some(id) = serving {
+                let moved = driver
+                    .get_device()
+                    .get_current_track()
+                    .abs_diff(track_before);
+                if let Some(record) = pending_tasks.get_mut(&id) {
+                    record.seek_distance += moved;
+                }
+            }
+            if let Some(task) = driver.get_current_task() {
+                if let Some(record) = pending_tasks.get_mut(task.get_id()) {
+                    record.start.get_or_insert(time);
+                }
+            }
+
+            if result != 0 {
+                remaining_tasks -= 1;
+                let mut record = pending_tasks.remove(&result).unwrap();
+                record.start.get_or_insert(time);
+                record.completion = time;
+                for pending in pending_tasks.values_mut() {
+                    if pending.arrival < record.arrival {
+                        pending.bypassed += 1;
+                    }
+                }
+
+                response_times.push(record.get_response_time());
+                tasks.push(record);
+
+                if verbose && (added_tasks - remaining_tasks) % 10 == 0 {
+                    println!(
+                        "{}/{} more responses are done.",
+                        added_tasks - remaining_tasks,
+                        requests
+                    );
+                }
+            }
+
+            if let Some(sampler) = sampler.as_mut() {
+                sampler.observe(
+                    time,
+                    remaining_tasks,
+                    added_tasks - remaining_tasks,
+                    driver.get_device(),
+                );
+            }
+        }
+
+        Ok(SimulationResult {
+            response_times: response_times,
+            tasks: tasks,
+            total_time: time,
+            disk: *driver.get_device().get_statistics(),
+            flash: driver.get_device().get_flash_statistics().copied(),
+            samples: sampler.map(|s| s.finish()).unwrap_or_default(),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::disk::disk::{Disk, DiskMetadata};
+
+        fn arrivals(tracks: &[u32]) -> Vec<Arrival> {
+            tracks
+                .iter()
+                .enumerate()
+                .map(|(i, &track)| Arrival {
+                    time: 0,
+                    task: Task::new(i as u32 + 1, track, 90),
+                    owner: 0,
+                })
+                .collect()
+        }
+
+        #[test]
+        fn naive_runs_count_every_seek_and_reversal() {
+            // From track 1 the head goes 1 -> 5 -> 2 -> 8: 4 + 3 + 6 tracks.
+            let arrivals = arrivals(&[5, 2, 8]);
+            let result = run_simulation(
+                SchedulerConfig::new(Algorithms::NAIVE),
+                Box::new(Disk::new(DiskMetadata::from_config(1, 1))),
+                &arrivals,
+                false,
+                None,
+            )
+            .unwrap();
+
+            let order: Vec<u32> = result.tasks.iter().map(|task| task.id).collect();
+            assert_eq!(order, vec![1, 2, 3]);
+            assert_eq!(result.disk.get_tracks_traversed(), 13);
+            assert_eq!(result.disk.get_seeks(), 3);
+            assert_eq!(result.disk.get_direction_reversals(), 2);
+            assert_eq!(result.disk.get_average_seek_distance(), 13.0 / 3.0);
+            assert_eq!(result.disk.get_seek_time(), 13);
+            let seek_distances: Vec<u32> = result.tasks.iter().map(|t| t.seek_distance).collect();
+            assert_eq!(seek_distances, vec![4, 3, 6]);
+        }
+
+        #[test]
+        fn tasks_on_the_current_track_need_no_seek() {
+            let result = run_simulation(
+                SchedulerConfig::new(Algorithms::NAIVE),
+                Box::new(Disk::new(DiskMetadata::from_config(1, 1))),
+                &arrivals(&[1, 1]),
+                false,
+                None,
+            )
+            .unwrap();
+
+            assert_eq!(result.disk.get_seeks(), 0);
+            assert_eq!(result.disk.get_tracks_traversed(), 0);
+            assert_eq!(result.disk.get_average_seek_distance(), 0.0);
+            assert_eq!(result.disk.get_direction_reversals(), 0);
+        }
+    }
+}
@@ -0,0 +1,858 @@
+pub mod sweep {
+    use std::{
+        collections::BTreeMap,
+        fs,
+        path::Path,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            mpsc,
+        },
+        thread,
+    };
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use serde::Deserialize;
+
+    use crate::analysis::{
+        aggregate::aggregate::Analysis,
+        report::report::{report_path, write_report},
+    };
+    use crate::simulation::{
+        checkpoint::checkpoint::{fingerprint, Manifest},
+        results::results::{read_runs, ResultWriter, RunRecord},
+        scenario::scenario::{OutputConfig, OutputFormat, Scenario},
+        workload::workload::WorkloadConfig,
+    };
+
+    #[derive(Deserialize, Copy, Clone, Eq, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    pub enum SweepMode {
+        /// Every combination of the parameter values.
+        GRID,
+        /// The n-th value of every parameter together; all lists must be equally long.
+        LIST,
+        /// `samples` combinations, each value drawn uniformly from its parameter.
+        RANDOM,
+    }
+
+    #[derive(Deserialize, Copy, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct ParameterRange {
+        pub start: f64,
+        pub end: f64,
+        #[serde(default = "ParameterRange::default_step")]
+        pub step: f64,
+    }
+
+    /// One swept value, addressed by its dotted path in the scenario file,
+    /// e.g. `disk.forward_speed` or `scheduler.algorithm`.
+    #[derive(Deserialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct SweepParameter {
+        pub name: String,
+        #[serde(default)]
+        pub values: Vec<toml::Value>,
+        #[serde(default)]
+        pub range: Option<ParameterRange>,
+    }
+
+    #[derive(Deserialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct SweepSpec {
+        /// Scenario file the runs start from, relative to the spec file.
+        #[serde(default)]
+        pub scenario: Option<String>,
+        /// Values that override the base scenario in every run.
+        #[serde(default)]
+        pub base: Option<toml::Table>,
+        #[serde(default = "SweepSpec::default_mode")]
+        pub mode: SweepMode,
+        #[serde(default)]
+        pub samples: Option<u32>,
+        #[serde(default = "SweepSpec::default_repetitions")]
+        pub repetitions: u32,
+        #[serde(default)]
+        pub seed: Option<u64>,
+        #[serde(default)]
+        pub parameters: Vec<SweepParameter>,
+        /// Threads running simulations; defaults to the number of CPU cores.
+        #[serde(default)]
+        pub workers: Option<usize>,
+        #[serde(default = "SweepSpec::default_output")]
+        pub output: OutputConfig,
+    }
+
+    pub struct SweepRun {
+        pub index: usize,
+        pub key: String,
+        /// The key without the repetition.
+        pub configuration: String,
+        pub repetition: u32,
+        pub scenario: Scenario,
+    }
+
+    impl ParameterRange {
+        fn default_step() -> f64 {
+            1.0
+        }
+
+        fn expand(&self) -> Result<Vec<toml::Value>, String> {
+            if self.step <= 0.0 {
+                return Err("range step must be positive".to_owned());
+            }
+
+            let integer = self.start.fract() == 0.0 && self.step.fract() == 0.0;
+            let mut values = Vec::new();
+            let mut value = self.start;
+            while value <= self.end + self.step * 1e-9 {
+                values.push(if integer {
+                    toml::Value::Integer(value as i64)
+                } else {
+                    toml::Value::Float(value)
+                });
+                value += self.step;
+            }
+
+            Ok(values)
+        }
+    }
+
+    impl SweepParameter {
+        pub fn new(name: &str, values: Vec<toml::Value>) -> SweepParameter {
+            SweepParameter {
+                name: name.to_owned(),
+                values: values,
+                range: None,
+            }
+        }
+
+        fn get_values(&self) -> Result<Vec<toml::Value>, String> {
+            let mut values = self.values.clone();
+            if let Some(range) = &self.range {
+                values.extend(range.expand()?);
+            }
+
+            if values.is_empty() {
+                return Err(format!("parameter `{}` has no values", self.name));
+            }
+
+            Ok(values)
+        }
+    }
+
+    fn value_to_string(value: &toml::Value) -> String {
+        match value {
+            toml::Value::String(s) => s.clone(),
+            v => v.to_string(),
+        }
+    }
+
+    /// Mixes the sweep seed with the run key so a run gets the same workload
+    /// however the runs are ordered or distributed over workers.
+    pub fn derive_seed(seed: u64, key: &str) -> u64 {
+        let mut mixed = seed ^ fingerprint(key);
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94d049bb133111eb);
+        mixed ^= mixed >> 31;
+
+        mixed >> 1
+    }
+
+    fn set_path(root: &mut toml::Value, path: &str, value: toml::Value) -> Result<(), String> {
+        let mut current = root;
+        let keys: Vec<&str> = path.split('.').collect();
+
+        for key in keys[..keys.len() - 1].iter() {
+            let table = current
+                .as_table_mut()
+                .ok_or_else(|| format!("`{}` does not name a table in the scenario", path))?;
+            current = table
+                .entry(key.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        }
+
+        let table = current
+            .as_table_mut()
+            .ok_or_else(|| format!("`{}` does not name a table in the scenario", path))?;
+        table.insert(keys[keys.len() - 1].to_owned(), value);
+
+        Ok(())
+    }
+
+    fn has_path(root: &toml::Value, path: &str) -> bool {
+        let mut current = root;
+        for key in path.split('.') {
+            match current.get(key) {
+                Some(value) => current = value,
+                None => return false,
+            }
+        }
+
+        true
+    }
+
+    fn merge(base: &mut toml::Value, overrides: &toml::Table) {
+        for (key, value) in overrides.iter() {
+            match (base.get_mut(key), value) {
+                (Some(existing @ toml::Value::Table(_)), toml::Value::Table(table)) => {
+                    merge(existing, table);
+                }
+                _ => {
+                    if let Some(table) = base.as_table_mut() {
+                        table.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    impl SweepSpec {
+        fn default_mode() -> SweepMode {
+            SweepMode::GRID
+        }
+
+        fn default_repetitions() -> u32 {
+            1
+        }
+
+        fn default_output() -> OutputConfig {
+            let mut output = OutputConfig::default();
+            output.format = OutputFormat::JSONL;
+
+            output
+        }
+
+        pub fn default() -> SweepSpec {
+            SweepSpec {
+                scenario: None,
+                base: None,
+                mode: SweepSpec::default_mode(),
+                samples: None,
+                repetitions: SweepSpec::default_repetitions(),
+                seed: None,
+                parameters: Vec::new(),
+                workers: None,
+                output: SweepSpec::default_output(),
+            }
+        }
+
+        /// The grid the "Log" menu entry has always run, written in the legacy format.
+        pub fn legacy() -> SweepSpec {
+            let integers =
+                |values: &[i64]| values.iter().map(|&v| toml::Value::Integer(v)).collect();
+            let mut spec = SweepSpec::default();
+
+            spec.parameters = vec![
+                SweepParameter::new(
+                    "scheduler.algorithm",
+                    vec![
+                        toml::Value::String("naive".to_owned()),
+                        toml::Value::String("elevator".to_owned()),
+                    ],
+                ),
+                SweepParameter::new("disk.forward_speed", integers(&[1, 5, 10, 15, 20, 25])),
+                SweepParameter::new("disk.spin_speed", integers(&[25, 50, 100, 250, 500])),
+                SweepParameter::new("disk.tracks", integers(&[1000, 5000, 10000, 50000])),
+                SweepParameter::new("workload.requests", integers(&[100, 500, 1000, 5000])),
+            ];
+            spec.repetitions = 10;
+            spec.output = OutputConfig::default();
+            spec.output.report = false;
+
+            spec
+        }
+
+        pub fn from_file(path: &str) -> Result<SweepSpec, String> {
+            let content = fs::read_to_string(path)
+                .map_err(|e| format!("Could not read sweep spec {}: {}", path, e))?;
+            let mut spec: SweepSpec = toml::from_str(&content)
+                .map_err(|e| format!("{}: invalid sweep spec: {}", path, e))?;
+
+            if let Some(scenario) = &spec.scenario {
+                if let Some(directory) = Path::new(path).parent() {
+                    spec.scenario = Some(directory.join(scenario).to_string_lossy().into_owned());
+                }
+            }
+
+            Ok(spec)
+        }
+
+        fn base_scenario(&self) -> Result<toml::Value, String> {
+            let mut base = match &self.scenario {
+                Some(path) => Scenario::from_file(path)?.to_value(),
+                None => Scenario::default().to_value(),
+            };
+
+            if let Some(overrides) = &self.base {
+                merge(&mut base, overrides);
+            }
+
+            Ok(base)
+        }
+
+        fn combinations(&self, base_seed: u64) -> Result<Vec<Vec<toml::Value>>, String> {
+            let values: Vec<Vec<toml::Value>> = self
+                .parameters
+                .iter()
+                .map(|parameter| parameter.get_values())
+                .collect::<Result<_, _>>()?;
+
+            match self.mode {
+                SweepMode::GRID => {
+                    let mut combinations: Vec<Vec<toml::Value>> = vec![Vec::new()];
+                    for parameter_values in values.iter() {
+                        let mut next =
+                            Vec::with_capacity(combinations.len() * parameter_values.len());
+                        for combination in combinations.iter() {
+                            for value in parameter_values.iter() {
+                                let mut extended = combination.clone();
+                                extended.push(value.clone());
+                                next.push(extended);
+                            }
+                        }
+                        combinations = next;
+                    }
+
+                    Ok(combinations)
+                }
+                SweepMode::LIST => {
+                    let length = values.first().map(|v| v.len()).unwrap_or(1);
+                    if values.iter().any(|v| v.len() != length) {
+                        return Err(
+                            "every parameter of a list sweep needs the same number of values"
+                                .to_owned(),
+                        );
+                    }
+
+                    Ok((0..length)
+                        .map(|i| values.iter().map(|v| v[i].clone()).collect())
+                        .collect())
+                }
+                SweepMode::RANDOM => {
+                    let samples = self
+                        .samples
+                        .ok_or_else(|| "a random sweep needs `samples`".to_owned())?;
+                    let mut rng = StdRng::seed_from_u64(self.seed.unwrap_or(base_seed));
+
+                    Ok((0..samples)
+                        .map(|_| {
+                            values
+                                .iter()
+                                .map(|v| v[rng.gen_range(0..v.len())].clone())
+                                .collect()
+                        })
+                        .collect())
+                }
+            }
+        }
+
+        /// Turns the spec into the list of scenarios to simulate. `base_seed`
+        /// only matters when neither the spec nor the scenario sets a seed.
+        pub fn expand(&self, base_seed: u64) -> Result<Vec<SweepRun>, String> {
+            let base = self.base_scenario()?;
+            let mut runs = Vec::new();
+            // Values equal to their default are left out of the written
+            // scenario, so a parameter only needs to show up in one run.
+            let mut written_parameters = vec![false; self.parameters.len()];
+
+            for combination in self.combinations(base_seed)? {
+                let mut value = base.clone();
+                let mut key = String::new();
+
+                for (parameter, assigned) in self.parameters.iter().zip(combination) {
+                    key.push_str(&format!(
+                        "{}={},",
+                        parameter.name,
+                        value_to_string(&assigned)
+                    ));
+                    set_path(&mut value, &parameter.name, assigned)?;
+                }
+
+                let scenario = Scenario::from_value(value)
+                    .map_err(|e| format!("run `{}`: {}", key.trim_end_matches(','), e))?;
+                let written = scenario.to_value();
+                for (parameter, seen) in self.parameters.iter().zip(written_parameters.iter_mut()) {
+                    *seen |= has_path(&written, &parameter.name);
+                }
+
+                let configuration = key.trim_end_matches(',').to_owned();
+                for repetition in 0..self.repetitions {
+                    let index = runs.len();
+                    let key = format!("{}repetition={}", key, repetition);
+                    let mut scenario = scenario.clone();
+                    scenario.output.timeseries = None;
+                    scenario.output.charts = None;
+                    let seed = self.seed.or(scenario.workload.seed).unwrap_or(base_seed);
+                    scenario.workload.seed = Some(derive_seed(seed, &key));
+
+                    runs.push(SweepRun {
+                        index: index,
+                        key: key,
+                        configuration: configuration.clone(),
+                        repetition: repetition,
+                        scenario: scenario,
+                    });
+                }
+            }
+
+            for (parameter, seen) in self.parameters.iter().zip(written_parameters) {
+                if !seen {
+                    return Err(format!("`{}` is not a scenario parameter", parameter.name));
+                }
+            }
+
+            Ok(runs)
+        }
+
+        pub fn resolve_workers(&self) -> usize {
+            match self.workers {
+                Some(workers) => workers.max(1),
+                None => thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1),
+            }
+        }
+
+        /// Identifies the expanded runs and the output options deciding which
+        /// files are written and what goes in them, so a manifest is only
+        /// resumed by the sweep that wrote it.
+        fn fingerprint_runs(&self, runs: &[SweepRun]) -> u64 {
+            let mut text = format!(
+                "{} tasks={}",
+                self.output.format.get_extension(),
+                self.output.tasks
+            );
+            for run in runs.iter() {
+                text.push_str(&run.key);
+                text.push_str(&run.scenario.to_value().to_string());
+            }
+
+            fingerprint(&text)
+        }
+
+        /// Runs every expanded scenario on `workers` threads and returns the
+        /// path of the results. Results are written in expansion order no
+        /// matter which worker finishes first.
+        ///
+        /// When the output already has a manifest from the same spec, runs it
+        /// lists are skipped and the rest are appended; `fresh` starts over.
+        pub fn run(&self, verbose: bool, fresh: bool) -> Result<String, String> {
+            let path = self.output.resolve_path();
+            let existing = if fresh { None } else { Manifest::open(&path)? };
+            let base_seed = match &existing {
+                Some(manifest) => manifest.get_seed(),
+                None => WorkloadConfig::new(0, None).resolve_seed(),
+            };
+
+            let runs = self.expand(base_seed)?;
+            let fingerprint = self.fingerprint_runs(&runs);
+            let (mut manifest, mut writer) = match existing {
+                Some(manifest) if manifest.count_completed() > 0 => {
+                    if manifest.get_fingerprint() != fingerprint {
+                        return Err(format!(
+                            "{} was written by a different sweep; choose another output or start over with --fresh",
+                            path
+                        ));
+                    }
+
+                    let writer = ResultWriter::resume(
+                        &path,
+                        self.output.format,
+                        self.output.tasks,
+                        manifest.get_offsets(),
+                    )?;
+                    (manifest, writer)
+                }
+                _ => {
+                    let writer =
+                        ResultWriter::create(&path, self.output.format, self.output.tasks)?;
+                    (Manifest::create(&path, fingerprint, base_seed)?, writer)
+                }
+            };
+
+            if manifest.count_completed() > 0 {
+                println!(
+                    "Resuming: {}/{} runs were already done",
+                    manifest.count_completed(),
+                    runs.len()
+                );
+            }
+
+            let runs: Vec<&SweepRun> = runs
+                .iter()
+                .filter(|run| !manifest.is_completed(&run.key))
+                .collect();
+            let workers = self.resolve_workers().min(runs.len().max(1));
+
+            println!("Running {} simulations on {} workers", runs.len(), workers);
+
+            let next_run = AtomicUsize::new(0);
+            thread::scope(|scope| {
+                let (sender, receiver) =
+                    mpsc::channel::<(usize, Result<(Scenario, RunRecord), String>)>();
+
+                for _ in 0..workers {
+                    let sender = sender.clone();
+                    let next_run = &next_run;
+                    let runs = &runs;
+
+                    scope.spawn(move || loop {
+                        let index = next_run.fetch_add(1, Ordering::Relaxed);
+                        if index >= runs.len() {
+                            break;
+                        }
+
+                        let run = runs[index];
+                        let mut scenario = run.scenario.clone();
+                        let finished = scenario.run(false).map(|result| {
+                            let record = RunRecord::new(
+                                run.key.clone(),
+                                run.configuration.clone(),
+                                run.repetition,
+                                &scenario,
+                                result,
+                            );
+                            (scenario, record)
+                        });
+
+                        // The receiver is gone once a run failed.
+                        if sender.send((index, finished)).is_err() {
+                            break;
+                        }
+                    });
+                }
+                drop(sender);
+
+                let mut pending: BTreeMap<usize, (Scenario, RunRecord)> = BTreeMap::new();
+                let mut next_to_write = 0;
+                for (index, finished) in receiver {
+                    pending.insert(
+                        index,
+                        finished.map_err(|e| format!("{}: {}", runs[index].key, e))?,
+                    );
+
+                    while let Some((scenario, record)) = pending.remove(&next_to_write) {
+                        writer.write_run(&scenario, &record)?;
+                        manifest.record(&record.summary.key, writer.sync()?)?;
+                        next_to_write += 1;
+
+                        if verbose {
+                            println!(
+                                "{}/{} runs are done: {}",
+                                next_to_write,
+                                runs.len(),
+                                record.summary.key
+                            );
+                        }
+                    }
+                }
+
+                Ok::<(), String>(())
+            })?;
+            writer.finish()?;
+
+            if self.output.report {
+                let records = read_runs(std::slice::from_ref(&path), self.output.tasks)?;
+                let report = report_path(&path);
+                write_report(&report, &path, &records, &Analysis::default())?;
+                println!("Report written to {}", report);
+            }
+
+            Ok(path)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::simulation::simulation::simulation::Algorithms;
+
+        fn spec(text: &str) -> SweepSpec {
+            toml::from_str(text).unwrap()
+        }
+
+        fn keys(runs: &[SweepRun]) -> Vec<&str> {
+            runs.iter().map(|run| run.key.as_str()).collect()
+        }
+
+        #[test]
+        fn derived_seeds_depend_on_the_seed_and_the_key_only() {
+            assert_eq!(
+                derive_seed(7, "a=1,repetition=0"),
+                derive_seed(7, "a=1,repetition=0")
+            );
+            assert_ne!(
+                derive_seed(7, "a=1,repetition=0"),
+                derive_seed(7, "a=1,repetition=1")
+            );
+            assert_ne!(
+                derive_seed(7, "a=1,repetition=0"),
+                derive_seed(8, "a=1,repetition=0")
+            );
+            // Seeds are written to signed integer columns too.
+            assert!(derive_seed(u64::MAX, "") <= i64::MAX as u64);
+        }
+
+        #[test]
+        fn grid_expands_every_combination_and_repetition() {
+            let runs = spec(
+                r#"
+                repetitions = 2
+                seed = 5
+
+                [[parameters]]
+                name = "scheduler.algorithm"
+                values = ["naive", "elevator"]
+
+                [[parameters]]
+                name = "disk.forward_speed"
+                range = { start = 1, end = 3, step = 2 }
+                "#,
+            )
+            .expand(0)
+            .unwrap();
+
+            assert_eq!(
+                keys(&runs),
+                vec![
+                    "scheduler.algorithm=naive,disk.forward_speed=1,repetition=0",
+                    "scheduler.algorithm=naive,disk.forward_speed=1,repetition=1",
+                    "scheduler.algorithm=naive,disk.forward_speed=3,repetition=0",
+                    "scheduler.algorithm=naive,disk.forward_speed=3,repetition=1",
+                    "scheduler.algorithm=elevator,disk.forward_speed=1,repetition=0",
+                    "scheduler.algorithm=elevator,disk.forward_speed=1,repetition=1",
+                    "scheduler.algorithm=elevator,disk.forward_speed=3,repetition=0",
+                    "scheduler.algorithm=elevator,disk.forward_speed=3,repetition=1",
+                ]
+            );
+            assert_eq!(runs[3].repetition, 1);
+            assert_eq!(runs[3].index, 3);
+            assert_eq!(*runs[3].scenario.disk.metadata.get_forward_speed(), 3);
+            assert!(runs[4].scenario.scheduler.algorithm == Algorithms::ELEVATOR);
+            assert_eq!(
+                runs[0].scenario.workload.seed,
+                runs[0].scenario.workload.seed
+            );
+            assert_ne!(
+                runs[0].scenario.workload.seed,
+                runs[2].scenario.workload.seed
+            );
+        }
+
+        #[test]
+        fn a_run_keeps_its_seed_when_other_runs_change() {
+            let all = spec(
+                r#"
+                [[parameters]]
+                name = "disk.forward_speed"
+                values = [1, 2, 3]
+                "#,
+            )
+            .expand(11)
+            .unwrap();
+            let one = spec(
+                r#"
+                [[parameters]]
+                name = "disk.forward_speed"
+                values = [3]
+                "#,
+            )
+            .expand(11)
+            .unwrap();
+
+            assert_eq!(all[2].key, one[0].key);
+            assert_eq!(all[2].scenario.workload.seed, one[0].scenario.workload.seed);
+        }
+
+        #[test]
+        fn list_sweeps_pair_up_values() {
+            let list = r#"
+                mode = "list"
+
+                [[parameters]]
+                name = "disk.forward_speed"
+                values = [1, 2]
+
+                [[parameters]]
+                name = "disk.spin_speed"
+                values = [10, 20]
+                "#;
+            let runs = spec(list).expand(0).unwrap();
+            assert_eq!(
+                keys(&runs),
+                vec![
+                    "disk.forward_speed=1,disk.spin_speed=10,repetition=0",
+                    "disk.forward_speed=2,disk.spin_speed=20,repetition=0",
+                ]
+            );
+
+            let uneven = list.replace("[10, 20]", "[10, 20, 30]");
+            assert!(spec(&uneven).expand(0).is_err());
+        }
+
+        #[test]
+        fn random_sweeps_draw_reproducible_samples() {
+            let random = r#"
+                mode = "random"
+                samples = 4
+
+                [[parameters]]
+                name = "disk.forward_speed"
+                range = { start = 1, end = 100 }
+                "#;
+            let first = spec(random).expand(9).unwrap();
+            let again = spec(random).expand(9).unwrap();
+
+            assert_eq!(first.len(), 4);
+            assert_eq!(keys(&first), keys(&again));
+
+            let without_samples = random.replace("samples = 4", "");
+            assert!(spec(&without_samples).expand(9).is_err());
+        }
+
+        #[test]
+        fn ranges_keep_integers_and_include_their_end() {
+            let integers = ParameterRange {
+                start: 1.0,
+                end: 7.0,
+                step: 3.0,
+            };
+            let floats = ParameterRange {
+                start: 0.1,
+                end: 0.3,
+                step: 0.1,
+            };
+
+            assert_eq!(
+                integers.expand().unwrap(),
+                vec![
+                    toml::Value::Integer(1),
+                    toml::Value::Integer(4),
+                    toml::Value::Integer(7)
+                ]
+            );
+            assert_eq!(floats.expand().unwrap().len(), 3);
+            assert!(ParameterRange {
+                step: 0.0,
+                ..integers
+            }
+            .expand()
+            .is_err());
+        }
+
+        #[test]
+        fn workers_write_the_runs_in_expansion_order() {
+            let dir = tempfile::tempdir().unwrap();
+            let output = |workers: usize| -> String {
+                let path = dir.path().join(format!("{}.jsonl", workers));
+                let mut spec = spec(
+                    r#"
+                    repetitions = 3
+                    seed = 1
+
+                    [base.disk]
+                    tracks = 100
+                    spin_speed = 5
+
+                    [base.workload]
+                    requests = 20
+                    arrival = { kind = "periodic", interval = 100 }
+
+                    [[parameters]]
+                    name = "disk.forward_speed"
+                    values = [1, 2, 3]
+                    "#,
+                );
+                spec.workers = Some(workers);
+                spec.output.path = Some(path.to_str().unwrap().to_owned());
+
+                fs::read_to_string(spec.run(false, true).unwrap()).unwrap()
+            };
+
+            let sequential = output(1);
+            assert_eq!(sequential.lines().count(), 9);
+            assert_eq!(output(4), sequential);
+        }
+
+        #[test]
+        fn fingerprints_change_with_the_files_written() {
+            let mut spec = spec(
+                r#"
+                [[parameters]]
+                name = "disk.forward_speed"
+                values = [1, 2]
+                "#,
+            );
+            let runs = spec.expand(0).unwrap();
+            let first = spec.fingerprint_runs(&runs);
+            assert_eq!(spec.fingerprint_runs(&runs), first);
+
+            spec.output.tasks = !spec.output.tasks;
+            let without_tasks = spec.fingerprint_runs(&runs);
+            assert_ne!(without_tasks, first);
+
+            spec.output.format = OutputFormat::LEGACY;
+            assert_ne!(spec.fingerprint_runs(&runs), without_tasks);
+            assert_ne!(
+                spec.fingerprint_runs(&runs[..1]),
+                spec.fingerprint_runs(&runs)
+            );
+        }
+
+        #[test]
+        fn an_interrupted_sweep_resumes_where_it_stopped() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("sweep.jsonl");
+            let output = path.to_str().unwrap().to_owned();
+            let mut spec = spec(
+                r#"
+                seed = 3
+
+                [base.disk]
+                tracks = 100
+                spin_speed = 5
+
+                [base.workload]
+                requests = 10
+                arrival = { kind = "periodic", interval = 100 }
+
+                [[parameters]]
+                name = "disk.forward_speed"
+                values = [1, 2, 3, 4]
+                "#,
+            );
+            spec.workers = Some(1);
+            spec.output.path = Some(output.clone());
+
+            spec.run(false, true).unwrap();
+            let complete = fs::read_to_string(&path).unwrap();
+
+            // Forget the last run and leave half of its record behind.
+            let manifest_path = Manifest::path_for(&output);
+            let manifest = fs::read_to_string(&manifest_path).unwrap();
+            let manifest = &manifest[..manifest.trim_end().rfind('\n').unwrap() + 1];
+            fs::write(&manifest_path, manifest).unwrap();
+            fs::write(&path, &complete[..complete.len() - 10]).unwrap();
+
+            spec.run(false, false).unwrap();
+            assert_eq!(fs::read_to_string(&path).unwrap(), complete);
+
+            spec.parameters[0].values.pop();
+            assert!(spec.run(false, false).is_err());
+        }
+
+        #[test]
+        fn unknown_parameters_are_rejected() {
+            let runs = spec(
+                r#"
+                [[parameters]]
+                name = "disk.warp_speed"
+                values = [1]
+                "#,
+            )
+            .expand(0);
+
+            assert!(runs.is_err());
+        }
+    }
+}
@@ -0,0 +1,203 @@
+pub mod timeseries {
+    use std::fs::File;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::disk::{device::device::Device, hardware_manager::hardware_manager::DiskState};
+
+    /// The state of a simulation at one sampled tick.
+    #[derive(Serialize, Deserialize)]
+    pub struct Sample {
+        pub time: u32,
+        /// Requests that arrived but have not completed yet.
+        pub queue_length: u32,
+        pub state: String,
+        pub track: u32,
+        pub completed: u32,
+        /// Share of the ticks since the previous sample the disk was not idle.
+        pub utilization: f64,
+    }
+
+    /// Takes a `Sample` every `interval` ticks.
+    pub struct Sampler {
+        interval: u32,
+        last_idle_time: u32,
+        samples: Vec<Sample>,
+    }
+
+    fn state_name(state: &DiskState) -> &'static str {
+        match state {
+            DiskState::STOP => "STOP",
+            DiskState::READ(_) => "READ",
+            DiskState::WRITE(_) => "WRITE",
+            DiskState::MOVE(_) => "MOVE",
+            DiskState::SWITCH(_) => "SWITCH",
+        }
+    }
+
+    impl Sampler {
+        pub fn new(interval: u32) -> Sampler {
+            Sampler {
+                interval: interval.max(1),
+                last_idle_time: 0,
+                samples: Vec::new(),
+            }
+        }
+
+        pub fn observe(
+            &mut self,
+            time: u32,
+            queue_length: u32,
+            completed: u32,
+            device: &dyn Device,
+        ) {
+            if !time.is_multiple_of(self.interval) {
+                return;
+            }
+
+            // A device may idle longer than an interval between two samples,
+            // or report less idle time than before when it was swapped.
+            let idle_time = device.get_statistics().get_idle_time();
+            let idle = idle_time.saturating_sub(self.last_idle_time);
+            self.last_idle_time = idle_time;
+            let busy = self.interval.saturating_sub(idle);
+
+            self.samples.push(Sample {
+                time: time,
+                queue_length: queue_length,
+                state: state_name(device.get_state()).to_owned(),
+                track: device.get_current_track(),
+                completed: completed,
+                utilization: (busy as f64 / self.interval as f64).clamp(0.0, 1.0),
+            });
+        }
+
+        pub fn finish(self) -> Vec<Sample> {
+            self.samples
+        }
+    }
+
+    /// Writes samples as CSV, one row per sampled tick.
+    pub fn write_time_series(path: &str, samples: &[Sample]) -> Result<(), String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Could not create time series {}: {}", path, e))?;
+        let mut writer = csv::Writer::from_writer(file);
+
+        for sample in samples.iter() {
+            writer
+                .serialize(sample)
+                .map_err(|e| format!("Could not write time series {}: {}", path, e))?;
+        }
+
+        writer
+            .flush()
+            .map_err(|e| format!("Could not write time series {}: {}", path, e))
+    }
+
+    pub fn read_time_series(path: &str) -> Result<Vec<Sample>, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Could not open time series {}: {}", path, e))?;
+
+        csv::Reader::from_reader(file)
+            .deserialize()
+            .map(|sample| sample.map_err(|e| format!("{}: invalid time series row: {}", path, e)))
+            .collect()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::{
+            device::device::Device,
+            disk::disk::{Disk, DiskMetadata},
+            driver::driver::Task,
+        };
+        use crate::simulation::{
+            simulation::simulation::{run_simulation, Algorithms, SchedulerConfig},
+            workload::workload::Arrival,
+        };
+
+        /// Two requests on track 3, at ticks 0 and 100, sampled every 10 ticks.
+        fn samples() -> Vec<Sample> {
+            let arrivals: Vec<Arrival> = [(0, 10), (100, 20)]
+                .iter()
+                .enumerate()
+                .map(|(i, &(time, angle))| Arrival {
+                    time: time,
+                    task: Task::new(i as u32 + 1, 3, angle),
+                    owner: 0,
+                })
+                .collect();
+
+            run_simulation(
+                SchedulerConfig::new(Algorithms::NAIVE),
+                Box::new(Disk::new(DiskMetadata::from_config(1, 1))),
+                &arrivals,
+                false,
+                Some(10),
+            )
+            .unwrap()
+            .samples
+        }
+
+        #[test]
+        fn samples_follow_the_queue_and_the_disk() {
+            let samples = samples();
+            let times: Vec<u32> = samples.iter().map(|s| s.time).collect();
+            assert_eq!(times, (1..=11).map(|i| i * 10).collect::<Vec<u32>>());
+
+            // The first request seeks two tracks and rotates ten degrees,
+            // finishing at tick 15; the disk is idle until the second arrives.
+            assert_eq!(samples[0].queue_length, 1);
+            assert_eq!(samples[0].state, "READ");
+            assert_eq!(samples[0].completed, 0);
+            assert_eq!(samples[0].utilization, 0.8);
+            assert_eq!(samples[1].queue_length, 0);
+            assert_eq!(samples[1].completed, 1);
+            assert_eq!(samples[1].utilization, 0.4);
+            for sample in samples[2..10].iter() {
+                assert_eq!(sample.queue_length, 0);
+                assert_eq!(sample.state, "STOP");
+                assert_eq!(sample.track, 3);
+                assert_eq!(sample.utilization, 0.0);
+            }
+            assert_eq!(samples[10].queue_length, 1);
+            assert_eq!(samples[10].utilization, 0.8);
+        }
+
+        #[test]
+        fn utilization_stays_between_zero_and_one() {
+            let mut sampler = Sampler::new(10);
+
+            // Idle for longer than the interval since the last sample.
+            let mut idle = Disk::new(DiskMetadata::from_config(1, 1));
+            for _ in 0..25 {
+                idle.idle();
+            }
+            sampler.observe(10, 0, 0, &idle);
+
+            // Less idle time than at the previous sample.
+            let fresh = Disk::new(DiskMetadata::from_config(1, 1));
+            sampler.observe(20, 0, 0, &fresh);
+
+            let utilization: Vec<f64> = sampler.finish().iter().map(|s| s.utilization).collect();
+            assert_eq!(utilization, vec![0.0, 1.0]);
+        }
+
+        #[test]
+        fn time_series_are_written_as_csv() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("series.csv");
+            write_time_series(path.to_str().unwrap(), &samples()).unwrap();
+
+            let content = std::fs::read_to_string(&path).unwrap();
+            let lines: Vec<&str> = content.lines().collect();
+            assert_eq!(lines.len(), 12);
+            assert_eq!(
+                lines[0],
+                "time,queue_length,state,track,completed,utilization"
+            );
+            assert_eq!(lines[1], "10,1,READ,3,0,0.8");
+        }
+    }
+}
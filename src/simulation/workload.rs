@@ -0,0 +1,669 @@
+pub mod workload {
+    use std::{
+        fs::{self, File},
+        io::Write,
+    };
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use serde::{Deserialize, Serialize};
+
+    use crate::disk::{
+        driver::driver::{Operation, Task},
+        geometry::geometry::Geometry,
+    };
+
+    #[derive(Serialize, Deserialize, Copy, Clone)]
+    #[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+    pub enum ArrivalProcess {
+        BERNOULLI { probability: f64 },
+        PERIODIC { interval: u32 },
+    }
+
+    #[derive(Serialize, Deserialize, Copy, Clone)]
+    #[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+    pub enum SpatialDistribution {
+        UNIFORM,
+        HOTSPOT {
+            center: u32,
+            width: u32,
+            probability: f64,
+        },
+    }
+
+    /// Relative weights of the operations requests ask for; missing ones are 0.
+    #[derive(Serialize, Deserialize, Copy, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct OperationMix {
+        #[serde(default)]
+        pub read: f64,
+        #[serde(default)]
+        pub write: f64,
+        #[serde(default)]
+        pub flush: f64,
+        #[serde(default)]
+        pub discard: f64,
+        /// Share of the writes, between 0 and 1, with forced unit access.
+        #[serde(default, skip_serializing_if = "OperationMix::is_zero")]
+        pub fua: f64,
+    }
+
+    #[derive(Serialize, Deserialize, Clone)]
+    #[serde(deny_unknown_fields)]
+    pub struct WorkloadConfig {
+        pub requests: u32,
+        #[serde(default)]
+        pub seed: Option<u64>,
+        #[serde(default)]
+        pub arrival: Option<ArrivalProcess>,
+        #[serde(default = "SpatialDistribution::default")]
+        pub spatial: SpatialDistribution,
+        /// Number of clients the requests are spread over uniformly.
+        #[serde(default = "WorkloadConfig::default_owners")]
+        pub owners: u32,
+        /// Sectors each request reads or writes.
+        #[serde(
+            default = "WorkloadConfig::default_length",
+            skip_serializing_if = "WorkloadConfig::is_single_sector"
+        )]
+        pub length: u32,
+        /// Every request is a read without a mix.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub mix: Option<OperationMix>,
+    }
+
+    pub struct Arrival {
+        pub time: u32,
+        pub task: Task,
+        /// The client that issued the request, below `WorkloadConfig::owners`.
+        pub owner: u32,
+    }
+
+    impl ArrivalProcess {
+        /// The arrival rate the interactive menu has always used.
+        pub fn legacy(requests: u32) -> ArrivalProcess {
+            ArrivalProcess::BERNOULLI {
+                probability: requests as f64 / 10000000.0,
+            }
+        }
+
+        fn next_gap(&self, rng: &mut StdRng) -> u32 {
+            match *self {
+                ArrivalProcess::BERNOULLI { probability } => {
+                    if probability >= 1.0 {
+                        return 1;
+                    }
+
+                    let u: f64 = 1.0 - rng.gen::<f64>();
+                    (u.ln() / (1.0 - probability).ln()).ceil().max(1.0) as u32
+                }
+                ArrivalProcess::PERIODIC { interval } => interval.max(1),
+            }
+        }
+
+        pub fn validate(&self) -> Result<(), String> {
+            match *self {
+                ArrivalProcess::BERNOULLI { probability } => {
+                    if !(probability > 0.0 && probability <= 1.0) {
+                        return Err(
+                            "workload.arrival.probability must be above 0 and at most 1".to_owned()
+                        );
+                    }
+                }
+                ArrivalProcess::PERIODIC { interval } => {
+                    if interval == 0 {
+                        return Err("workload.arrival.interval must be at least 1".to_owned());
+                    }
+                }
+            }
+
+            Ok(())
+        }
+    }
+
+    impl SpatialDistribution {
+        pub fn default() -> SpatialDistribution {
+            SpatialDistribution::UNIFORM
+        }
+
+        /// Checks the distribution against a disk of `max_track` tracks.
+        pub fn validate(&self, max_track: u32) -> Result<(), String> {
+            if let SpatialDistribution::HOTSPOT {
+                center,
+                probability,
+                ..
+            } = *self
+            {
+                if center == 0 || center > max_track {
+                    return Err(format!(
+                        "workload.spatial.center must be between 1 and disk.tracks ({})",
+                        max_track
+                    ));
+                }
+                if !(0.0..=1.0).contains(&probability) {
+                    return Err("workload.spatial.probability must be between 0 and 1".to_owned());
+                }
+            }
+
+            Ok(())
+        }
+
+        fn sample_track(&self, rng: &mut StdRng, max_track: u32) -> u32 {
+            match *self {
+                SpatialDistribution::UNIFORM => rng.gen_range(1..=max_track),
+                SpatialDistribution::HOTSPOT {
+                    center,
+                    width,
+                    probability,
+                } => {
+                    if rng.gen::<f64>() < probability {
+                        let low = center.saturating_sub(width / 2).clamp(1, max_track);
+                        let high = center.saturating_add(width / 2).min(max_track).max(low);
+                        rng.gen_range(low..=high)
+                    } else {
+                        rng.gen_range(1..=max_track)
+                    }
+                }
+            }
+        }
+    }
+
+    impl OperationMix {
+        /// Reads with `writes` of the requests, between 0 and 1, writes instead.
+        pub fn from_write_ratio(writes: f64) -> OperationMix {
+            OperationMix {
+                read: 1.0 - writes,
+                write: writes,
+                flush: 0.0,
+                discard: 0.0,
+                fua: 0.0,
+            }
+        }
+
+        fn is_zero(value: &f64) -> bool {
+            *value == 0.0
+        }
+
+        fn get_weights(&self) -> [f64; 4] {
+            [self.read, self.write, self.flush, self.discard]
+        }
+
+        pub fn validate(&self) -> Result<(), String> {
+            let weights = self.get_weights();
+            if weights
+                .iter()
+                .any(|weight| !weight.is_finite() || *weight < 0.0)
+            {
+                return Err("workload.mix weights must be finite and not negative".to_owned());
+            }
+            if weights.iter().sum::<f64>() <= 0.0 {
+                return Err("workload.mix needs at least one operation with a weight".to_owned());
+            }
+            if !(0.0..=1.0).contains(&self.fua) {
+                return Err("workload.mix.fua must be between 0 and 1".to_owned());
+            }
+
+            Ok(())
+        }
+
+        fn sample(&self, rng: &mut StdRng) -> Operation {
+            let weights = self.get_weights();
+            let mut draw = rng.gen::<f64>() * weights.iter().sum::<f64>();
+            for (operation, weight) in Operation::ALL.iter().zip(weights.iter()) {
+                if draw < *weight {
+                    return *operation;
+                }
+                draw -= weight;
+            }
+
+            // Rounding can leave the draw just past the last weight.
+            *Operation::ALL
+                .iter()
+                .zip(weights.iter())
+                .rev()
+                .find(|(_, weight)| **weight > 0.0)
+                .unwrap()
+                .0
+        }
+    }
+
+    impl WorkloadConfig {
+        pub fn new(requests: u32, seed: Option<u64>) -> WorkloadConfig {
+            WorkloadConfig {
+                requests: requests,
+                seed: seed,
+                arrival: None,
+                spatial: SpatialDistribution::default(),
+                owners: WorkloadConfig::default_owners(),
+                length: WorkloadConfig::default_length(),
+                mix: None,
+            }
+        }
+
+        fn default_owners() -> u32 {
+            1
+        }
+
+        fn default_length() -> u32 {
+            1
+        }
+
+        fn is_single_sector(length: &u32) -> bool {
+            *length == 1
+        }
+
+        pub fn get_arrival(&self) -> ArrivalProcess {
+            self.arrival
+                .unwrap_or_else(|| ArrivalProcess::legacy(self.requests))
+        }
+
+        /// Returns the configured seed, or draws a fresh one when the workload has none.
+        /// Drawn seeds stay below 2^63 so they can be written back into TOML.
+        pub fn resolve_seed(&self) -> u64 {
+            self.seed
+                .unwrap_or_else(|| rand::thread_rng().gen::<u64>() >> 1)
+        }
+
+        /// Requests spread over `max_track` tracks, or cylinders, and `heads` surfaces.
+        pub fn generate(
+            &self,
+            max_track: u32,
+            heads: u32,
+            seed: u64,
+        ) -> Result<Vec<Arrival>, String> {
+            let mut rng = StdRng::seed_from_u64(seed);
+            // Owners, heads and operations come from their own streams so
+            // that adding them leaves the arrivals of an existing seed unchanged.
+            let mut owner_rng = StdRng::seed_from_u64(seed ^ 0x6f776e657273);
+            let mut head_rng = StdRng::seed_from_u64(seed ^ 0x6865616473);
+            let mut operation_rng = StdRng::seed_from_u64(seed ^ 0x6f7073);
+            let arrival = self.get_arrival();
+            let mut arrivals = Vec::with_capacity(self.requests as usize);
+            let mut time = 0;
+
+            for task_id in 1..=self.requests {
+                time += arrival.next_gap(&mut rng);
+                let track = self.spatial.sample_track(&mut rng, max_track);
+                let angle = rng.gen_range(0..=359);
+
+                let owner = if self.owners > 1 {
+                    owner_rng.gen_range(0..self.owners)
+                } else {
+                    0
+                };
+                let head = if heads > 1 {
+                    head_rng.gen_range(0..heads)
+                } else {
+                    0
+                };
+
+                let mut task = Task::with_length(task_id, track, head, angle, self.length)?;
+                if let Some(mix) = &self.mix {
+                    task.set_operation(mix.sample(&mut operation_rng));
+                    if task.get_operation() == Operation::WRITE && mix.fua > 0.0 {
+                        task.set_fua(operation_rng.gen::<f64>() < mix.fua);
+                    }
+                }
+
+                arrivals.push(Arrival {
+                    time: time - 1,
+                    task: task,
+                    owner: owner,
+                });
+            }
+
+            Ok(arrivals)
+        }
+    }
+
+    /// How traces name a write with forced unit access.
+    const FUA: &str = "fua";
+
+    fn get_trace_operation(task: &Task) -> &'static str {
+        if task.is_fua() {
+            FUA
+        } else {
+            task.get_operation().get_name()
+        }
+    }
+
+    /// Writes arrivals as a trace that `read_trace` can replay:
+    /// `time,lba,length,owner` when every request was addressed by logical
+    /// block, `time,track,angle,owner` otherwise, with `head` and `length`
+    /// columns when any request needs them and a last `operation` column
+    /// when any request is not a read.
+    pub fn write_trace(path: &str, arrivals: &[Arrival]) -> Result<(), String> {
+        let mut trace_file =
+            File::create(path).map_err(|e| format!("Could not create trace {}: {}", path, e))?;
+
+        let blocks = !arrivals.is_empty() && arrivals.iter().all(|a| a.task.get_lba().is_some());
+        let operations = arrivals
+            .iter()
+            .any(|a| a.task.get_operation() != Operation::READ);
+        let lengths = operations || arrivals.iter().any(|a| *a.task.get_length() > 1);
+        let heads = lengths || arrivals.iter().any(|a| *a.task.get_head() > 0);
+        let mut content = String::from(if blocks {
+            "time,lba,length,owner"
+        } else if lengths {
+            "time,track,angle,owner,head,length"
+        } else if heads {
+            "time,track,angle,owner,head"
+        } else {
+            "time,track,angle,owner"
+        });
+        content.push_str(if operations { ",operation\n" } else { "\n" });
+        for arrival in arrivals.iter() {
+            let task = &arrival.task;
+            if blocks {
+                content.push_str(&format!(
+                    "{},{},{},{}",
+                    arrival.time,
+                    task.get_lba().unwrap(),
+                    task.get_length(),
+                    arrival.owner
+                ));
+            } else {
+                content.push_str(&format!(
+                    "{},{},{},{}",
+                    arrival.time,
+                    task.get_track(),
+                    task.get_angle(),
+                    arrival.owner
+                ));
+                if heads {
+                    content.push_str(&format!(",{}", task.get_head()));
+                }
+                if lengths {
+                    content.push_str(&format!(",{}", task.get_length()));
+                }
+            }
+            if operations {
+                content.push_str(&format!(",{}", get_trace_operation(task)));
+            }
+            content.push('\n');
+        }
+
+        trace_file
+            .write_all(content.as_bytes())
+            .map_err(|e| format!("Could not write trace {}: {}", path, e))
+    }
+
+    /// Reads a `time,track,angle[,owner[,head[,length]]]` trace, or a
+    /// `time,lba,length[,owner]` one when its header names an `lba` column,
+    /// for a disk of `cylinders` cylinders laid out by `geometry`. Owners and
+    /// heads default to 0 and lengths to 1. A row may end with the name of
+    /// its operation, or `fua` for a write with forced unit access, otherwise
+    /// it is a read. Rows the disk has no sectors for are errors.
+    pub fn read_trace(
+        path: &str,
+        geometry: &Geometry,
+        cylinders: u32,
+    ) -> Result<Vec<Arrival>, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read trace {}: {}", path, e))?;
+        let mapper = geometry.get_mapper(cylinders);
+
+        let mut blocks = false;
+        let mut arrivals: Vec<Arrival> = Vec::new();
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            let location = format!("{}:{}", path, line_number + 1);
+            if line.starts_with("time") {
+                blocks = line.split(',').any(|column| column.trim() == "lba");
+                continue;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            let (operation, fua) = match fields.last() {
+                Some(last) if last.parse::<u64>().is_err() => {
+                    let operation = if last.eq_ignore_ascii_case(FUA) {
+                        (Operation::WRITE, true)
+                    } else {
+                        (
+                            Operation::from_name(last)
+                                .map_err(|e| format!("{}: {}", location, e))?,
+                            false,
+                        )
+                    };
+                    fields.pop();
+                    operation
+                }
+                _ => (Operation::READ, false),
+            };
+            let fields: Result<Vec<u64>, _> = fields.iter().map(|f| f.parse::<u64>()).collect();
+            let (expected, columns) = if blocks {
+                ("time,lba,length[,owner][,operation]", 3..=4)
+            } else {
+                (
+                    "time,track,angle[,owner[,head[,length]]][,operation]",
+                    3..=6,
+                )
+            };
+            let fields = match fields {
+                Ok(fields) if columns.contains(&fields.len()) => fields,
+                _ => {
+                    return Err(format!(
+                        "{}: expected `{}`, found `{}`",
+                        location, expected, line
+                    ))
+                }
+            };
+            let field = |index: usize, default: u32| match fields.get(index) {
+                Some(&value) => u32::try_from(value)
+                    .map_err(|_| format!("{}: {} does not fit in 32 bits", location, value)),
+                None => Ok(default),
+            };
+
+            let task_id = arrivals.len() as u32 + 1;
+            let time = field(0, 0)?;
+            let mut task = if blocks {
+                Task::from_lba(task_id, fields[1], field(2, 1)?, mapper.as_ref())
+                    .map_err(|e| format!("{}: {}", location, e))?
+            } else {
+                let (track, head, angle, length) =
+                    (field(1, 0)?, field(4, 0)?, field(2, 0)?, field(5, 1)?);
+                if track == 0 || track > cylinders {
+                    return Err(format!(
+                        "{}: track must be between 1 and {}",
+                        location, cylinders
+                    ));
+                }
+                if head >= geometry.heads {
+                    return Err(format!(
+                        "{}: head must be below {}, the number of heads",
+                        location, geometry.heads
+                    ));
+                }
+                if angle >= 360 {
+                    return Err(format!("{}: angle must be below 360", location));
+                }
+                if length == 0 {
+                    return Err(format!("{}: length must be at least 1", location));
+                }
+                Task::with_length(task_id, track, head, angle, length)
+                    .map_err(|e| format!("{}: {}", location, e))?
+            };
+
+            task.set_operation(operation);
+            task.set_fua(fua);
+
+            arrivals.push(Arrival {
+                time: time,
+                task: task,
+                owner: field(3, 0)?,
+            });
+        }
+
+        arrivals.sort_by_key(|arrival| arrival.time);
+        Ok(arrivals)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::disk::geometry::geometry::Geometry;
+
+        fn hotspot(center: u32, width: u32, probability: f64) -> SpatialDistribution {
+            SpatialDistribution::HOTSPOT {
+                center: center,
+                width: width,
+                probability: probability,
+            }
+        }
+
+        #[test]
+        fn arrival_processes_need_a_usable_rate() {
+            for probability in [0.0, -0.5, 1.5, f64::NAN] {
+                assert!(ArrivalProcess::BERNOULLI {
+                    probability: probability
+                }
+                .validate()
+                .is_err());
+            }
+            assert!(ArrivalProcess::BERNOULLI { probability: 1.0 }
+                .validate()
+                .is_ok());
+            assert!(ArrivalProcess::PERIODIC { interval: 0 }.validate().is_err());
+            assert!(ArrivalProcess::PERIODIC { interval: 3 }.validate().is_ok());
+        }
+
+        #[test]
+        fn hotspots_have_to_be_on_the_disk() {
+            assert!(hotspot(0, 10, 0.5).validate(100).is_err());
+            assert!(hotspot(101, 10, 0.5).validate(100).is_err());
+            assert!(hotspot(50, 10, 1.5).validate(100).is_err());
+            assert!(hotspot(100, 10, 0.5).validate(100).is_ok());
+            assert!(SpatialDistribution::UNIFORM.validate(1).is_ok());
+        }
+
+        #[test]
+        fn hotspot_tracks_stay_on_the_disk() {
+            let mut rng = StdRng::seed_from_u64(2);
+            for distribution in [hotspot(1, 50, 1.0), hotspot(100, 1000, 1.0)] {
+                for _ in 0..1000 {
+                    let track = distribution.sample_track(&mut rng, 100);
+                    assert!((1..=100).contains(&track));
+                }
+            }
+
+            let narrow = hotspot(40, 4, 1.0);
+            for _ in 0..1000 {
+                assert!((38..=42).contains(&narrow.sample_track(&mut rng, 100)));
+            }
+        }
+
+        /// Writes `content` to a trace file and reads it back for a two head
+        /// disk with 10 cylinders.
+        fn trace(content: &str) -> Result<Vec<Arrival>, String> {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("trace.csv");
+            fs::write(&path, content).unwrap();
+
+            let mut geometry = Geometry::default();
+            geometry.heads = 2;
+
+            read_trace(path.to_str().unwrap(), &geometry, 10)
+        }
+
+        #[test]
+        fn traces_are_read_in_arrival_order() {
+            let arrivals = trace(
+                "time,track,angle,owner,head,length\n30,2,90,1,1,4,write\n# comment\n10,10,0\n",
+            )
+            .unwrap();
+
+            assert_eq!(arrivals.len(), 2);
+            assert_eq!(arrivals[0].time, 10);
+            assert_eq!(*arrivals[0].task.get_track(), 10);
+            assert_eq!(*arrivals[0].task.get_length(), 1);
+            assert!(arrivals[0].task.get_operation() == Operation::READ);
+            assert_eq!(*arrivals[1].task.get_angle(), 90);
+            assert_eq!(*arrivals[1].task.get_head(), 1);
+            assert_eq!(*arrivals[1].task.get_length(), 4);
+            assert_eq!(arrivals[1].owner, 1);
+            assert!(arrivals[1].task.get_operation() == Operation::WRITE);
+        }
+
+        #[test]
+        fn traces_by_block_are_placed_by_the_mapping() {
+            let arrivals = trace("time,lba,length\n5,720,2,fua\n").unwrap();
+
+            assert_eq!(arrivals[0].task.get_lba(), Some(720));
+            assert_eq!(*arrivals[0].task.get_track(), 2);
+            assert_eq!(*arrivals[0].task.get_length(), 2);
+            assert!(arrivals[0].task.is_fua());
+
+            let error = trace("time,lba,length\n5,7199,2\n").err().unwrap();
+            assert!(error.contains(":2: "), "{}", error);
+        }
+
+        #[test]
+        fn trace_rows_off_the_disk_are_rejected_with_their_line() {
+            for (row, message) in [
+                ("1,0,10", "track must be between 1 and 10"),
+                ("1,11,10", "track must be between 1 and 10"),
+                ("1,3,10,0,2", "head must be below 2"),
+                ("1,3,360", "angle must be below 360"),
+                ("1,3,10,0,0,0", "length must be at least 1"),
+                ("1,3", "expected `time,track,angle"),
+                ("1,3,10,seek", "Unknown operation"),
+            ] {
+                let error = trace(&format!("time,track,angle\n\n{}\n", row))
+                    .err()
+                    .unwrap();
+                assert!(error.contains(":3: "), "{}", error);
+                assert!(error.contains(message), "{}", error);
+            }
+        }
+
+        #[test]
+        fn written_traces_replay_the_same_workload() {
+            let arrivals = WorkloadConfig::new(20, None)
+                .generate(100, 1, 3)
+                .unwrap();
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("trace.csv");
+            write_trace(path.to_str().unwrap(), &arrivals).unwrap();
+
+            let replayed = read_trace(path.to_str().unwrap(), &Geometry::default(), 100).unwrap();
+            assert_eq!(replayed.len(), arrivals.len());
+            for (replayed, arrival) in replayed.iter().zip(arrivals.iter()) {
+                assert_eq!(replayed.time, arrival.time);
+                assert_eq!(replayed.task.get_track(), arrival.task.get_track());
+                assert_eq!(replayed.task.get_angle(), arrival.task.get_angle());
+            }
+        }
+
+        #[test]
+        fn workloads_without_sectors_are_rejected() {
+            let mut workload = WorkloadConfig::new(5, None);
+            workload.length = 0;
+            assert!(workload.generate(100, 1, 3).is_err());
+        }
+
+        #[test]
+        fn workloads_are_reproducible_from_their_seed() {
+            let workload = WorkloadConfig::new(50, None);
+            let tasks = |seed| -> Vec<(u32, u32, u32)> {
+                workload
+                    .generate(100, 1, seed)
+                    .unwrap()
+                    .iter()
+                    .map(|arrival| {
+                        (
+                            arrival.time,
+                            *arrival.task.get_track(),
+                            *arrival.task.get_angle(),
+                        )
+                    })
+                    .collect()
+            };
+
+            assert_eq!(tasks(5).len(), 50);
+            assert_eq!(tasks(5), tasks(5));
+            assert_ne!(tasks(5), tasks(6));
+        }
+    }
+}